@@ -118,6 +118,7 @@ pub unsafe fn discoverPetriNetAlphaPPPFromActProjAuto<'local>(
             .map(|(i, a)| (a.clone(), i))
             .collect(),
         traces: Vec::new(),
+        ..Default::default()
     };
     log_proj.traces = variants
         .iter()
@@ -163,6 +164,7 @@ pub unsafe fn testActProjPassPerformance<'local>(
             .map(|(i, a)| (a.clone(), i))
             .collect(),
         traces: Vec::new(),
+        ..Default::default()
     };
 
     log_proj.traces = variants
@@ -203,6 +205,7 @@ pub unsafe fn discoverPetriNetAlphaPPPFromActProj<'local>(
             .map(|(i, a)| (a.clone(), i))
             .collect(),
         traces: Vec::new(),
+        ..Default::default()
     };
 
     log_proj.traces = variants
@@ -1,5 +1,10 @@
 use process_mining::{
     alphappp::full::{alphappp_discover_petri_net_with_timing_fn, AlphaPPPConfig},
+    dfg::{
+        performance::DFGEdgeLabel,
+        text_export::{export_dfg_to_dot, export_dfg_to_mermaid},
+        DirectlyFollowsGraph,
+    },
     event_log::{
         activity_projection::EventLogActivityProjection,
         constants::ACTIVITY_NAME,
@@ -7,7 +12,7 @@ use process_mining::{
         ocel::xml_ocel_import::import_ocel_xml_slice,
         stream_xes::{stream_xes_slice, stream_xes_slice_gz},
     },
-    petri_net::image_export::{export_petri_net_to_dot_graph, graph_to_dot},
+    petri_net::text_export::{export_petri_net_to_dot, export_petri_net_to_mermaid},
     PetriNet, OCEL,
 };
 use wasm_bindgen::prelude::*;
@@ -200,6 +205,34 @@ pub unsafe fn wasm_destroy_ocel_pointer(addr: usize) -> JsValue {
 #[wasm_bindgen]
 pub fn wasm_petri_net_dot(pn: &str) -> String {
     let pn: PetriNet = serde_json::from_str(pn).unwrap();
-    let g = export_petri_net_to_dot_graph(&pn, None);
-    graph_to_dot(&g)
+    export_petri_net_to_dot(&pn)
+}
+
+#[wasm_bindgen]
+pub fn wasm_petri_net_mermaid(pn: &str) -> String {
+    let pn: PetriNet = serde_json::from_str(pn).unwrap();
+    export_petri_net_to_mermaid(&pn)
+}
+
+/// Filter a [`DirectlyFollowsGraph`] (given as JSON) to the top activities and paths
+///
+/// Returns the JSON-serialized `DFGFilterResult` (i.e., the filtered DFG and the removed activities and paths)
+#[wasm_bindgen]
+pub fn wasm_filter_dfg(dfg_json: &str, activity_fraction: f64, path_fraction: f64) -> String {
+    console_error_panic_hook::set_once();
+    let dfg: DirectlyFollowsGraph<'_> = serde_json::from_str(dfg_json).unwrap();
+    let res = dfg.filter_activities_and_paths(activity_fraction, path_fraction);
+    serde_json::to_string(&res).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn wasm_dfg_dot(dfg_json: &str) -> String {
+    let dfg: DirectlyFollowsGraph<'_> = serde_json::from_str(dfg_json).unwrap();
+    export_dfg_to_dot(&dfg, DFGEdgeLabel::Frequency)
+}
+
+#[wasm_bindgen]
+pub fn wasm_dfg_mermaid(dfg_json: &str) -> String {
+    let dfg: DirectlyFollowsGraph<'_> = serde_json::from_str(dfg_json).unwrap();
+    export_dfg_to_mermaid(&dfg, DFGEdgeLabel::Frequency)
 }
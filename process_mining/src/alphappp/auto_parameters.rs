@@ -1,6 +1,9 @@
 use crate::{
     event_log::activity_projection::EventLogActivityProjection,
-    petri_net::petri_net_struct::{PetriNet, Transition},
+    petri_net::{
+        adjacency::PetriNetAdjacency,
+        petri_net_struct::{PetriNet, Transition, TransitionID},
+    },
 };
 
 use super::full::{alphappp_discover_petri_net, AlphaPPPConfig};
@@ -142,62 +145,45 @@ pub fn alphappp_discover_with_auto_parameters(
 }
 
 fn score_discovered_pn(pn: &PetriNet, config: &AlphaPPPConfig) -> f32 {
-    fn is_transition_well_connected(pn: &PetriNet, t: &Transition) -> bool {
+    fn is_transition_well_connected(
+        pn: &PetriNet,
+        adjacency: &PetriNetAdjacency,
+        t: &Transition,
+    ) -> bool {
         if t.label.is_some() {
-            let preset_connected = pn
-                .preset_of_transition(t.into())
-                .into_iter()
-                .filter(|p| {
-                    pn.is_in_initial_marking(p)
-                        || pn.is_in_a_final_marking(p)
-                        || pn
-                            .preset_of_place(*p)
-                            .into_iter()
-                            .filter(|ot_id| {
-                                pn.transitions
-                                    .get(&ot_id.get_uuid())
-                                    .unwrap()
-                                    .label
-                                    .is_some()
-                            })
-                            .count()
-                            >= 1
-                })
-                .count()
-                >= 1;
+            let is_labelled = |ot_id: &TransitionID| {
+                pn.transitions
+                    .get(&ot_id.get_uuid())
+                    .unwrap()
+                    .label
+                    .is_some()
+            };
+            let preset_connected = adjacency.preset_of_transition(t.into()).any(|p| {
+                pn.is_in_initial_marking(&p)
+                    || pn.is_in_a_final_marking(&p)
+                    || adjacency
+                        .preset_of_place(p)
+                        .any(|ot_id| is_labelled(&ot_id))
+            });
 
-            let postset_connected = pn
-                .postset_of_transition(t.into())
-                .into_iter()
-                .filter(|p| {
-                    pn.is_in_initial_marking(p)
-                        || pn.is_in_a_final_marking(p)
-                        || pn
-                            .postset_of_place(*p)
-                            .into_iter()
-                            .filter(|ot_id| {
-                                pn.transitions
-                                    .get(&ot_id.get_uuid())
-                                    .unwrap()
-                                    .label
-                                    .is_some()
-                            })
-                            .count()
-                            >= 1
-                })
-                .count()
-                >= 1;
+            let postset_connected = adjacency.postset_of_transition(t.into()).any(|p| {
+                pn.is_in_initial_marking(&p)
+                    || pn.is_in_a_final_marking(&p)
+                    || adjacency
+                        .postset_of_place(p)
+                        .any(|ot_id| is_labelled(&ot_id))
+            });
 
             preset_connected && postset_connected
         } else {
             false
         }
     }
+    let adjacency = pn.adjacency();
     let num_disconnected_trans = pn
         .transitions
-        .clone()
-        .into_iter()
-        .filter(|(_, t)| !is_transition_well_connected(pn, t))
+        .values()
+        .filter(|t| !is_transition_well_connected(pn, &adjacency, t))
         .count();
 
     return config.fitness_thresh
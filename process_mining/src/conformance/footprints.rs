@@ -0,0 +1,398 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dfg::DirectlyFollowsGraph;
+use crate::event_log::activity_projection::{ActivityProjectionDFG, EventLogActivityProjection};
+use crate::petri_net::language::LanguageError;
+use crate::PetriNet;
+
+/// Relation between two activities in a [`Footprint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FootprintRelation {
+    /// Causality (`a → b`): `a` is directly followed by `b`, but never the other way around
+    Causality,
+    /// Reverse causality (`a ← b`): `b` is directly followed by `a`, but never the other way around
+    ReverseCausality,
+    /// Parallel (`a || b`): `a` is directly followed by `b` and vice versa
+    Parallel,
+    /// Choice (`a # b`): `a` is never directly followed by `b` and vice versa
+    Choice,
+}
+
+impl FootprintRelation {
+    /// Derive the relation from whether `a` is directly followed by `b` (`a_to_b`) and vice versa (`b_to_a`)
+    pub fn from_directly_follows(a_to_b: bool, b_to_a: bool) -> Self {
+        match (a_to_b, b_to_a) {
+            (true, true) => FootprintRelation::Parallel,
+            (true, false) => FootprintRelation::Causality,
+            (false, true) => FootprintRelation::ReverseCausality,
+            (false, false) => FootprintRelation::Choice,
+        }
+    }
+
+    /// Get the symbol of the relation (`→`, `←`, `||` or `#`)
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            FootprintRelation::Causality => "→",
+            FootprintRelation::ReverseCausality => "←",
+            FootprintRelation::Parallel => "||",
+            FootprintRelation::Choice => "#",
+        }
+    }
+}
+
+impl Display for FootprintRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Footprint matrix of a process (e.g., an event log or a process model)
+///
+/// For each pair of activities, the footprint contains one [`FootprintRelation`],
+/// which is derived from the directly-follows relation of the process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Footprint {
+    /// All activities (sorted by name)
+    ///
+    /// The index of an activity is used as row/column index in [`Footprint::relations`]
+    pub activities: Vec<String>,
+    /// Matrix of relations: `relations[a][b]` is the relation between activity `a` and `b`
+    pub relations: Vec<Vec<FootprintRelation>>,
+}
+
+impl Footprint {
+    /// Create a [`Footprint`] from a set of activities and a set of directly-follows relations between them
+    ///
+    /// Activities occurring in the relations are added automatically.
+    pub fn from_directly_follows<'b, A, R>(activities: A, df_relations: R) -> Self
+    where
+        A: IntoIterator<Item = &'b str>,
+        R: IntoIterator<Item = (&'b str, &'b str)>,
+    {
+        let df_relations: HashSet<(&str, &str)> = df_relations.into_iter().collect();
+        let mut acts: HashSet<&str> = activities.into_iter().collect();
+        acts.extend(df_relations.iter().flat_map(|(a, b)| [*a, *b]));
+        let mut activities: Vec<String> = acts.into_iter().map(|a| a.to_string()).collect();
+        activities.sort();
+        let relations = activities
+            .iter()
+            .map(|a| {
+                activities
+                    .iter()
+                    .map(|b| {
+                        FootprintRelation::from_directly_follows(
+                            df_relations.contains(&(a.as_str(), b.as_str())),
+                            df_relations.contains(&(b.as_str(), a.as_str())),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            activities,
+            relations,
+        }
+    }
+
+    /// Create a [`Footprint`] from an [`ActivityProjectionDFG`]
+    ///
+    /// The activity names are given by `activities` (e.g., [`EventLogActivityProjection::activities`]),
+    /// which are indexed by the nodes of the DFG.
+    pub fn from_activity_projection_dfg(
+        dfg: &ActivityProjectionDFG,
+        activities: &[String],
+    ) -> Self {
+        Self::from_directly_follows(
+            dfg.nodes.iter().map(|n| activities[*n].as_str()),
+            dfg.edges
+                .iter()
+                .filter(|(_, w)| **w > 0)
+                .map(|((a, b), _)| (activities[*a].as_str(), activities[*b].as_str())),
+        )
+    }
+
+    /// Create a [`Footprint`] from an [`EventLogActivityProjection`]
+    ///
+    /// Only activities which occur in at least one trace are considered.
+    pub fn from_activity_projection(log: &EventLogActivityProjection) -> Self {
+        let dfg = ActivityProjectionDFG::from_event_log_projection(log);
+        let occurring: HashSet<usize> = log
+            .traces
+            .iter()
+            .filter(|(_, w)| *w > 0)
+            .flat_map(|(t, _)| t.iter().copied())
+            .collect();
+        Self::from_directly_follows(
+            occurring.iter().map(|a| log.activities[*a].as_str()),
+            dfg.edges
+                .keys()
+                .map(|(a, b)| (log.activities[*a].as_str(), log.activities[*b].as_str())),
+        )
+    }
+
+    /// Create a [`Footprint`] from a [`DirectlyFollowsGraph`]
+    pub fn from_dfg(dfg: &DirectlyFollowsGraph<'_>) -> Self {
+        Self::from_directly_follows(
+            dfg.activities.keys().map(|a| a.as_str()),
+            dfg.directly_follows_relations
+                .keys()
+                .map(|(a, b)| (a.as_ref(), b.as_ref())),
+        )
+    }
+
+    /// Create a [`Footprint`] from the language of a labelled [`PetriNet`]
+    ///
+    /// All firing sequences starting in the initial marking and producing at most `max_length` labels are explored
+    /// (silent transitions, i.e., transitions without a label, can fire arbitrarily often).
+    /// Two labels are in a directly-follows relation if they are fired consecutively in such a sequence,
+    /// ignoring silent transitions in between.
+    ///
+    /// If no initial marking is set, the empty marking is used.
+    /// For nets with an unbounded or very large language, the result is an approximation.
+    /// Fails if more than `max_states` markings have to be explored
+    /// (see [`DEFAULT_MAX_STATES`](crate::petri_net::state_space::DEFAULT_MAX_STATES) for a reasonable default).
+    pub fn from_petri_net(
+        net: &PetriNet,
+        max_length: usize,
+        max_states: usize,
+    ) -> Result<Self, LanguageError> {
+        let df_relations = net.directly_follows_relations(max_length, max_states)?;
+        Ok(Self::from_directly_follows(
+            net.transitions.values().filter_map(|t| t.label.as_deref()),
+            df_relations.iter().map(|(a, b)| (a.as_str(), b.as_str())),
+        ))
+    }
+
+    /// Get the relation between activity `a` and `b`
+    ///
+    /// Returns `None` if any of the activities is not part of the footprint
+    pub fn get_relation(&self, a: &str, b: &str) -> Option<FootprintRelation> {
+        let a = self
+            .activities
+            .binary_search_by(|x| x.as_str().cmp(a))
+            .ok()?;
+        let b = self
+            .activities
+            .binary_search_by(|x| x.as_str().cmp(b))
+            .ok()?;
+        Some(self.relations[a][b])
+    }
+
+    /// Get the relation between activity `a` and `b`, where activities not part of the footprint are in a choice relation
+    fn get_relation_or_choice(&self, a: &str, b: &str) -> FootprintRelation {
+        self.get_relation(a, b).unwrap_or(FootprintRelation::Choice)
+    }
+}
+
+impl Display for Footprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .activities
+            .iter()
+            .map(|a| a.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(2);
+        write!(f, "{:width$}", "")?;
+        for a in &self.activities {
+            write!(f, " | {a:width$}")?;
+        }
+        writeln!(f)?;
+        for (a, row) in self.activities.iter().zip(&self.relations) {
+            write!(f, "{a:width$}")?;
+            for rel in row {
+                write!(f, " | {:width$}", rel.symbol())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Mismatching cell of two [`Footprint`]s (see [`compare_footprints`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FootprintMismatch {
+    /// Activity of the row
+    pub from: String,
+    /// Activity of the column
+    pub to: String,
+    /// Relation in the first footprint
+    pub relation_a: FootprintRelation,
+    /// Relation in the second footprint
+    pub relation_b: FootprintRelation,
+}
+
+/// Result of comparing two [`Footprint`]s (see [`compare_footprints`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FootprintComparison {
+    /// All activities of both footprints (sorted by name)
+    pub activities: Vec<String>,
+    /// All mismatching cells (sorted by row and column activity)
+    pub mismatches: Vec<FootprintMismatch>,
+    /// Number of compared cells (i.e., the number of activities squared)
+    pub num_cells: usize,
+    /// Conformance score between `0.0` and `1.0`: The fraction of matching cells
+    pub conformance: f64,
+}
+
+/// Compare two [`Footprint`]s (e.g., of an event log and of a process model)
+///
+/// All cells of the footprint matrix over the activities of both footprints are compared.
+/// Activities missing in one of the footprints are considered to be in a choice relation (`#`) with all other activities.
+///
+/// The conformance score is the fraction of matching cells (i.e., `1.0` if the footprints are identical).
+pub fn compare_footprints(a: &Footprint, b: &Footprint) -> FootprintComparison {
+    let mut activities: Vec<String> = a
+        .activities
+        .iter()
+        .chain(b.activities.iter())
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    activities.sort();
+    let mut mismatches = Vec::new();
+    for from in &activities {
+        for to in &activities {
+            let relation_a = a.get_relation_or_choice(from, to);
+            let relation_b = b.get_relation_or_choice(from, to);
+            if relation_a != relation_b {
+                mismatches.push(FootprintMismatch {
+                    from: from.clone(),
+                    to: to.clone(),
+                    relation_a,
+                    relation_b,
+                });
+            }
+        }
+    }
+    let num_cells = activities.len() * activities.len();
+    let conformance = if num_cells == 0 {
+        1.0
+    } else {
+        1.0 - mismatches.len() as f64 / num_cells as f64
+    };
+    FootprintComparison {
+        activities,
+        mismatches,
+        num_cells,
+        conformance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::petri_net_struct::{ArcType, Marking, PlaceID, TransitionID};
+    use crate::petri_net::state_space::DEFAULT_MAX_STATES;
+    use uuid::Uuid;
+
+    fn projection(traces: &[&[&str]]) -> EventLogActivityProjection {
+        let mut proj = EventLogActivityProjection::default();
+        for t in traces {
+            let trace = t
+                .iter()
+                .map(|a| match proj.act_to_index.get(*a) {
+                    Some(i) => *i,
+                    None => {
+                        proj.activities.push(a.to_string());
+                        proj.act_to_index
+                            .insert(a.to_string(), proj.activities.len() - 1);
+                        proj.activities.len() - 1
+                    }
+                })
+                .collect();
+            proj.traces.push((trace, 1));
+        }
+        proj
+    }
+
+    /// Net with `a` followed by `b` and `c` in parallel, followed by `d`
+    fn parallel_net() -> PetriNet {
+        let mut net = PetriNet::new();
+        let places: Vec<PlaceID> = (0..6).map(|_| net.add_place(None)).collect();
+        let a = net.add_transition(Some("a".to_string()), None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        let c = net.add_transition(Some("c".to_string()), None);
+        let tau = net.add_transition(None, None);
+        let d = net.add_transition(Some("d".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(places[0], a), None);
+        net.add_arc(ArcType::transition_to_place(a, places[1]), None);
+        net.add_arc(ArcType::transition_to_place(a, places[2]), None);
+        net.add_arc(ArcType::place_to_transition(places[1], b), None);
+        net.add_arc(ArcType::place_to_transition(places[2], c), None);
+        net.add_arc(ArcType::transition_to_place(b, places[3]), None);
+        net.add_arc(ArcType::transition_to_place(c, places[4]), None);
+        net.add_arc(ArcType::place_to_transition(places[3], tau), None);
+        net.add_arc(ArcType::place_to_transition(places[4], tau), None);
+        net.add_arc(ArcType::transition_to_place(tau, places[5]), None);
+        net.add_arc(ArcType::place_to_transition(places[5], d), None);
+        net.initial_marking = Some(Marking::from([(places[0], 1)]));
+        net
+    }
+
+    #[test]
+    fn test_footprint_from_projection() {
+        let log = projection(&[&["a", "b", "c", "d"], &["a", "c", "b", "d"]]);
+        let fp = Footprint::from_activity_projection(&log);
+        assert_eq!(fp.activities, vec!["a", "b", "c", "d"]);
+        assert_eq!(
+            fp.get_relation("a", "b"),
+            Some(FootprintRelation::Causality)
+        );
+        assert_eq!(
+            fp.get_relation("b", "a"),
+            Some(FootprintRelation::ReverseCausality)
+        );
+        assert_eq!(fp.get_relation("b", "c"), Some(FootprintRelation::Parallel));
+        assert_eq!(fp.get_relation("a", "d"), Some(FootprintRelation::Choice));
+        assert_eq!(fp.get_relation("a", "x"), None);
+        assert!(fp.to_string().contains("||"));
+    }
+
+    #[test]
+    fn test_footprint_from_petri_net() {
+        let log = projection(&[&["a", "b", "c", "d"], &["a", "c", "b", "d"]]);
+        let log_fp = Footprint::from_activity_projection(&log);
+        let net_fp = Footprint::from_petri_net(&parallel_net(), 10, DEFAULT_MAX_STATES).unwrap();
+        assert_eq!(log_fp, net_fp);
+        let cmp = compare_footprints(&log_fp, &net_fp);
+        assert!(cmp.mismatches.is_empty());
+        // Arcs to nodes which are not part of the net are ignored
+        let mut net = parallel_net();
+        net.add_arc(
+            ArcType::transition_to_place(TransitionID(Uuid::new_v4()), PlaceID(Uuid::new_v4())),
+            None,
+        );
+        assert_eq!(
+            Footprint::from_petri_net(&net, 10, DEFAULT_MAX_STATES).unwrap(),
+            net_fp
+        );
+        assert_eq!(cmp.conformance, 1.0);
+    }
+
+    #[test]
+    fn test_compare_footprints() {
+        let log = projection(&[&["a", "b", "c", "d"], &["a", "c", "e"]]);
+        let log_fp = Footprint::from_activity_projection(&log);
+        let net_fp = Footprint::from_petri_net(&parallel_net(), 10, DEFAULT_MAX_STATES).unwrap();
+        let cmp = compare_footprints(&log_fp, &net_fp);
+        assert_eq!(cmp.activities.len(), 5);
+        assert_eq!(cmp.num_cells, 25);
+        // b/c, b/d and c/e differ (2 cells each)
+        assert_eq!(cmp.mismatches.len(), 6);
+        assert_eq!(
+            cmp.mismatches[0],
+            FootprintMismatch {
+                from: "b".to_string(),
+                to: "c".to_string(),
+                relation_a: FootprintRelation::Causality,
+                relation_b: FootprintRelation::Parallel,
+            }
+        );
+        assert!((cmp.conformance - 19.0 / 25.0).abs() < 1e-9);
+    }
+}
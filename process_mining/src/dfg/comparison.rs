@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::event_log::event_log_struct::EventLogClassifier;
+use crate::EventLog;
+
+use super::dfg_struct::DirectlyFollowsGraph;
+use super::performance::DurationStatistics;
+
+/// Default significance level used for [`compare_dfgs`]
+pub const DEFAULT_SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Difference of one element (activity or directly-follows relation) between two [`DirectlyFollowsGraph`]s
+///
+/// The first graph (`a`) is considered the baseline, so all differences are computed as `b - a`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DFGElementDifference {
+    /// Frequency in the first graph
+    pub frequency_a: u32,
+    /// Frequency in the second graph
+    pub frequency_b: u32,
+    /// Absolute frequency difference (`frequency_b - frequency_a`)
+    pub absolute_difference: i64,
+    /// Relative frequency in the first graph (i.e., average number of occurrences per case)
+    pub relative_frequency_a: f64,
+    /// Relative frequency in the second graph (i.e., average number of occurrences per case)
+    pub relative_frequency_b: f64,
+    /// Relative frequency difference (`relative_frequency_b - relative_frequency_a`)
+    pub relative_difference: f64,
+    /// Difference of the mean durations in milliseconds (`mean_b - mean_a`)
+    ///
+    /// For directly-follows relations, these are the durations between the events.
+    /// For activities, these are the sojourn times.
+    /// Only available if both graphs have performance annotations containing this element.
+    pub mean_duration_difference: Option<f64>,
+    /// z-score of the difference in occurrence rates (per case)
+    pub z_score: f64,
+    /// Two-sided p-value of the difference in occurrence rates (per case)
+    pub p_value: f64,
+    /// Whether the difference is statistically significant (i.e., `p_value` is below the significance level)
+    pub significant: bool,
+}
+
+impl DFGElementDifference {
+    fn new(
+        frequency_a: u32,
+        frequency_b: u32,
+        (num_cases_a, num_cases_b): (u32, u32),
+        durations: (Option<&DurationStatistics>, Option<&DurationStatistics>),
+        significance_level: f64,
+    ) -> Self {
+        let rate = |freq: u32, cases: u32| {
+            if cases == 0 {
+                0.0
+            } else {
+                freq as f64 / cases as f64
+            }
+        };
+        let relative_frequency_a = rate(frequency_a, num_cases_a);
+        let relative_frequency_b = rate(frequency_b, num_cases_b);
+        let relative_difference = relative_frequency_b - relative_frequency_a;
+        // Normal approximation of the difference of two Poisson rates
+        let variance = rate(frequency_a, num_cases_a) / (num_cases_a.max(1) as f64)
+            + rate(frequency_b, num_cases_b) / (num_cases_b.max(1) as f64);
+        let z_score = if variance > 0.0 {
+            relative_difference / variance.sqrt()
+        } else {
+            0.0
+        };
+        let p_value = 2.0 * (1.0 - standard_normal_cdf(z_score.abs()));
+        Self {
+            frequency_a,
+            frequency_b,
+            absolute_difference: frequency_b as i64 - frequency_a as i64,
+            relative_frequency_a,
+            relative_frequency_b,
+            relative_difference,
+            mean_duration_difference: match durations {
+                (Some(a), Some(b)) => Some(b.mean - a.mean),
+                _ => None,
+            },
+            z_score,
+            p_value,
+            significant: p_value < significance_level,
+        }
+    }
+}
+
+/// Comparison of two [`DirectlyFollowsGraph`]s (see [`compare_dfgs`])
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DFGComparison {
+    /// Number of cases of the first graph (i.e., sum of start activity frequencies)
+    pub num_cases_a: u32,
+    /// Number of cases of the second graph (i.e., sum of start activity frequencies)
+    pub num_cases_b: u32,
+    /// Differences of all activities occurring in any of the two graphs
+    pub activities: HashMap<String, DFGElementDifference>,
+    /// Differences of all directly-follows relations occurring in any of the two graphs
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub directly_follows_relations: HashMap<(String, String), DFGElementDifference>,
+}
+
+/// Compare two [`DirectlyFollowsGraph`]s
+///
+/// The first graph (`a`) is considered the baseline, so all differences are computed as `b - a`.
+/// Frequencies are normalized by the number of cases of each graph (i.e., the sum of start activity frequencies).
+///
+/// Significance is determined by comparing the per-case occurrence rates of each element using a two-sided test
+/// (normal approximation of the difference of two Poisson rates) at the passed `significance_level` (e.g., [`DEFAULT_SIGNIFICANCE_LEVEL`]).
+pub fn compare_dfgs(
+    a: &DirectlyFollowsGraph<'_>,
+    b: &DirectlyFollowsGraph<'_>,
+    significance_level: f64,
+) -> DFGComparison {
+    let num_cases = (
+        a.start_activities.values().sum(),
+        b.start_activities.values().sum(),
+    );
+    let activities: HashSet<&String> = a.activities.keys().chain(b.activities.keys()).collect();
+    let activities = activities
+        .into_iter()
+        .map(|act| {
+            let durations = (
+                a.performance
+                    .as_ref()
+                    .and_then(|p| p.sojourn_times.get(act)),
+                b.performance
+                    .as_ref()
+                    .and_then(|p| p.sojourn_times.get(act)),
+            );
+            (
+                act.clone(),
+                DFGElementDifference::new(
+                    a.activities.get(act).copied().unwrap_or_default(),
+                    b.activities.get(act).copied().unwrap_or_default(),
+                    num_cases,
+                    durations,
+                    significance_level,
+                ),
+            )
+        })
+        .collect();
+    let df_relations: HashSet<(String, String)> = a
+        .directly_follows_relations
+        .keys()
+        .chain(b.directly_follows_relations.keys())
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect();
+    let directly_follows_relations = df_relations
+        .into_iter()
+        .map(|df| {
+            let key = (df.0.as_str().into(), df.1.as_str().into());
+            let durations = (
+                a.performance.as_ref().and_then(|p| p.df_durations.get(&df)),
+                b.performance.as_ref().and_then(|p| p.df_durations.get(&df)),
+            );
+            let diff = DFGElementDifference::new(
+                a.directly_follows_relations
+                    .get(&key)
+                    .copied()
+                    .unwrap_or_default(),
+                b.directly_follows_relations
+                    .get(&key)
+                    .copied()
+                    .unwrap_or_default(),
+                num_cases,
+                durations,
+                significance_level,
+            );
+            (df, diff)
+        })
+        .collect();
+    DFGComparison {
+        num_cases_a: num_cases.0,
+        num_cases_b: num_cases.1,
+        activities,
+        directly_follows_relations,
+    }
+}
+
+/// Compare the [`DirectlyFollowsGraph`]s of two [`EventLog`]s
+///
+/// If a `timestamp_key` is passed, performance annotations are computed (see [`DirectlyFollowsGraph::create_from_log_with_performance`])
+/// and performance differences are included in the result.
+///
+/// See [`compare_dfgs`] for details.
+pub fn compare_logs(
+    log_a: &EventLog,
+    log_b: &EventLog,
+    classifier: &EventLogClassifier,
+    timestamp_key: Option<&str>,
+    significance_level: f64,
+) -> DFGComparison {
+    let create = |log: &EventLog| match timestamp_key {
+        Some(ts_key) => {
+            DirectlyFollowsGraph::create_from_log_with_performance(log, classifier, ts_key)
+        }
+        None => DirectlyFollowsGraph::create_from_log(log, classifier),
+    };
+    compare_dfgs(&create(log_a), &create(log_b), significance_level)
+}
+
+/// Cumulative distribution function of the standard normal distribution
+///
+/// Uses the approximation of the error function from Abramowitz and Stegun (7.1.26)
+fn standard_normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dfg(variants: &[(&[&str], u32)]) -> DirectlyFollowsGraph<'static> {
+        let mut dfg = DirectlyFollowsGraph::new();
+        for (acts, freq) in variants {
+            dfg.add_activity_sequence(acts.iter().map(|a| a.to_string()), *freq);
+        }
+        dfg
+    }
+
+    #[test]
+    fn test_standard_normal_cdf() {
+        assert!((standard_normal_cdf(0.0) - 0.5).abs() < 1e-6);
+        assert!((standard_normal_cdf(1.96) - 0.975).abs() < 1e-3);
+        assert!((standard_normal_cdf(-1.96) - 0.025).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_compare_dfgs() {
+        let a = dfg(&[(&["a", "b", "c"], 500), (&["a", "c"], 500)]);
+        let b = dfg(&[(&["a", "b", "c"], 900), (&["a", "c"], 95), (&["a", "d"], 5)]);
+        let cmp = compare_dfgs(&a, &b, DEFAULT_SIGNIFICANCE_LEVEL);
+        assert_eq!(cmp.num_cases_a, 1000);
+        assert_eq!(cmp.num_cases_b, 1000);
+
+        let ab = &cmp.directly_follows_relations[&("a".to_string(), "b".to_string())];
+        assert_eq!(ab.absolute_difference, 400);
+        assert!((ab.relative_difference - 0.4).abs() < 1e-9);
+        assert!(ab.significant);
+        assert!(ab.mean_duration_difference.is_none());
+
+        let act_a = &cmp.activities["a"];
+        assert_eq!(act_a.absolute_difference, 0);
+        assert!(!act_a.significant);
+
+        let ad = &cmp.directly_follows_relations[&("a".to_string(), "d".to_string())];
+        assert_eq!(ad.frequency_a, 0);
+        assert_eq!(ad.frequency_b, 5);
+        assert_eq!(cmp.activities["d"].frequency_a, 0);
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::petri_net::petri_net_struct::{ArcType, Marking, PlaceID};
+use crate::PetriNet;
+
+use super::dfg_struct::DirectlyFollowsGraph;
+
+impl DirectlyFollowsGraph<'_> {
+    /// Convert the [`DirectlyFollowsGraph`] to a labelled [`PetriNet`] (workflow net)
+    ///
+    /// The resulting net has exactly one (labelled) transition per activity.
+    /// Each activity `a` has an input place (before `a`) and an output place (after `a`).
+    /// Each directly-follows relation `(a, b)` is encoded by a silent transition moving the token from the output place of `a`
+    /// to the input place of `b`, such that the output place of an activity encodes the choice between its successors.
+    ///
+    /// A unique source place is connected to the input places of all start activities using silent transitions,
+    /// and the output places of all end activities are connected to a unique sink place using silent transitions.
+    /// The initial marking contains one token in the source place and the final marking one token in the sink place.
+    ///
+    /// Activities only occurring in directly-follows relations (but not in [`DirectlyFollowsGraph::activities`]) are also added.
+    pub fn to_petri_net(&self) -> PetriNet {
+        let mut activities: HashSet<&str> = self.activities.keys().map(|a| a.as_str()).collect();
+        activities.extend(
+            self.directly_follows_relations
+                .keys()
+                .flat_map(|(a, b)| [a.as_ref(), b.as_ref()]),
+        );
+        activities.extend(self.start_activities.keys().map(|a| a.as_str()));
+        activities.extend(self.end_activities.keys().map(|a| a.as_str()));
+        let mut activities: Vec<&str> = activities.into_iter().collect();
+        activities.sort();
+
+        let mut net = PetriNet::new();
+        let source = net.add_place(None);
+        let sink = net.add_place(None);
+        // Input and output place of each activity
+        let mut act_places: HashMap<&str, (PlaceID, PlaceID)> = HashMap::new();
+        for act in &activities {
+            let input = net.add_place(None);
+            let output = net.add_place(None);
+            let t = net.add_transition(Some(act.to_string()), None);
+            net.add_arc(ArcType::place_to_transition(input, t), None);
+            net.add_arc(ArcType::transition_to_place(t, output), None);
+            act_places.insert(act, (input, output));
+        }
+
+        let add_silent_connection = |net: &mut PetriNet, from: PlaceID, to: PlaceID| {
+            let t = net.add_transition(None, None);
+            net.add_arc(ArcType::place_to_transition(from, t), None);
+            net.add_arc(ArcType::transition_to_place(t, to), None);
+        };
+        let mut start_activities: Vec<&String> = self.start_activities.keys().collect();
+        start_activities.sort();
+        for act in start_activities {
+            add_silent_connection(&mut net, source, act_places[act.as_str()].0);
+        }
+        let mut df_relations: Vec<(&str, &str)> = self
+            .directly_follows_relations
+            .keys()
+            .map(|(a, b)| (a.as_ref(), b.as_ref()))
+            .collect();
+        df_relations.sort();
+        for (a, b) in df_relations {
+            add_silent_connection(&mut net, act_places[a].1, act_places[b].0);
+        }
+        let mut end_activities: Vec<&String> = self.end_activities.keys().collect();
+        end_activities.sort();
+        for act in end_activities {
+            add_silent_connection(&mut net, act_places[act.as_str()].1, sink);
+        }
+
+        net.initial_marking = Some(Marking::from([(source, 1)]));
+        net.final_markings = Some(vec![Marking::from([(sink, 1)])]);
+        net
+    }
+}
+
+impl From<&DirectlyFollowsGraph<'_>> for PetriNet {
+    fn from(dfg: &DirectlyFollowsGraph<'_>) -> Self {
+        dfg.to_petri_net()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::footprints::Footprint;
+    use crate::petri_net::state_space::DEFAULT_MAX_STATES;
+
+    #[test]
+    fn test_dfg_to_petri_net() {
+        let mut dfg = DirectlyFollowsGraph::new();
+        dfg.add_activity_sequence(["a", "b", "c"].map(String::from), 5);
+        dfg.add_activity_sequence(["a", "c", "b", "d"].map(String::from), 2);
+        dfg.add_activity_sequence(["b", "d"].map(String::from), 1);
+        let net: PetriNet = (&dfg).into();
+
+        // One labelled transition per activity
+        let mut labels: Vec<&str> = net
+            .transitions
+            .values()
+            .filter_map(|t| t.label.as_deref())
+            .collect();
+        labels.sort();
+        assert_eq!(labels, vec!["a", "b", "c", "d"]);
+        // Silent transitions: 2 start, 5 df relations, 2 end
+        assert_eq!(
+            net.transitions
+                .values()
+                .filter(|t| t.label.is_none())
+                .count(),
+            2 + 5 + 2
+        );
+        assert_eq!(net.places.len(), 2 + 2 * 4);
+        assert_eq!(net.initial_marking.as_ref().unwrap().len(), 1);
+        assert_eq!(net.final_markings.as_ref().unwrap().len(), 1);
+
+        // The behavior of the net matches the DFG
+        assert_eq!(
+            Footprint::from_petri_net(&net, 20, DEFAULT_MAX_STATES).unwrap(),
+            Footprint::from_dfg(&dfg)
+        );
+    }
+}
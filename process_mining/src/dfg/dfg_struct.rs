@@ -1,20 +1,32 @@
-use crate::event_log::event_log_struct::EventLogClassifier;
+use super::performance::DFGPerformance;
+use crate::event_log::activity_projection::EventLogActivityProjection;
+use crate::event_log::event_log_struct::{EventLogClassifier, Trace};
+use crate::event_log::stream_xes::XESParsingTraceStream;
 use crate::EventLog;
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "dataframes")]
+use polars::prelude::{DataFrame, DataType, PolarsError};
+use rayon::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::serde_as;
 use std::{
-    borrow::Cow,
+    borrow::{Borrow, Cow},
     collections::{HashMap, HashSet},
 };
 
 /// Activity in a directly-follows graph.
 type Activity = String;
 
+/// Add `count` to the (saturating) total of `key`
+fn add_count<K: std::hash::Hash + Eq>(totals: &mut HashMap<K, u64>, key: K, count: u64) {
+    let total = totals.entry(key).or_default();
+    *total = total.saturating_add(count);
+}
+
 /// A directly-follows graph of [`Activity`]s.
 /// Graph containing a set of activities, a set of directly-follows relations, a set of start
 /// activities, and a set of end activities.
-/// Both, the number of occurrences of activities and of directly follows relations are annotated
-/// with their frequency.
+/// The number of occurrences of activities, of directly follows relations, and of start and end activities
+/// are annotated with their frequency.
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DirectlyFollowsGraph<'a> {
@@ -23,10 +35,21 @@ pub struct DirectlyFollowsGraph<'a> {
     /// Directly-follows relations
     #[serde_as(as = "Vec<(_, _)>")]
     pub directly_follows_relations: HashMap<(Cow<'a, str>, Cow<'a, str>), u32>,
-    /// Start activities
-    pub start_activities: HashSet<Activity>,
-    /// End activities
-    pub end_activities: HashSet<Activity>,
+    /// Start activities (with the number of traces starting with them)
+    ///
+    /// For backwards compatibility, a list of activities (each with frequency 1) can also be deserialized
+    #[serde(deserialize_with = "deserialize_activity_frequencies")]
+    pub start_activities: HashMap<Activity, u32>,
+    /// End activities (with the number of traces ending with them)
+    ///
+    /// For backwards compatibility, a list of activities (each with frequency 1) can also be deserialized
+    #[serde(deserialize_with = "deserialize_activity_frequencies")]
+    pub end_activities: HashMap<Activity, u32>,
+    /// Optional performance annotations (e.g., durations of directly-follows relations)
+    ///
+    /// See [`DirectlyFollowsGraph::create_from_log_with_performance`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub performance: Option<DFGPerformance>,
 }
 
 impl Default for DirectlyFollowsGraph<'_> {
@@ -41,35 +64,218 @@ impl<'a> DirectlyFollowsGraph<'a> {
         Self {
             activities: HashMap::new(),
             directly_follows_relations: HashMap::new(),
-            start_activities: HashSet::new(),
-            end_activities: HashSet::new(),
+            start_activities: HashMap::new(),
+            end_activities: HashMap::new(),
+            performance: None,
         }
     }
 
     /// Construct a [`DirectlyFollowsGraph`] from an [`EventLog`] using the specified [`EventLogClassifier`] to derive the 'activity' names
     ///
     /// If there is no special classifier to be used, the default (`&EventLogClassifier::default()`) can also simply be passed in
+    ///
+    /// The global event attributes of the log are used as fallback for missing event attributes.
+    /// The traces of the event log are processed in parallel.
     pub fn create_from_log(event_log: &EventLog, classifier: &EventLogClassifier) -> Self {
+        event_log
+            .traces
+            .par_iter()
+            .fold(Self::new, |mut dfg, t| {
+                dfg.add_activity_sequence(
+                    t.events.iter().map(|e| {
+                        classifier.get_class_identity_with_globals(e, &event_log.global_event_attrs)
+                    }),
+                    1,
+                );
+                dfg
+            })
+            .reduce(Self::new, |mut dfg, other| {
+                dfg.add_frequencies_of(other);
+                dfg
+            })
+    }
+
+    /// Construct a [`DirectlyFollowsGraph`] from an iterator of [`Trace`]s using the specified [`EventLogClassifier`] to derive the 'activity' names
+    ///
+    /// Traces are processed one at a time, so only the [`DirectlyFollowsGraph`] itself is kept in memory.
+    /// See also [`DirectlyFollowsGraph::create_from_trace_stream`].
+    pub fn create_from_traces<T: Borrow<Trace>, I: IntoIterator<Item = T>>(
+        traces: I,
+        classifier: &EventLogClassifier,
+    ) -> Self {
         let mut result = Self::new();
-        event_log.traces.iter().for_each(|t| {
-            let mut last_event_identity: Option<String> = None;
-            t.events.iter().for_each(|e| {
-                let curr_event_identity = classifier.get_class_identity(e);
-                result.add_activity(curr_event_identity.clone(), 1);
-
-                if let Some(last_ev_id) = last_event_identity.take() {
-                    result.add_df_relation(last_ev_id.into(), curr_event_identity.clone().into(), 1)
-                } else {
-                    result.add_start_activity(curr_event_identity.clone());
-                }
+        for t in traces {
+            result.add_activity_sequence(
+                t.borrow()
+                    .events
+                    .iter()
+                    .map(|e| classifier.get_class_identity(e)),
+                1,
+            );
+        }
+        result
+    }
+
+    /// Construct a [`DirectlyFollowsGraph`] from a [`XESParsingTraceStream`] using the specified [`EventLogClassifier`] to derive the 'activity' names
+    ///
+    /// In contrast to [`DirectlyFollowsGraph::create_from_log`], the event log is never fully loaded into memory.
+    pub fn create_from_trace_stream(
+        stream: &mut XESParsingTraceStream<'_>,
+        classifier: &EventLogClassifier,
+    ) -> Self {
+        Self::create_from_traces(stream, classifier)
+    }
 
-                last_event_identity = Some(curr_event_identity.clone());
+    /// Construct a [`DirectlyFollowsGraph`] from an [`EventLogActivityProjection`]
+    ///
+    /// The activity names are the activities of the projection, and all frequencies are weighted by the variant counts.
+    /// Frequencies exceeding [`u32::MAX`] are saturated.
+    pub fn create_from_activity_projection(projection: &EventLogActivityProjection) -> Self {
+        // Sum up the (`u64`) variant counts first and only saturate the totals
+        let mut activities: HashMap<usize, u64> = HashMap::new();
+        let mut df_relations: HashMap<(usize, usize), u64> = HashMap::new();
+        let mut start_activities: HashMap<usize, u64> = HashMap::new();
+        let mut end_activities: HashMap<usize, u64> = HashMap::new();
+        for (trace, count) in &projection.traces {
+            for act in trace {
+                add_count(&mut activities, *act, *count);
+            }
+            for pair in trace.windows(2) {
+                add_count(&mut df_relations, (pair[0], pair[1]), *count);
+            }
+            if let (Some(first), Some(last)) = (trace.first(), trace.last()) {
+                add_count(&mut start_activities, *first, *count);
+                add_count(&mut end_activities, *last, *count);
+            }
+        }
+
+        let name = |act: usize| projection.activities[act].clone();
+        let saturate = |count: u64| u32::try_from(count).unwrap_or(u32::MAX);
+        let mut result = Self::new();
+        for (act, count) in activities {
+            result.add_activity(name(act), saturate(count));
+        }
+        for ((a, b), count) in df_relations {
+            result.add_df_relation(name(a).into(), name(b).into(), saturate(count));
+        }
+        for (act, count) in start_activities {
+            result.add_start_activity_with_frequency(name(act), saturate(count));
+        }
+        for (act, count) in end_activities {
+            result.add_end_activity_with_frequency(name(act), saturate(count));
+        }
+        result
+    }
+
+    #[cfg(feature = "dataframes")]
+    /// Construct a [`DirectlyFollowsGraph`] from a polars [`DataFrame`] with one row per event
+    ///
+    /// Events are grouped into cases by the `case_column` and labeled by the `activity_column` (both are cast to strings).
+    /// If a `timestamp_column` (of datetime or integer type) is passed, the events of each case are (stably) sorted by it;
+    /// otherwise, the row order is used.
+    /// Rows with a null case or activity (or a null timestamp, if a `timestamp_column` is passed) are skipped.
+    ///
+    /// Note: This function is only available if the `dataframes` feature is enabled.
+    pub fn create_from_dataframe(
+        df: &DataFrame,
+        case_column: &str,
+        activity_column: &str,
+        timestamp_column: Option<&str>,
+    ) -> Result<Self, PolarsError> {
+        let cases = df.column(case_column)?.cast(&DataType::String)?;
+        let activities = df.column(activity_column)?.cast(&DataType::String)?;
+        let timestamps = match timestamp_column {
+            Some(col) => Some(df.column(col)?.cast(&DataType::Int64)?),
+            None => None,
+        };
+        // Without a timestamp column, all events get the same timestamp (i.e., the row order is kept)
+        let timestamps: Vec<Option<i64>> = match &timestamps {
+            Some(ts) => ts.i64()?.into_iter().collect(),
+            None => vec![Some(0); df.height()],
+        };
+        let mut case_order: Vec<&str> = Vec::new();
+        let mut events_per_case: HashMap<&str, Vec<(i64, &str)>> = HashMap::new();
+        for ((case, act), ts) in cases
+            .str()?
+            .into_iter()
+            .zip(activities.str()?.into_iter())
+            .zip(timestamps)
+        {
+            let (Some(case), Some(act), Some(ts)) = (case, act, ts) else {
+                continue;
+            };
+            let events = events_per_case.entry(case).or_insert_with(|| {
+                case_order.push(case);
+                Vec::new()
             });
-            if let Some(last_ev_id) = last_event_identity.take() {
-                result.add_end_activity(last_ev_id);
+            events.push((ts, act));
+        }
+        let mut result = Self::new();
+        for case in case_order {
+            let mut events = events_per_case.remove(case).unwrap_or_default();
+            events.sort_by_key(|(ts, _)| *ts);
+            result.add_activity_sequence(events.into_iter().map(|(_, act)| act.to_string()), 1);
+        }
+        Ok(result)
+    }
+
+    /// Add a sequence of activities (e.g., the activities of a trace) with a frequency
+    ///
+    /// Adds the activities, the directly-follows relations between them, as well as the first and last activity as start and end activity.
+    pub fn add_activity_sequence<I: IntoIterator<Item = Activity>>(
+        &mut self,
+        activities: I,
+        frequency: u32,
+    ) {
+        let mut last_activity: Option<Activity> = None;
+        for act in activities {
+            self.add_activity(act.clone(), frequency);
+            match last_activity.take() {
+                Some(last_act) => {
+                    self.add_df_relation(last_act.into(), act.clone().into(), frequency)
+                }
+                None => self.add_start_activity_with_frequency(act.clone(), frequency),
             }
-        });
+            last_activity = Some(act);
+        }
+        if let Some(last_act) = last_activity {
+            self.add_end_activity_with_frequency(last_act, frequency);
+        }
+    }
+
+    /// Add all frequencies of another [`DirectlyFollowsGraph`] to this one
+    ///
+    /// Performance annotations are not merged.
+    fn add_frequencies_of(&mut self, other: DirectlyFollowsGraph<'a>) {
+        for (act, freq) in other.activities {
+            self.add_activity(act, freq);
+        }
+        for ((from, to), freq) in other.directly_follows_relations {
+            self.add_df_relation(from, to, freq);
+        }
+        for (act, freq) in other.start_activities {
+            self.add_start_activity_with_frequency(act, freq);
+        }
+        for (act, freq) in other.end_activities {
+            self.add_end_activity_with_frequency(act, freq);
+        }
+    }
 
+    /// Construct a [`DirectlyFollowsGraph`] from an [`EventLog`] including [`DFGPerformance`] annotations
+    ///
+    /// In addition to [`DirectlyFollowsGraph::create_from_log`], durations are derived from the timestamps stored
+    /// under the passed `timestamp_key` (e.g., [`TIMESTAMP_NAME`](crate::event_log::constants::TIMESTAMP_NAME)).
+    pub fn create_from_log_with_performance(
+        event_log: &EventLog,
+        classifier: &EventLogClassifier,
+        timestamp_key: &str,
+    ) -> Self {
+        let mut result = Self::create_from_log(event_log, classifier);
+        result.performance = Some(DFGPerformance::create_from_log(
+            event_log,
+            classifier,
+            timestamp_key,
+        ));
         result
     }
 
@@ -85,14 +291,32 @@ impl<'a> DirectlyFollowsGraph<'a> {
         *self.activities.entry(activity).or_default() += frequency;
     }
 
-    /// Adds an activity to the set of start activities.
+    /// Adds an activity to the start activities (with frequency 1).
+    ///
+    /// If the activity already is a start activity, its frequency is increased by 1.
     pub fn add_start_activity(&mut self, activity: Activity) {
-        self.start_activities.insert(activity);
+        self.add_start_activity_with_frequency(activity, 1);
     }
 
-    /// Adds an activity to the set of end activities.
+    /// Adds an activity to the start activities with a frequency.
+    ///
+    /// If the activity already is a start activity, the frequency count is added to the existing frequency.
+    pub fn add_start_activity_with_frequency(&mut self, activity: Activity, frequency: u32) {
+        *self.start_activities.entry(activity).or_default() += frequency;
+    }
+
+    /// Adds an activity to the end activities (with frequency 1).
+    ///
+    /// If the activity already is an end activity, its frequency is increased by 1.
     pub fn add_end_activity(&mut self, activity: Activity) {
-        self.end_activities.insert(activity);
+        self.add_end_activity_with_frequency(activity, 1);
+    }
+
+    /// Adds an activity to the end activities with a frequency.
+    ///
+    /// If the activity already is an end activity, the frequency count is added to the existing frequency.
+    pub fn add_end_activity_with_frequency(&mut self, activity: Activity, frequency: u32) {
+        *self.end_activities.entry(activity).or_default() += frequency;
     }
 
     /// Checks if an activity is already contained in the directly-follows graph.
@@ -102,12 +326,12 @@ impl<'a> DirectlyFollowsGraph<'a> {
 
     /// Checks if an activity is a start activity in the directly-follows graph.
     pub fn is_start_activity<S: AsRef<str>>(&self, activity: S) -> bool {
-        self.start_activities.contains(activity.as_ref())
+        self.start_activities.contains_key(activity.as_ref())
     }
 
     /// Checks if an activity is an end activity in the directly-follows graph.
     pub fn is_end_activity<S: AsRef<str>>(&self, activity: S) -> bool {
-        self.end_activities.contains(activity.as_ref())
+        self.end_activities.contains_key(activity.as_ref())
     }
 
     /// Removes an activity from the directly-follows graph.
@@ -213,6 +437,22 @@ impl<'a> DirectlyFollowsGraph<'a> {
     }
 }
 
+/// Deserialize start/end activity frequencies from either a map (activity to frequency) or a list of activities
+fn deserialize_activity_frequencies<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<Activity, u32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ActivityFrequencies {
+        Frequencies(HashMap<Activity, u32>),
+        Activities(Vec<Activity>),
+    }
+    Ok(match ActivityFrequencies::deserialize(deserializer)? {
+        ActivityFrequencies::Frequencies(freqs) => freqs,
+        ActivityFrequencies::Activities(acts) => acts.into_iter().map(|act| (act, 1)).collect(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     pub const SAMPLE_JSON_DFG: &str = r#"
@@ -285,6 +525,109 @@ mod tests {
         assert_eq!(graph.activities, test_hashmap);
     }
 
+    #[test]
+    fn dfg_from_traces_stream_and_projection() {
+        use crate::event_log::{Event, Trace};
+        let trace = |acts: &[&str]| Trace {
+            attributes: Vec::new(),
+            events: acts.iter().map(|a| Event::new(a.to_string())).collect(),
+        };
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![
+                trace(&["a", "b", "c"]),
+                trace(&["a", "c"]),
+                trace(&["a", "b", "c"]),
+                trace(&["b"]),
+            ],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+        let classifier = EventLogClassifier::default();
+        let from_log = DirectlyFollowsGraph::create_from_log(&log, &classifier);
+        assert_eq!(from_log.activities["a"], 3);
+        assert_eq!(
+            from_log.directly_follows_relations[&("b".into(), "c".into())],
+            2
+        );
+        assert_eq!(from_log.start_activities["a"], 3);
+        assert_eq!(from_log.start_activities["b"], 1);
+        assert_eq!(from_log.end_activities["c"], 3);
+
+        let from_traces = DirectlyFollowsGraph::create_from_traces(&log.traces, &classifier);
+        let projection: EventLogActivityProjection = (&log).into();
+        let from_projection = DirectlyFollowsGraph::create_from_activity_projection(&projection);
+        for dfg in [from_traces, from_projection] {
+            assert_eq!(dfg.activities, from_log.activities);
+            assert_eq!(
+                dfg.directly_follows_relations,
+                from_log.directly_follows_relations
+            );
+            assert_eq!(dfg.start_activities, from_log.start_activities);
+            assert_eq!(dfg.end_activities, from_log.end_activities);
+        }
+    }
+
+    #[test]
+    fn dfg_from_activity_projection_saturates() {
+        let projection = EventLogActivityProjection {
+            activities: vec!["a".to_string(), "b".to_string()],
+            traces: vec![(vec![0, 1], u64::from(u32::MAX)), (vec![0], 1)],
+            ..Default::default()
+        };
+        let dfg = DirectlyFollowsGraph::create_from_activity_projection(&projection);
+        assert_eq!(dfg.activities["a"], u32::MAX);
+        assert_eq!(dfg.activities["b"], u32::MAX);
+        assert_eq!(dfg.start_activities["a"], u32::MAX);
+        assert_eq!(dfg.end_activities["a"], 1);
+        assert_eq!(
+            dfg.directly_follows_relations[&("a".into(), "b".into())],
+            u32::MAX
+        );
+    }
+
+    #[cfg(feature = "dataframes")]
+    #[test]
+    fn dfg_from_dataframe() {
+        use polars::prelude::*;
+        let df = df!(
+            "case" => ["1", "1", "2", "1", "2"],
+            "activity" => ["b", "a", "a", "c", "c"],
+            "timestamp" => [2i64, 1, 1, 3, 2]
+        )
+        .unwrap();
+        let dfg =
+            DirectlyFollowsGraph::create_from_dataframe(&df, "case", "activity", Some("timestamp"))
+                .unwrap();
+        assert_eq!(dfg.start_activities["a"], 2);
+        assert_eq!(dfg.end_activities["c"], 2);
+        assert!(dfg.contains_df_relation(("a", "b")));
+        assert!(dfg.contains_df_relation(("a", "c")));
+        assert!(!dfg.contains_df_relation(("b", "a")));
+    }
+
+    #[cfg(feature = "dataframes")]
+    #[test]
+    fn dfg_from_dataframe_with_nulls() {
+        use polars::prelude::*;
+        // Only the events `a` and `c` have both an activity and a timestamp
+        let df = df!(
+            "case" => ["1", "1", "1", "1"],
+            "activity" => [Some("a"), None, Some("b"), Some("c")],
+            "timestamp" => [Some(1i64), Some(2), None, Some(3)]
+        )
+        .unwrap();
+        let dfg =
+            DirectlyFollowsGraph::create_from_dataframe(&df, "case", "activity", Some("timestamp"))
+                .unwrap();
+        assert_eq!(dfg.activities.len(), 2);
+        assert!(!dfg.activities.contains_key(""));
+        assert!(dfg.contains_df_relation(("a", "c")));
+        assert_eq!(dfg.start_activities["a"], 1);
+    }
+
     #[test]
     fn deserialize_dfg_test() {
         let dfg: DirectlyFollowsGraph<'_> = serde_json::from_str(SAMPLE_JSON_DFG).unwrap();
@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::dfg_struct::DirectlyFollowsGraph;
+
+/// Result of filtering a [`DirectlyFollowsGraph`] (see [`DirectlyFollowsGraph::filter_activities_and_paths`])
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DFGFilterResult<'a> {
+    /// The filtered [`DirectlyFollowsGraph`]
+    pub dfg: DirectlyFollowsGraph<'a>,
+    /// Activities which were removed
+    pub removed_activities: Vec<String>,
+    /// Directly-follows relations which were removed
+    pub removed_df_relations: Vec<(String, String)>,
+}
+
+/// Sort the passed items by frequency (descending) and name (ascending)
+fn sort_by_frequency<T: Ord>(items: &mut [(T, u32)]) {
+    items.sort_by(|(a, a_freq), (b, b_freq)| b_freq.cmp(a_freq).then_with(|| a.cmp(b)));
+}
+
+/// Number of elements to keep of `len` elements when keeping the top `fraction`
+fn num_to_keep(len: usize, fraction: f64) -> usize {
+    ((fraction.clamp(0.0, 1.0) * len as f64).ceil() as usize).min(len)
+}
+
+/// Get all activities reachable from `sources` using the passed edges (in the given direction)
+fn reachable<'b>(
+    sources: impl Iterator<Item = &'b str>,
+    edges: &HashSet<(&'b str, &'b str)>,
+    forward: bool,
+) -> HashSet<&'b str> {
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (a, b) in edges {
+        let (from, to) = if forward { (*a, *b) } else { (*b, *a) };
+        adjacency.entry(from).or_default().push(to);
+    }
+    let mut visited: HashSet<&str> = sources.collect();
+    let mut queue: Vec<&str> = visited.iter().copied().collect();
+    while let Some(act) = queue.pop() {
+        for next in adjacency.get(act).into_iter().flatten() {
+            if visited.insert(next) {
+                queue.push(next);
+            }
+        }
+    }
+    visited
+}
+
+impl<'a> DirectlyFollowsGraph<'a> {
+    /// Filter the [`DirectlyFollowsGraph`] to the most frequent activities and directly-follows relations (paths)
+    ///
+    /// Similar to the activity and path sliders in Disco, this keeps the top `activity_fraction` (between `0.0` and `1.0`)
+    /// of activities by frequency and then the top `path_fraction` of directly-follows relations between the kept activities.
+    /// The most frequent start activity and the most frequent end activity are always kept.
+    ///
+    /// Afterwards, additional (most frequent) directly-follows relations between kept activities are added back, such that
+    /// every kept activity is reachable from a start activity and can reach an end activity.
+    /// Activities for which this is not possible are removed.
+    ///
+    /// Returns the filtered [`DirectlyFollowsGraph`] together with the removed activities and directly-follows relations.
+    pub fn filter_activities_and_paths(
+        &self,
+        activity_fraction: f64,
+        path_fraction: f64,
+    ) -> DFGFilterResult<'a> {
+        let mut sorted_acts: Vec<(&str, u32)> = self
+            .activities
+            .iter()
+            .map(|(act, freq)| (act.as_str(), *freq))
+            .collect();
+        sort_by_frequency(&mut sorted_acts);
+        let mut kept_acts: HashSet<&str> = sorted_acts
+            .iter()
+            .take(num_to_keep(sorted_acts.len(), activity_fraction))
+            .map(|(act, _)| *act)
+            .collect();
+        // Keep the most frequent start and end activity, so that the result is not empty if all frequent activities occur mid-trace
+        for endpoints in [&self.start_activities, &self.end_activities] {
+            let mut sorted_endpoints: Vec<(&str, u32)> = endpoints
+                .iter()
+                .map(|(act, freq)| (act.as_str(), *freq))
+                .collect();
+            sort_by_frequency(&mut sorted_endpoints);
+            if let Some((act, _)) = sorted_endpoints.first() {
+                kept_acts.insert(act);
+            }
+        }
+
+        let mut candidate_edges: Vec<((&str, &str), u32)> = self
+            .directly_follows_relations
+            .iter()
+            .filter(|((a, b), _)| kept_acts.contains(a.as_ref()) && kept_acts.contains(b.as_ref()))
+            .map(|((a, b), freq)| ((a.as_ref(), b.as_ref()), *freq))
+            .collect();
+        sort_by_frequency(&mut candidate_edges);
+        let mut kept_edges: HashSet<(&str, &str)> = candidate_edges
+            .iter()
+            .take(num_to_keep(candidate_edges.len(), path_fraction))
+            .map(|(edge, _)| *edge)
+            .collect();
+
+        // Repair connectivity until a fixpoint is reached
+        loop {
+            let mut changed = false;
+            for forward in [true, false] {
+                let sources = if forward {
+                    &self.start_activities
+                } else {
+                    &self.end_activities
+                };
+                let sources = || {
+                    sources
+                        .keys()
+                        .map(|a| a.as_str())
+                        .filter(|a| kept_acts.contains(a))
+                };
+                let mut connected = reachable(sources(), &kept_edges, forward);
+                // Greedily add the most frequent edge connecting a new activity
+                while connected.len() < kept_acts.len() {
+                    let next_edge = candidate_edges.iter().find(|((a, b), _)| {
+                        let (from, to) = if forward { (a, b) } else { (b, a) };
+                        connected.contains(from) && !connected.contains(to)
+                    });
+                    match next_edge {
+                        Some((edge, _)) => {
+                            kept_edges.insert(*edge);
+                            connected = reachable(sources(), &kept_edges, forward);
+                            changed = true;
+                        }
+                        None => break,
+                    }
+                }
+                if connected.len() < kept_acts.len() {
+                    kept_acts.retain(|a| connected.contains(a));
+                    kept_edges.retain(|(a, b)| kept_acts.contains(a) && kept_acts.contains(b));
+                    candidate_edges
+                        .retain(|((a, b), _)| kept_acts.contains(a) && kept_acts.contains(b));
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut dfg = DirectlyFollowsGraph::new();
+        let mut removed_activities = Vec::new();
+        for (act, freq) in &sorted_acts {
+            if kept_acts.contains(act) {
+                dfg.add_activity(act.to_string(), *freq);
+                if let Some(freq) = self.start_activities.get(*act) {
+                    dfg.add_start_activity_with_frequency(act.to_string(), *freq);
+                }
+                if let Some(freq) = self.end_activities.get(*act) {
+                    dfg.add_end_activity_with_frequency(act.to_string(), *freq);
+                }
+            } else {
+                removed_activities.push(act.to_string());
+            }
+        }
+        let mut removed_df_relations = Vec::new();
+        for ((a, b), freq) in &self.directly_follows_relations {
+            if kept_edges.contains(&(a.as_ref(), b.as_ref())) {
+                dfg.add_df_relation(a.clone(), b.clone(), *freq);
+            } else {
+                removed_df_relations.push((a.to_string(), b.to_string()));
+            }
+        }
+        removed_df_relations.sort();
+
+        if let Some(perf) = &self.performance {
+            let mut perf = perf.clone();
+            perf.df_durations
+                .retain(|(a, b), _| kept_edges.contains(&(a.as_str(), b.as_str())));
+            perf.df_case_frequencies
+                .retain(|(a, b), _| kept_edges.contains(&(a.as_str(), b.as_str())));
+            perf.sojourn_times
+                .retain(|a, _| kept_acts.contains(a.as_str()));
+            perf.activity_case_frequencies
+                .retain(|a, _| kept_acts.contains(a.as_str()));
+            dfg.performance = Some(perf);
+        }
+
+        DFGFilterResult {
+            dfg,
+            removed_activities,
+            removed_df_relations,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dfg() -> DirectlyFollowsGraph<'static> {
+        let mut dfg = DirectlyFollowsGraph::new();
+        for (act, freq) in [("a", 10), ("b", 8), ("c", 6), ("d", 2), ("e", 1)] {
+            dfg.add_activity(act.to_string(), freq);
+        }
+        dfg.add_start_activity("a".to_string());
+        dfg.add_end_activity("c".to_string());
+        dfg.add_end_activity("e".to_string());
+        for (a, b, freq) in [
+            ("a", "b", 8),
+            ("b", "c", 5),
+            ("b", "d", 2),
+            ("d", "c", 1),
+            ("a", "c", 2),
+            ("d", "b", 1),
+            ("a", "e", 1),
+        ] {
+            dfg.add_df_relation(a.into(), b.into(), freq);
+        }
+        dfg
+    }
+
+    #[test]
+    fn test_filter_keeps_everything() {
+        let dfg = sample_dfg();
+        let res = dfg.filter_activities_and_paths(1.0, 1.0);
+        assert_eq!(res.dfg.activities.len(), 5);
+        assert_eq!(res.dfg.directly_follows_relations.len(), 7);
+        assert!(res.removed_activities.is_empty());
+        assert!(res.removed_df_relations.is_empty());
+    }
+
+    #[test]
+    fn test_filter_activities_and_paths() {
+        let dfg = sample_dfg();
+        // Keep top 4 activities (a,b,c,d) and only the most frequent path (a->b)
+        let res = dfg.filter_activities_and_paths(0.8, 0.0);
+        assert_eq!(res.removed_activities, vec!["e".to_string()]);
+        // b->c is added to connect c; b->d and d->b to connect d
+        let kept: HashSet<(String, String)> = res
+            .dfg
+            .directly_follows_relations
+            .keys()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect();
+        let expected: HashSet<(String, String)> = [("a", "b"), ("b", "c"), ("b", "d"), ("d", "b")]
+            .into_iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect();
+        assert_eq!(kept, expected);
+        assert!(res.dfg.is_start_activity("a"));
+        assert!(!res.dfg.is_end_activity("e"));
+    }
+
+    #[test]
+    fn test_filter_keeps_start_and_end_activity() {
+        // The frequent activities b and c only occur mid-trace
+        let mut dfg = DirectlyFollowsGraph::new();
+        for (act, freq) in [("a", 2), ("b", 6), ("c", 6), ("d", 2)] {
+            dfg.add_activity(act.to_string(), freq);
+        }
+        dfg.add_start_activity_with_frequency("a".to_string(), 2);
+        dfg.add_end_activity_with_frequency("d".to_string(), 2);
+        for (a, b, freq) in [("a", "b", 2), ("b", "c", 6), ("c", "b", 4), ("c", "d", 2)] {
+            dfg.add_df_relation(a.into(), b.into(), freq);
+        }
+        let res = dfg.filter_activities_and_paths(0.5, 0.0);
+        assert!(res.removed_activities.is_empty());
+        assert_eq!(res.dfg.directly_follows_relations.len(), 3);
+        assert_eq!(
+            res.removed_df_relations,
+            vec![("c".to_string(), "b".to_string())]
+        );
+        assert!(res.dfg.is_start_activity("a"));
+        assert!(res.dfg.is_end_activity("d"));
+    }
+
+    #[test]
+    fn test_filter_removes_unreachable_activities() {
+        let mut dfg = sample_dfg();
+        // d can no longer reach an end activity
+        dfg.directly_follows_relations
+            .remove(&("d".into(), "c".into()));
+        dfg.directly_follows_relations
+            .remove(&("d".into(), "b".into()));
+        let res = dfg.filter_activities_and_paths(1.0, 1.0);
+        assert_eq!(res.removed_activities, vec!["d".to_string()]);
+        assert_eq!(
+            res.removed_df_relations,
+            vec![("b".to_string(), "d".to_string())]
+        );
+    }
+}
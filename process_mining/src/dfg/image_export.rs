@@ -8,7 +8,11 @@ use graphviz_rust::{
 };
 use uuid::Uuid;
 
-use crate::dfg::dfg_struct::DirectlyFollowsGraph;
+use crate::dfg::{
+    comparison::{DFGComparison, DFGElementDifference},
+    dfg_struct::DirectlyFollowsGraph,
+    performance::{format_duration, DFGEdgeLabel},
+};
 
 ///
 /// Export the image of a [`DirectlyFollowsGraph`]
@@ -21,7 +25,23 @@ pub fn export_dfg_image<P: AsRef<std::path::Path>>(
     format: Format,
     dpi_factor: Option<f32>,
 ) -> Result<(), std::io::Error> {
-    let g = export_dfg_to_dot_graph(dfg, dpi_factor);
+    export_dfg_image_with_labels(dfg, path, format, dpi_factor, DFGEdgeLabel::Frequency)
+}
+
+///
+/// Export the image of a [`DirectlyFollowsGraph`] with the specified [`DFGEdgeLabel`]s on the directly-follows edges
+///
+/// Labels other than [`DFGEdgeLabel::Frequency`] require [`DirectlyFollowsGraph::performance`] annotations
+/// (see [`DirectlyFollowsGraph::create_from_log_with_performance`]).
+///
+pub fn export_dfg_image_with_labels<P: AsRef<std::path::Path>>(
+    dfg: &DirectlyFollowsGraph<'_>,
+    path: P,
+    format: Format,
+    dpi_factor: Option<f32>,
+    edge_label: DFGEdgeLabel,
+) -> Result<(), std::io::Error> {
+    let g = export_dfg_to_dot_graph_with_labels(dfg, dpi_factor, edge_label);
 
     g.print(&mut PrinterContext::default());
 
@@ -38,15 +58,28 @@ pub fn export_dfg_image<P: AsRef<std::path::Path>>(
 /// Also see [`export_dfg_image`], as well as [`export_dfg_image_svg`] and [`export_dfg_image_png`]
 ///
 pub fn export_dfg_to_dot_graph(dfg: &DirectlyFollowsGraph<'_>, dpi_factor: Option<f32>) -> Graph {
+    export_dfg_to_dot_graph_with_labels(dfg, dpi_factor, DFGEdgeLabel::Frequency)
+}
+
+///
+/// Export the a [`DirectlyFollowsGraph`] to a DOT graph (used in Graphviz) with the specified [`DFGEdgeLabel`]s on the directly-follows edges
+///
+/// Also see [`export_dfg_to_dot_graph`] and [`export_dfg_image_with_labels`]
+///
+pub fn export_dfg_to_dot_graph_with_labels(
+    dfg: &DirectlyFollowsGraph<'_>,
+    dpi_factor: Option<f32>,
+    edge_label: DFGEdgeLabel,
+) -> Graph {
     let mut sorted_acts: Vec<_> = dfg.activities.iter().collect();
     sorted_acts.sort_by(|(a_act, _), (b_act, _)| {
-        if dfg.start_activities.contains(*a_act) {
+        if dfg.start_activities.contains_key(*a_act) {
             Ordering::Less
-        } else if dfg.start_activities.contains(*b_act) {
+        } else if dfg.start_activities.contains_key(*b_act) {
             Ordering::Greater
-        } else if dfg.end_activities.contains(*a_act) {
+        } else if dfg.end_activities.contains_key(*a_act) {
             Ordering::Greater
-        } else if dfg.end_activities.contains(*b_act) {
+        } else if dfg.end_activities.contains_key(*b_act) {
             Ordering::Less
         } else {
             Ordering::Equal
@@ -77,10 +110,13 @@ pub fn export_dfg_to_dot_graph(dfg: &DirectlyFollowsGraph<'_>, dpi_factor: Optio
         .directly_follows_relations
         .iter()
         .map(|(dfr, &frequency)| {
-            let attrs = if frequency == 1 {
-                Vec::default()
-            } else {
-                vec![attr!("label", (format!("{}", frequency)))]
+            let label = match edge_label {
+                DFGEdgeLabel::Frequency if frequency == 1 => None,
+                _ => dfg.get_df_label(&dfr.0, &dfr.1, edge_label),
+            };
+            let attrs = match label {
+                Some(label) => vec![attr!("label", esc label)],
+                None => Vec::default(),
             };
             stmt!(edge!(node_id!(esc dfr.0) => node_id!(esc dfr.1), attrs))
         })
@@ -101,6 +137,114 @@ pub fn graph_to_dot(g: &Graph) -> String {
     g.print(&mut PrinterContext::default())
 }
 
+///
+/// Export the image of a [`DFGComparison`] (i.e., a color-coded difference graph)
+///
+/// See [`export_dfg_comparison_to_dot_graph`] for details on the visualization.
+///
+pub fn export_dfg_comparison_image<P: AsRef<std::path::Path>>(
+    comparison: &DFGComparison,
+    path: P,
+    format: Format,
+    dpi_factor: Option<f32>,
+) -> Result<(), std::io::Error> {
+    let g = export_dfg_comparison_to_dot_graph(comparison, dpi_factor);
+
+    let out = graphviz_rust::exec(g, &mut PrinterContext::default(), vec![format.into()])?;
+
+    let mut f = File::create(path)?;
+    f.write_all(&out)?;
+    Ok(())
+}
+
+/// Color used for significant increases in a [`DFGComparison`] graph
+const INCREASE_COLOR: &str = "#2E7D32";
+/// Color used for significant decreases in a [`DFGComparison`] graph
+const DECREASE_COLOR: &str = "#C62828";
+/// Color used for non-significant differences in a [`DFGComparison`] graph
+const UNCHANGED_COLOR: &str = "#9E9E9E";
+
+/// Get the color and the label of a [`DFGElementDifference`] for visualization
+fn difference_color_and_label(diff: &DFGElementDifference) -> (&'static str, String) {
+    let color = if !diff.significant || diff.relative_difference == 0.0 {
+        UNCHANGED_COLOR
+    } else if diff.relative_difference > 0.0 {
+        INCREASE_COLOR
+    } else {
+        DECREASE_COLOR
+    };
+    let change = if diff.frequency_a == 0 {
+        "new".to_string()
+    } else if diff.frequency_b == 0 {
+        "removed".to_string()
+    } else {
+        format!(
+            "{:+.1}%",
+            100.0 * diff.relative_difference / diff.relative_frequency_a
+        )
+    };
+    let mut label = format!("{:+} ({change})", diff.absolute_difference);
+    if let Some(perf) = diff.mean_duration_difference {
+        let sign = if perf >= 0.0 { "+" } else { "" };
+        label.push_str(&format!("\\n{sign}{}", format_duration(perf)));
+    }
+    (color, label)
+}
+
+///
+/// Export a [`DFGComparison`] to a DOT graph (used in Graphviz)
+///
+/// The resulting difference graph contains all activities and directly-follows relations of both compared graphs.
+/// Elements with a significant increase in relative frequency are colored green, elements with a significant decrease red and all others gray.
+/// Elements only present in one of the graphs are drawn dashed.
+/// Labels contain the absolute frequency difference, the change of the relative frequency and (if available) the difference of the mean durations.
+///
+/// Also see [`export_dfg_comparison_image`]
+///
+pub fn export_dfg_comparison_to_dot_graph(
+    comparison: &DFGComparison,
+    dpi_factor: Option<f32>,
+) -> Graph {
+    let mut sorted_acts: Vec<_> = comparison.activities.iter().collect();
+    sorted_acts.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let activity_nodes: Vec<Stmt> = sorted_acts
+        .into_iter()
+        .map(|(act, diff)| {
+            let (color, diff_label) = difference_color_and_label(diff);
+            let label = format!("{act}\\n{diff_label}");
+            let style = if diff.frequency_a == 0 || diff.frequency_b == 0 {
+                "\"filled,dashed\""
+            } else {
+                "\"filled\""
+            };
+            stmt!(node!(esc act; attr!("label", esc label), attr!("shape","box"), attr!("fontsize",12), attr!("style",style), attr!("fillcolor","white"), attr!("color", esc color), attr!("penwidth",2), attr!("height",0.5)))
+        })
+        .collect();
+
+    let mut sorted_dfs: Vec<_> = comparison.directly_follows_relations.iter().collect();
+    sorted_dfs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let arcs: Vec<Stmt> = sorted_dfs
+        .into_iter()
+        .map(|((from, to), diff)| {
+            let (color, label) = difference_color_and_label(diff);
+            let style = if diff.frequency_a == 0 || diff.frequency_b == 0 {
+                "dashed"
+            } else {
+                "solid"
+            };
+            let pen_width = 1.0 + (4.0 * diff.relative_difference.abs()).min(4.0);
+            stmt!(edge!(node_id!(esc from) => node_id!(esc to), vec![attr!("label", esc label), attr!("color", esc color), attr!("fontcolor", esc color), attr!("style", style), attr!("penwidth", pen_width)]))
+        })
+        .collect();
+
+    let mut global_graph_options = vec![stmt!(attr!("rankdir", "LR"))];
+    if let Some(dpi_fac) = dpi_factor {
+        global_graph_options.push(stmt!(attr!("dpi", (dpi_fac * 96.0))))
+    }
+
+    graph!(strict di id!(esc Uuid::new_v4()),vec![global_graph_options,activity_nodes, arcs].into_iter().flatten().collect())
+}
+
 ///
 /// Export the image of a [`DirectlyFollowsGraph`] as a SVG file
 ///
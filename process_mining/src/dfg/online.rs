@@ -0,0 +1,498 @@
+use std::borrow::Borrow;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::event_log::{
+    constants::TRACE_ID_NAME, event_log_struct::EventLogClassifier,
+    stream_xes::XESParsingTraceStream, Trace, XESEditableAttribute,
+};
+
+use super::dfg_struct::DirectlyFollowsGraph;
+
+/// Strategy used by the [`OnlineDFGMiner`] to bound its memory consumption
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnlineDFGMemoryBound {
+    /// Keep all open cases and all counts (exact, but memory grows with the stream)
+    Unbounded,
+    /// Lossy Counting with the given maximal (relative) approximation error (e.g., `0.001`)
+    ///
+    /// The stream is split into buckets of `ceil(1 / max_error)` events.
+    /// At the end of each bucket, infrequent cases, activities and directly-follows relations are removed,
+    /// such that every count is underestimated by at most `max_error` times the number of processed events.
+    /// Events of removed (infrequent) cases are treated as the start of a new case.
+    LossyCounting {
+        /// Maximal approximation error
+        max_error: f64,
+    },
+    /// Only consider events within the given time window (relative to the most recent event timestamp)
+    ///
+    /// Counts of older events are removed, and cases without any event in the window are forgotten.
+    SlidingWindow {
+        /// Size of the time window
+        window: Duration,
+    },
+}
+
+/// Event processed by the [`OnlineDFGMiner`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnlineEvent {
+    /// Case ID
+    pub case_id: String,
+    /// Activity label
+    pub activity: String,
+    /// Timestamp
+    pub timestamp: DateTime<FixedOffset>,
+    /// Whether this is the last event of the case (i.e., the case is closed afterwards)
+    pub last_in_case: bool,
+}
+
+/// Count with the maximal underestimation (`delta`) used in Lossy Counting
+#[derive(Debug, Clone, Copy, Default)]
+struct Count {
+    count: u64,
+    delta: u64,
+}
+
+/// State of an open case
+#[derive(Debug, Clone)]
+struct CaseState {
+    last_activity: String,
+    last_timestamp: DateTime<FixedOffset>,
+    /// Number of events of the case (used in Lossy Counting)
+    count: Count,
+}
+
+/// Count increment recorded for the sliding time window
+#[derive(Debug, Clone)]
+enum WindowEntry {
+    Activity(String),
+    DirectlyFollows(String, String),
+    Start(String),
+    End(String),
+    CaseEvent(String),
+}
+
+///
+/// Incremental (online) miner of [`DirectlyFollowsGraph`]s over unbounded event streams
+///
+/// Events (case ID, activity, timestamp) are processed one at a time (see [`OnlineDFGMiner::process_event`]).
+/// For each open case, only the last activity is kept, which is used to derive the directly-follows relations.
+/// Memory can be bounded using Lossy Counting or a sliding time window (see [`OnlineDFGMemoryBound`]).
+///
+/// A snapshot of the currently discovered [`DirectlyFollowsGraph`] can be obtained at any time using [`OnlineDFGMiner::snapshot`].
+///
+#[derive(Debug, Clone)]
+pub struct OnlineDFGMiner {
+    memory_bound: OnlineDFGMemoryBound,
+    num_events: u64,
+    cases: HashMap<String, CaseState>,
+    activities: HashMap<String, Count>,
+    df_relations: HashMap<(String, String), Count>,
+    start_activities: HashMap<String, Count>,
+    end_activities: HashMap<String, Count>,
+    window: VecDeque<(DateTime<FixedOffset>, WindowEntry)>,
+}
+
+/// Increment the count of `key`, initializing new entries with the passed Lossy Counting `delta`
+fn increment<K: Hash + Eq>(map: &mut HashMap<K, Count>, key: K, delta: u64) {
+    map.entry(key).or_insert(Count { count: 0, delta }).count += 1;
+}
+
+/// Decrement the count of `key`, removing it if the count reaches zero
+fn decrement<K: Hash + Eq>(map: &mut HashMap<K, Count>, key: &K) {
+    if let Some(c) = map.get_mut(key) {
+        c.count = c.count.saturating_sub(1);
+        if c.count == 0 {
+            map.remove(key);
+        }
+    }
+}
+
+impl OnlineDFGMiner {
+    /// Create a new [`OnlineDFGMiner`] using the given [`OnlineDFGMemoryBound`]
+    pub fn new(memory_bound: OnlineDFGMemoryBound) -> Self {
+        Self {
+            memory_bound,
+            num_events: 0,
+            cases: HashMap::new(),
+            activities: HashMap::new(),
+            df_relations: HashMap::new(),
+            start_activities: HashMap::new(),
+            end_activities: HashMap::new(),
+            window: VecDeque::new(),
+        }
+    }
+
+    /// Number of events processed so far
+    pub fn num_events(&self) -> u64 {
+        self.num_events
+    }
+
+    /// Number of currently tracked open cases
+    pub fn num_open_cases(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Size of the Lossy Counting buckets (if Lossy Counting is used)
+    fn bucket_width(&self) -> Option<u64> {
+        match self.memory_bound {
+            OnlineDFGMemoryBound::LossyCounting { max_error } => {
+                Some((1.0 / max_error.max(f64::MIN_POSITIVE)).ceil().max(1.0) as u64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Current Lossy Counting bucket (starting at `1`), or `0` if Lossy Counting is not used
+    fn current_bucket(&self) -> u64 {
+        match self.bucket_width() {
+            Some(width) => self.num_events.div_ceil(width).max(1),
+            None => 0,
+        }
+    }
+
+    /// Lossy Counting `delta` for newly inserted entries
+    fn new_delta(&self) -> u64 {
+        self.current_bucket().saturating_sub(1)
+    }
+
+    /// Process a single event
+    ///
+    /// Events are expected to arrive in timestamp order (relevant for [`OnlineDFGMemoryBound::SlidingWindow`]).
+    pub fn process_event(
+        &mut self,
+        case_id: &str,
+        activity: &str,
+        timestamp: DateTime<FixedOffset>,
+    ) {
+        self.num_events += 1;
+        if let OnlineDFGMemoryBound::SlidingWindow { window } = self.memory_bound {
+            self.evict_before(timestamp - window);
+        }
+        let delta = self.new_delta();
+        increment(&mut self.activities, activity.to_string(), delta);
+        let mut entries = vec![WindowEntry::Activity(activity.to_string())];
+        match self.cases.get_mut(case_id) {
+            Some(state) => {
+                let df = (state.last_activity.clone(), activity.to_string());
+                entries.push(WindowEntry::DirectlyFollows(df.0.clone(), df.1.clone()));
+                increment(&mut self.df_relations, df, delta);
+                state.last_activity = activity.to_string();
+                state.last_timestamp = timestamp;
+                state.count.count += 1;
+            }
+            None => {
+                increment(&mut self.start_activities, activity.to_string(), delta);
+                entries.push(WindowEntry::Start(activity.to_string()));
+                self.cases.insert(
+                    case_id.to_string(),
+                    CaseState {
+                        last_activity: activity.to_string(),
+                        last_timestamp: timestamp,
+                        count: Count { count: 1, delta },
+                    },
+                );
+            }
+        }
+        if let OnlineDFGMemoryBound::SlidingWindow { .. } = self.memory_bound {
+            entries.push(WindowEntry::CaseEvent(case_id.to_string()));
+            self.window
+                .extend(entries.into_iter().map(|e| (timestamp, e)));
+        }
+        if let Some(width) = self.bucket_width() {
+            if self.num_events % width == 0 {
+                self.prune_lossy_counting();
+            }
+        }
+    }
+
+    /// Process an [`OnlineEvent`]
+    ///
+    /// If the event is the last event of its case, the case is closed afterwards (see [`OnlineDFGMiner::close_case`]).
+    pub fn process(&mut self, event: &OnlineEvent) {
+        self.process_event(&event.case_id, &event.activity, event.timestamp);
+        if event.last_in_case {
+            self.close_case(&event.case_id);
+        }
+    }
+
+    /// Close a case, marking its last activity as an end activity
+    ///
+    /// Returns `false` if the case is not (or no longer) tracked.
+    pub fn close_case(&mut self, case_id: &str) -> bool {
+        match self.cases.remove(case_id) {
+            Some(state) => {
+                let delta = self.new_delta();
+                increment(&mut self.end_activities, state.last_activity.clone(), delta);
+                if let OnlineDFGMemoryBound::SlidingWindow { .. } = self.memory_bound {
+                    self.window
+                        .push_back((state.last_timestamp, WindowEntry::End(state.last_activity)));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove all window entries with a timestamp before `cutoff`
+    fn evict_before(&mut self, cutoff: DateTime<FixedOffset>) {
+        while self.window.front().is_some_and(|(ts, _)| *ts < cutoff) {
+            let (ts, entry) = self.window.pop_front().unwrap();
+            match entry {
+                WindowEntry::Activity(a) => decrement(&mut self.activities, &a),
+                WindowEntry::DirectlyFollows(a, b) => decrement(&mut self.df_relations, &(a, b)),
+                WindowEntry::Start(a) => decrement(&mut self.start_activities, &a),
+                WindowEntry::End(a) => decrement(&mut self.end_activities, &a),
+                WindowEntry::CaseEvent(case_id) => {
+                    if self
+                        .cases
+                        .get(&case_id)
+                        .is_some_and(|state| state.last_timestamp <= ts)
+                    {
+                        self.cases.remove(&case_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove all infrequent entries at the end of a Lossy Counting bucket
+    fn prune_lossy_counting(&mut self) {
+        let bucket = self.current_bucket();
+        let keep = |c: &Count| c.count + c.delta > bucket;
+        self.activities.retain(|_, c| keep(c));
+        self.df_relations.retain(|_, c| keep(c));
+        self.start_activities.retain(|_, c| keep(c));
+        self.end_activities.retain(|_, c| keep(c));
+        self.cases.retain(|_, state| keep(&state.count));
+    }
+
+    /// Process all events of the passed [`Trace`]s in timestamp order (see [`events_in_timestamp_order`])
+    ///
+    /// This is primarily useful for simulating an event stream based on recorded data.
+    pub fn process_traces<T: Borrow<Trace>, I: IntoIterator<Item = T>>(
+        &mut self,
+        traces: I,
+        classifier: &EventLogClassifier,
+        timestamp_key: &str,
+    ) {
+        for event in events_in_timestamp_order(traces, classifier, timestamp_key) {
+            self.process(&event);
+        }
+    }
+
+    /// Process all events of a [`XESParsingTraceStream`] in timestamp order (see [`events_in_timestamp_order`])
+    pub fn process_trace_stream(
+        &mut self,
+        stream: &mut XESParsingTraceStream<'_>,
+        classifier: &EventLogClassifier,
+        timestamp_key: &str,
+    ) {
+        self.process_traces(stream, classifier, timestamp_key)
+    }
+
+    /// Get a snapshot of the currently discovered [`DirectlyFollowsGraph`]
+    ///
+    /// End activities are only derived from closed cases (see [`OnlineDFGMiner::close_case`]).
+    pub fn snapshot(&self) -> DirectlyFollowsGraph<'static> {
+        let to_u32 = |c: &Count| u32::try_from(c.count).unwrap_or(u32::MAX);
+        let mut dfg = DirectlyFollowsGraph::new();
+        for (act, c) in &self.activities {
+            dfg.add_activity(act.clone(), to_u32(c));
+        }
+        for ((a, b), c) in &self.df_relations {
+            dfg.add_df_relation(a.clone().into(), b.clone().into(), to_u32(c));
+        }
+        for (act, c) in &self.start_activities {
+            dfg.add_start_activity_with_frequency(act.clone(), to_u32(c));
+        }
+        for (act, c) in &self.end_activities {
+            dfg.add_end_activity_with_frequency(act.clone(), to_u32(c));
+        }
+        dfg
+    }
+}
+
+/// Extract all events of the passed [`Trace`]s as [`OnlineEvent`]s, sorted by timestamp
+///
+/// The case ID of each event is the [`TRACE_ID_NAME`] attribute of its trace (or the index of the trace if not present).
+/// Activities are derived using the passed [`EventLogClassifier`].
+/// Events without a timestamp (under `timestamp_key`) are skipped.
+/// Events with equal timestamps keep their original order.
+///
+/// _Note_: All events are collected in memory, so this is intended for simulating event streams based on recorded data.
+pub fn events_in_timestamp_order<T: Borrow<Trace>, I: IntoIterator<Item = T>>(
+    traces: I,
+    classifier: &EventLogClassifier,
+    timestamp_key: &str,
+) -> Vec<OnlineEvent> {
+    let mut events = Vec::new();
+    for (i, trace) in traces.into_iter().enumerate() {
+        let trace: &Trace = trace.borrow();
+        let case_id = trace
+            .attributes
+            .get_by_key(TRACE_ID_NAME)
+            .and_then(|a| a.value.try_as_string())
+            .cloned()
+            .unwrap_or_else(|| i.to_string());
+        let first_index = events.len();
+        for e in &trace.events {
+            if let Some(ts) = e
+                .attributes
+                .get_by_key(timestamp_key)
+                .and_then(|a| a.value.try_as_date())
+            {
+                events.push(OnlineEvent {
+                    case_id: case_id.clone(),
+                    activity: classifier.get_class_identity(e),
+                    timestamp: *ts,
+                    last_in_case: false,
+                });
+            }
+        }
+        // Mark the latest event of the case (after sorting the events of the case, it is the last one)
+        if let Some(last) = events[first_index..]
+            .iter_mut()
+            .enumerate()
+            .max_by_key(|(j, e)| (e.timestamp, *j))
+            .map(|(_, e)| e)
+        {
+            last.last_in_case = true;
+        }
+    }
+    events.sort_by_key(|e| e.timestamp);
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{constants::TIMESTAMP_NAME, AttributeValue, Attributes, Event};
+
+    fn ts(minutes: i64) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2024-01-01T00:00:00+00:00").unwrap()
+            + Duration::minutes(minutes)
+    }
+
+    fn trace(case_id: &str, events: &[(&str, i64)]) -> Trace {
+        let mut attributes = Attributes::new();
+        attributes.add_to_attributes(
+            TRACE_ID_NAME.to_string(),
+            AttributeValue::String(case_id.to_string()),
+        );
+        Trace {
+            attributes,
+            events: events
+                .iter()
+                .map(|(act, m)| {
+                    let mut e = Event::new(act.to_string());
+                    e.attributes.add_to_attributes(
+                        TIMESTAMP_NAME.to_string(),
+                        AttributeValue::Date(ts(*m)),
+                    );
+                    e
+                })
+                .collect(),
+        }
+    }
+
+    fn sample_traces() -> Vec<Trace> {
+        vec![
+            trace("1", &[("a", 0), ("b", 2), ("c", 4)]),
+            trace("2", &[("a", 1), ("c", 3)]),
+            trace("3", &[("a", 10), ("b", 11), ("d", 12)]),
+        ]
+    }
+
+    #[test]
+    fn test_events_in_timestamp_order() {
+        let events = events_in_timestamp_order(
+            sample_traces(),
+            &EventLogClassifier::default(),
+            TIMESTAMP_NAME,
+        );
+        let order: Vec<(&str, &str)> = events
+            .iter()
+            .map(|e| (e.case_id.as_str(), e.activity.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("1", "a"),
+                ("2", "a"),
+                ("1", "b"),
+                ("2", "c"),
+                ("1", "c"),
+                ("3", "a"),
+                ("3", "b"),
+                ("3", "d")
+            ]
+        );
+        assert_eq!(events.iter().filter(|e| e.last_in_case).count(), 3);
+    }
+
+    #[test]
+    fn test_online_dfg_unbounded() {
+        let traces = sample_traces();
+        let mut miner = OnlineDFGMiner::new(OnlineDFGMemoryBound::Unbounded);
+        miner.process_traces(&traces, &EventLogClassifier::default(), TIMESTAMP_NAME);
+        assert_eq!(miner.num_events(), 8);
+        assert_eq!(miner.num_open_cases(), 0);
+        let online = miner.snapshot();
+        let offline =
+            DirectlyFollowsGraph::create_from_traces(&traces, &EventLogClassifier::default());
+        assert_eq!(online.activities, offline.activities);
+        assert_eq!(
+            online.directly_follows_relations,
+            offline.directly_follows_relations
+        );
+        assert_eq!(online.start_activities, offline.start_activities);
+        assert_eq!(online.end_activities, offline.end_activities);
+    }
+
+    #[test]
+    fn test_online_dfg_sliding_window() {
+        let mut miner = OnlineDFGMiner::new(OnlineDFGMemoryBound::SlidingWindow {
+            window: Duration::minutes(5),
+        });
+        miner.process_traces(
+            sample_traces(),
+            &EventLogClassifier::default(),
+            TIMESTAMP_NAME,
+        );
+        let dfg = miner.snapshot();
+        // Only the events of case 3 are in the window
+        assert_eq!(dfg.activities.len(), 3);
+        assert_eq!(dfg.activities["a"], 1);
+        assert!(!dfg
+            .directly_follows_relations
+            .contains_key(&("a".into(), "c".into())));
+        assert_eq!(dfg.directly_follows_relations[&("b".into(), "d".into())], 1);
+        assert!(dfg.is_end_activity("d"));
+        assert!(!dfg.is_end_activity("c"));
+    }
+
+    #[test]
+    fn test_online_dfg_lossy_counting() {
+        let mut miner =
+            OnlineDFGMiner::new(OnlineDFGMemoryBound::LossyCounting { max_error: 0.25 });
+        // Many cases with the frequent path a -> b and one case with the infrequent path a -> x
+        for i in 0..20 {
+            let case_id = i.to_string();
+            miner.process_event(&case_id, "a", ts(i));
+            let act = if i == 0 { "x" } else { "b" };
+            miner.process_event(&case_id, act, ts(i));
+            miner.close_case(&case_id);
+        }
+        let dfg = miner.snapshot();
+        assert!(dfg.directly_follows_relations[&("a".into(), "b".into())] >= 19 - 10);
+        assert!(!dfg
+            .directly_follows_relations
+            .contains_key(&("a".into(), "x".into())));
+        assert!(!dfg.activities.contains_key("x"));
+        assert_eq!(miner.num_open_cases(), 0);
+    }
+}
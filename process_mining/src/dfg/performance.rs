@@ -0,0 +1,350 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::event_log::{event_log_struct::EventLogClassifier, XESEditableAttribute};
+use crate::EventLog;
+
+use super::dfg_struct::DirectlyFollowsGraph;
+
+/// Summary statistics of a set of durations
+///
+/// All durations are given in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DurationStatistics {
+    /// Number of observed durations
+    pub count: usize,
+    /// Minimum duration
+    pub min: f64,
+    /// Maximum duration
+    pub max: f64,
+    /// Mean duration
+    pub mean: f64,
+    /// Median duration
+    pub median: f64,
+    /// Sum of all durations
+    pub sum: f64,
+}
+
+impl DurationStatistics {
+    /// Compute [`DurationStatistics`] from a list of durations (in milliseconds)
+    ///
+    /// Returns `None` if no durations are passed
+    pub fn from_durations(mut durations: Vec<f64>) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        durations.sort_by(|a, b| a.total_cmp(b));
+        let count = durations.len();
+        let sum: f64 = durations.iter().sum();
+        let median = if count % 2 == 0 {
+            (durations[count / 2 - 1] + durations[count / 2]) / 2.0
+        } else {
+            durations[count / 2]
+        };
+        Some(Self {
+            count,
+            min: durations[0],
+            max: durations[count - 1],
+            mean: sum / count as f64,
+            median,
+            sum,
+        })
+    }
+
+    /// Get the value of the specified [`PerformanceMetric`]
+    pub fn get(&self, metric: PerformanceMetric) -> f64 {
+        match metric {
+            PerformanceMetric::Min => self.min,
+            PerformanceMetric::Max => self.max,
+            PerformanceMetric::Mean => self.mean,
+            PerformanceMetric::Median => self.median,
+            PerformanceMetric::Sum => self.sum,
+        }
+    }
+}
+
+/// Performance metric of [`DurationStatistics`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PerformanceMetric {
+    /// Minimum duration
+    Min,
+    /// Maximum duration
+    Max,
+    /// Mean duration
+    Mean,
+    /// Median duration
+    Median,
+    /// Sum of all durations
+    Sum,
+}
+
+/// Performance annotations of a [`DirectlyFollowsGraph`]
+///
+/// Contains timing information derived from event timestamps, as well as case-level frequencies
+/// (i.e., in how many cases an activity or directly-follows relation occurs at least once).
+#[serde_as]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DFGPerformance {
+    /// Attribute key of the timestamps used for computing the durations
+    pub timestamp_key: String,
+    /// Durations between the events of directly-follows relations
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub df_durations: HashMap<(String, String), DurationStatistics>,
+    /// Sojourn times of activities
+    ///
+    /// The sojourn time of an event is the time since the preceding event in the same case
+    /// (i.e., the time the case spent waiting for and executing the activity).
+    /// The first event of each case has no sojourn time.
+    pub sojourn_times: HashMap<String, DurationStatistics>,
+    /// Number of cases in which an activity occurs
+    pub activity_case_frequencies: HashMap<String, u32>,
+    /// Number of cases in which a directly-follows relation occurs
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub df_case_frequencies: HashMap<(String, String), u32>,
+}
+
+impl DFGPerformance {
+    /// Compute the [`DFGPerformance`] of an [`EventLog`]
+    ///
+    /// Uses the specified [`EventLogClassifier`] to derive the 'activity' names and the timestamp attribute key to derive durations.
+    /// For both, the global event attributes of the log are used as fallback for missing event attributes.
+    /// Events without a timestamp are ignored for the durations, but still count towards the case frequencies.
+    pub fn create_from_log(
+        event_log: &EventLog,
+        classifier: &EventLogClassifier,
+        timestamp_key: &str,
+    ) -> Self {
+        let mut df_durations: HashMap<(String, String), Vec<f64>> = HashMap::new();
+        let mut sojourn_times: HashMap<String, Vec<f64>> = HashMap::new();
+        let mut activity_case_frequencies: HashMap<String, u32> = HashMap::new();
+        let mut df_case_frequencies: HashMap<(String, String), u32> = HashMap::new();
+
+        for t in &event_log.traces {
+            let mut acts_in_case: HashSet<String> = HashSet::new();
+            let mut dfs_in_case: HashSet<(String, String)> = HashSet::new();
+            let mut last_event: Option<(String, Option<f64>)> = None;
+            for e in &t.events {
+                let act =
+                    classifier.get_class_identity_with_globals(e, &event_log.global_event_attrs);
+                let ts = e
+                    .attributes
+                    .get_by_key_or_global(timestamp_key, &event_log.global_event_attrs)
+                    .and_then(|a| a.value.try_as_date())
+                    .map(|d| d.timestamp_millis() as f64);
+                if let Some((last_act, last_ts)) = last_event.take() {
+                    if let (Some(last_ts), Some(ts)) = (last_ts, ts) {
+                        let duration = ts - last_ts;
+                        df_durations
+                            .entry((last_act.clone(), act.clone()))
+                            .or_default()
+                            .push(duration);
+                        sojourn_times.entry(act.clone()).or_default().push(duration);
+                    }
+                    dfs_in_case.insert((last_act, act.clone()));
+                }
+                acts_in_case.insert(act.clone());
+                last_event = Some((act, ts));
+            }
+            for act in acts_in_case {
+                *activity_case_frequencies.entry(act).or_default() += 1;
+            }
+            for df in dfs_in_case {
+                *df_case_frequencies.entry(df).or_default() += 1;
+            }
+        }
+
+        Self {
+            timestamp_key: timestamp_key.to_string(),
+            df_durations: df_durations
+                .into_iter()
+                .filter_map(|(df, d)| Some((df, DurationStatistics::from_durations(d)?)))
+                .collect(),
+            sojourn_times: sojourn_times
+                .into_iter()
+                .filter_map(|(act, d)| Some((act, DurationStatistics::from_durations(d)?)))
+                .collect(),
+            activity_case_frequencies,
+            df_case_frequencies,
+        }
+    }
+}
+
+/// What to show as label of directly-follows edges when visualizing a [`DirectlyFollowsGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum DFGEdgeLabel {
+    /// Number of occurrences (i.e., event-level frequency)
+    #[default]
+    Frequency,
+    /// Number of cases (requires [`DFGPerformance`] annotations)
+    CaseFrequency,
+    /// Duration according to the passed metric (requires [`DFGPerformance`] annotations)
+    Performance(PerformanceMetric),
+}
+
+impl DirectlyFollowsGraph<'_> {
+    /// Get the label of the directly-follows relation `(from, to)` as a [`String`]
+    ///
+    /// Returns `None` if the requested information is not available (e.g., if the relation does not exist or
+    /// performance labels are requested but no [`DFGPerformance`] annotations are present)
+    pub fn get_df_label(&self, from: &str, to: &str, label: DFGEdgeLabel) -> Option<String> {
+        match label {
+            DFGEdgeLabel::Frequency => self
+                .directly_follows_relations
+                .get(&(from.into(), to.into()))
+                .map(|f| f.to_string()),
+            DFGEdgeLabel::CaseFrequency => self
+                .performance
+                .as_ref()?
+                .df_case_frequencies
+                .get(&(from.to_string(), to.to_string()))
+                .map(|f| f.to_string()),
+            DFGEdgeLabel::Performance(metric) => self
+                .performance
+                .as_ref()?
+                .df_durations
+                .get(&(from.to_string(), to.to_string()))
+                .map(|stats| format_duration(stats.get(metric))),
+        }
+    }
+}
+
+/// Format a duration (in milliseconds) in a human-readable way (e.g., `2d 3h` or `1.5s`)
+pub fn format_duration(duration_ms: f64) -> String {
+    const SECOND: f64 = 1000.0;
+    const MINUTE: f64 = 60.0 * SECOND;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    let abs = duration_ms.abs();
+    let sign = if duration_ms < 0.0 { "-" } else { "" };
+    if abs >= DAY {
+        let days = (abs / DAY).floor();
+        let hours = ((abs - days * DAY) / HOUR).floor();
+        format!("{sign}{days}d {hours}h")
+    } else if abs >= HOUR {
+        let hours = (abs / HOUR).floor();
+        let minutes = ((abs - hours * HOUR) / MINUTE).floor();
+        format!("{sign}{hours}h {minutes}m")
+    } else if abs >= MINUTE {
+        let minutes = (abs / MINUTE).floor();
+        let seconds = ((abs - minutes * MINUTE) / SECOND).floor();
+        format!("{sign}{minutes}m {seconds}s")
+    } else if abs >= SECOND {
+        format!("{sign}{:.1}s", abs / SECOND)
+    } else {
+        format!("{sign}{abs:.0}ms")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::event_log::{
+        constants::{ACTIVITY_NAME, TIMESTAMP_NAME},
+        Attribute, AttributeValue, Event, Trace, XESEditableAttribute,
+    };
+
+    fn event(act: &str, ts: &str) -> Event {
+        let mut e = Event::new(act.to_string());
+        e.attributes.add_to_attributes(
+            TIMESTAMP_NAME.to_string(),
+            AttributeValue::Date(DateTime::parse_from_rfc3339(ts).unwrap()),
+        );
+        e
+    }
+
+    #[test]
+    fn test_dfg_performance() {
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![
+                Trace {
+                    attributes: Vec::new(),
+                    events: vec![
+                        event("a", "2024-01-01T10:00:00+00:00"),
+                        event("b", "2024-01-01T10:01:00+00:00"),
+                        event("a", "2024-01-01T10:02:00+00:00"),
+                        event("b", "2024-01-01T10:05:00+00:00"),
+                    ],
+                },
+                Trace {
+                    attributes: Vec::new(),
+                    events: vec![
+                        event("a", "2024-01-01T10:00:00+00:00"),
+                        event("b", "2024-01-01T12:00:00+00:00"),
+                    ],
+                },
+            ],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+        let dfg = DirectlyFollowsGraph::create_from_log_with_performance(
+            &log,
+            &EventLogClassifier::default(),
+            TIMESTAMP_NAME,
+        );
+        assert_eq!(dfg.directly_follows_relations[&("a".into(), "b".into())], 3);
+        let perf = dfg.performance.as_ref().unwrap();
+        assert_eq!(perf.df_case_frequencies[&("a".into(), "b".into())], 2);
+        assert_eq!(perf.activity_case_frequencies["a"], 2);
+        let ab = perf.df_durations[&("a".into(), "b".into())];
+        assert_eq!(ab.count, 3);
+        assert_eq!(ab.min, 60_000.0);
+        assert_eq!(ab.max, 7_200_000.0);
+        assert_eq!(ab.median, 180_000.0);
+        assert_eq!(ab.sum, 7_440_000.0);
+        assert_eq!(perf.sojourn_times["a"].count, 1);
+        assert!(!perf.sojourn_times.contains_key("c"));
+
+        assert_eq!(
+            dfg.get_df_label("a", "b", DFGEdgeLabel::Frequency),
+            Some("3".to_string())
+        );
+        assert_eq!(
+            dfg.get_df_label("a", "b", DFGEdgeLabel::CaseFrequency),
+            Some("2".to_string())
+        );
+        assert_eq!(
+            dfg.get_df_label("a", "b", DFGEdgeLabel::Performance(PerformanceMetric::Max)),
+            Some("2h 0m".to_string())
+        );
+        assert_eq!(dfg.get_df_label("b", "b", DFGEdgeLabel::Frequency), None);
+    }
+
+    #[test]
+    fn test_dfg_performance_global_activity() {
+        let mut unnamed = event("a", "2024-01-01T10:30:00+00:00");
+        unnamed.attributes.remove_with_key(ACTIVITY_NAME);
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![Trace {
+                attributes: Vec::new(),
+                events: vec![event("a", "2024-01-01T10:00:00+00:00"), unnamed],
+            }],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: Some(vec![Attribute::new(
+                ACTIVITY_NAME.to_string(),
+                AttributeValue::String("b".to_string()),
+            )]),
+        };
+        let dfg = DirectlyFollowsGraph::create_from_log_with_performance(
+            &log,
+            &EventLogClassifier::default(),
+            TIMESTAMP_NAME,
+        );
+        assert_eq!(dfg.directly_follows_relations[&("a".into(), "b".into())], 1);
+        let perf = dfg.performance.as_ref().unwrap();
+        assert_eq!(
+            perf.df_durations[&("a".into(), "b".into())].sum,
+            1_800_000.0
+        );
+        assert_eq!(perf.activity_case_frequencies["b"], 1);
+    }
+}
@@ -0,0 +1,275 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::utils::graph_text_export::{
+    dot_attributes, escape_dot, escape_mermaid, CytoscapeElement, CytoscapeGraph,
+};
+
+use super::dfg_struct::DirectlyFollowsGraph;
+use super::performance::DFGEdgeLabel;
+
+/// Get the activities of a [`DirectlyFollowsGraph`] sorted by name, with start activities first and end activities last
+fn sorted_activities<'b>(dfg: &'b DirectlyFollowsGraph<'_>) -> Vec<(&'b String, u32)> {
+    let mut acts: Vec<(&String, u32)> = dfg.activities.iter().map(|(a, f)| (a, *f)).collect();
+    let rank = |a: &str| match (dfg.is_start_activity(a), dfg.is_end_activity(a)) {
+        (true, _) => 0,
+        (false, false) => 1,
+        (false, true) => 2,
+    };
+    acts.sort_by(|(a, _), (b, _)| match rank(a).cmp(&rank(b)) {
+        Ordering::Equal => a.cmp(b),
+        o => o,
+    });
+    acts
+}
+
+/// Get the directly-follows relations of a [`DirectlyFollowsGraph`] sorted by source and target activity
+fn sorted_df_relations<'b>(dfg: &'b DirectlyFollowsGraph<'_>) -> Vec<(&'b str, &'b str, u32)> {
+    let mut dfs: Vec<(&str, &str, u32)> = dfg
+        .directly_follows_relations
+        .iter()
+        .map(|((a, b), f)| (a.as_ref(), b.as_ref(), *f))
+        .collect();
+    dfs.sort();
+    dfs
+}
+
+/// Get the label of a directly-follows edge (or `None` for frequency labels of `1`, matching the graphviz export)
+fn edge_label(
+    dfg: &DirectlyFollowsGraph<'_>,
+    from: &str,
+    to: &str,
+    frequency: u32,
+    label: DFGEdgeLabel,
+) -> Option<String> {
+    match label {
+        DFGEdgeLabel::Frequency if frequency == 1 => None,
+        _ => dfg.get_df_label(from, to, label),
+    }
+}
+
+///
+/// Export a [`DirectlyFollowsGraph`] to DOT source code (used in Graphviz)
+///
+/// In contrast to the `image_export` module, this does not require the `graphviz-export` feature.
+/// The resulting graph uses the same visual notation: Start activities are filled green and end activities red.
+/// Edges are labeled according to the passed [`DFGEdgeLabel`].
+///
+pub fn export_dfg_to_dot(dfg: &DirectlyFollowsGraph<'_>, label: DFGEdgeLabel) -> String {
+    let mut res = String::from("digraph {\n  rankdir=LR;\n");
+    for (act, freq) in sorted_activities(dfg) {
+        let fill_color = match (dfg.is_start_activity(act), dfg.is_end_activity(act)) {
+            (true, true) => "\"#4B9969:#D4001F\"",
+            (true, false) => "\"#4B9969\"",
+            (false, true) => "\"#D4001F\"",
+            (false, false) => "\"white\"",
+        };
+        let attrs = dot_attributes(&[
+            ("label", format!("\"{}: {freq}\"", escape_dot(act))),
+            ("gradientangle", "45".to_string()),
+            ("shape", "box".to_string()),
+            ("fontsize", "12".to_string()),
+            ("style", "filled".to_string()),
+            ("fillcolor", fill_color.to_string()),
+            ("width", "1".to_string()),
+            ("height", "0.5".to_string()),
+        ]);
+        writeln!(res, "  \"{}\"{attrs};", escape_dot(act)).unwrap();
+    }
+    for (from, to, freq) in sorted_df_relations(dfg) {
+        let attrs = match edge_label(dfg, from, to, freq, label) {
+            Some(l) => dot_attributes(&[("label", format!("\"{}\"", escape_dot(&l)))]),
+            None => String::new(),
+        };
+        writeln!(
+            res,
+            "  \"{}\" -> \"{}\"{attrs};",
+            escape_dot(from),
+            escape_dot(to)
+        )
+        .unwrap();
+    }
+    res.push_str("}\n");
+    res
+}
+
+///
+/// Export a [`DirectlyFollowsGraph`] to a Mermaid flowchart
+///
+/// Start activities are filled green and end activities red
+/// (activities which are both get the classes `start` and `end`, where the latter determines the color).
+/// Edges are labeled according to the passed [`DFGEdgeLabel`].
+/// As Mermaid node IDs need to be simple identifiers, activities are numbered (`a0`, `a1`, ...).
+///
+pub fn export_dfg_to_mermaid(dfg: &DirectlyFollowsGraph<'_>, label: DFGEdgeLabel) -> String {
+    let mut res = String::from("flowchart LR\n");
+    let mut node_ids: HashMap<&str, String> = HashMap::new();
+    let (mut start_ids, mut end_ids) = (Vec::new(), Vec::new());
+    for (i, (act, freq)) in sorted_activities(dfg).into_iter().enumerate() {
+        let id = format!("a{i}");
+        writeln!(res, "  {id}[\"{}: {freq}\"]", escape_mermaid(act)).unwrap();
+        if dfg.is_start_activity(act) {
+            start_ids.push(id.clone());
+        }
+        if dfg.is_end_activity(act) {
+            end_ids.push(id.clone());
+        }
+        node_ids.insert(act.as_str(), id);
+    }
+    for (from, to, freq) in sorted_df_relations(dfg) {
+        // Relations can refer to activities not contained in `activities`
+        for act in [from, to] {
+            if !node_ids.contains_key(act) {
+                let id = format!("a{}", node_ids.len());
+                writeln!(res, "  {id}[\"{}\"]", escape_mermaid(act)).unwrap();
+                node_ids.insert(act, id);
+            }
+        }
+        let (from_id, to_id) = (&node_ids[from], &node_ids[to]);
+        match edge_label(dfg, from, to, freq, label) {
+            Some(l) => {
+                writeln!(res, "  {from_id} -->|\"{}\"| {to_id}", escape_mermaid(&l)).unwrap()
+            }
+            None => writeln!(res, "  {from_id} --> {to_id}").unwrap(),
+        }
+    }
+    for (class, color, ids) in [("start", "#4B9969", start_ids), ("end", "#D4001F", end_ids)] {
+        if !ids.is_empty() {
+            writeln!(res, "  classDef {class} fill:{color}").unwrap();
+            writeln!(res, "  class {} {class}", ids.join(",")).unwrap();
+        }
+    }
+    res
+}
+
+///
+/// Export a [`DirectlyFollowsGraph`] to a [`CytoscapeGraph`] (JSON graph format of Cytoscape.js)
+///
+/// As activities can contain arbitrary characters, nodes are numbered (`a0`, `a1`, ...) and edges as well (`df0`, `df1`, ...).
+/// Activity nodes contain the activity as `label`, their `frequency` as well as their `start_frequency` and `end_frequency`
+/// (and have the classes `start` and `end`, respectively).
+/// Edges contain their `frequency` and, if [`DirectlyFollowsGraph::performance`] annotations are available,
+/// also their `case_frequency` and `mean_duration` (in milliseconds).
+///
+pub fn export_dfg_to_cytoscape(dfg: &DirectlyFollowsGraph<'_>) -> CytoscapeGraph {
+    let mut elements = Vec::new();
+    let mut node_ids: HashMap<&str, String> = HashMap::new();
+    for (i, (act, freq)) in sorted_activities(dfg).into_iter().enumerate() {
+        let start_freq = dfg.start_activities.get(act).copied().unwrap_or_default();
+        let end_freq = dfg.end_activities.get(act).copied().unwrap_or_default();
+        let mut classes = vec!["activity"];
+        if start_freq > 0 {
+            classes.push("start");
+        }
+        if end_freq > 0 {
+            classes.push("end");
+        }
+        let id = format!("a{i}");
+        elements.push(
+            CytoscapeElement::node(id.clone(), act.clone(), &classes)
+                .with_data("frequency", freq)
+                .with_data("start_frequency", start_freq)
+                .with_data("end_frequency", end_freq),
+        );
+        node_ids.insert(act.as_str(), id);
+    }
+    for (i, (from, to, freq)) in sorted_df_relations(dfg).into_iter().enumerate() {
+        // Relations can refer to activities not contained in `activities`
+        for act in [from, to] {
+            if !node_ids.contains_key(act) {
+                let id = format!("a{}", node_ids.len());
+                elements.push(CytoscapeElement::node(id.clone(), act, &["activity"]));
+                node_ids.insert(act, id);
+            }
+        }
+        let mut el = CytoscapeElement::edge(
+            format!("df{i}"),
+            node_ids[from].clone(),
+            node_ids[to].clone(),
+            &["df"],
+        )
+        .with_data("frequency", freq);
+        if let Some(perf) = &dfg.performance {
+            let key = (from.to_string(), to.to_string());
+            if let Some(case_freq) = perf.df_case_frequencies.get(&key) {
+                el = el.with_data("case_frequency", *case_freq);
+            }
+            if let Some(stats) = perf.df_durations.get(&key) {
+                el = el.with_data("mean_duration", stats.mean);
+            }
+        }
+        elements.push(el);
+    }
+    CytoscapeGraph { elements }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dfg() -> DirectlyFollowsGraph<'static> {
+        let mut dfg = DirectlyFollowsGraph::new();
+        dfg.add_activity_sequence(["a", "b", "c"].map(String::from), 3);
+        dfg.add_activity_sequence(["a", "c"].map(String::from), 1);
+        dfg.add_activity_sequence(["a", "x \"y\""].map(String::from), 1);
+        dfg
+    }
+
+    #[test]
+    fn test_dfg_dot() {
+        let dot = export_dfg_to_dot(&sample_dfg(), DFGEdgeLabel::Frequency);
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("\"a\" -> \"b\" [label=\"3\"];"));
+        assert!(dot.contains("\"a\" -> \"c\";"));
+        assert!(dot.contains("\"x \\\"y\\\"\""));
+        assert!(dot.contains("label=\"a: 5\""));
+        assert!(dot.contains("fillcolor=\"#4B9969\""));
+    }
+
+    #[test]
+    fn test_dfg_mermaid() {
+        let mermaid = export_dfg_to_mermaid(&sample_dfg(), DFGEdgeLabel::Frequency);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("a0[\"a: 5\"]"));
+        assert!(mermaid.contains("a0 -->|\"3\"| a1"));
+        assert!(mermaid.contains("class a0 start"));
+        assert_eq!(mermaid.matches("-->").count(), 4);
+
+        // Activities which are start and end activities get both classes
+        let mut dfg = sample_dfg();
+        dfg.add_activity_sequence(["d".to_string()], 1);
+        let mermaid = export_dfg_to_mermaid(&dfg, DFGEdgeLabel::Frequency);
+        assert!(mermaid.contains("a1[\"d: 1\"]"));
+        assert!(mermaid.contains("class a0,a1 start"));
+        assert!(mermaid.contains("class a1,a3,a4 end"));
+    }
+
+    #[test]
+    fn test_dfg_cytoscape() {
+        let graph = export_dfg_to_cytoscape(&sample_dfg());
+        assert_eq!(graph.elements.len(), 4 + 4);
+        let a = &graph.elements[0];
+        assert_eq!(a.data["id"], "a0");
+        assert_eq!(a.data["label"], "a");
+        assert_eq!(a.data["start_frequency"], 5);
+        assert_eq!(a.classes, vec!["activity", "start"]);
+        assert!(graph.elements.iter().any(|e| e.data["id"] == "df0"
+            && e.data["source"] == "a0"
+            && e.data["target"] == "a1"
+            && e.data["frequency"] == 3));
+    }
+
+    #[test]
+    fn test_dfg_cytoscape_unique_ids() {
+        // Activity names containing `->` must not lead to clashing edge IDs
+        let mut dfg = DirectlyFollowsGraph::new();
+        dfg.add_activity_sequence(["a->b", "c"].map(String::from), 1);
+        dfg.add_activity_sequence(["a", "b->c"].map(String::from), 1);
+        let graph = export_dfg_to_cytoscape(&dfg);
+        assert_eq!(graph.elements.len(), 4 + 2);
+        let ids: std::collections::HashSet<&serde_json::Value> =
+            graph.elements.iter().map(|e| &e.data["id"]).collect();
+        assert_eq!(ids.len(), graph.elements.len());
+    }
+}
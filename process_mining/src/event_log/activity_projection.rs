@@ -1,22 +1,32 @@
+use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
 
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::event_log_struct::{Attribute, AttributeValue, EventLog};
+use super::event_log_struct::{Attribute, EventLog, EventLogClassifier};
 
-use super::constants::ACTIVITY_NAME;
-use super::{Event, XESEditableAttribute};
+use super::{Event, Trace, XESEditableAttribute};
 
 /// Name of `START_ACTIVITY` (can be added to [`EventLogActivityProjection`]/[`EventLog`] to mark START of traces)
 pub const START_ACTIVITY: &str = "__START";
 /// Name of `START_ACTIVITY` (can be added to [`EventLogActivityProjection`]/[`EventLog`] to mark END of traces)
 pub const END_ACTIVITY: &str = "__END";
+/// Activity label used for events without any of the attributes of the used [`EventLogClassifier`]
+pub const NO_ACTIVITY: &str = "No Activity";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 /// Projection of an event log on just activity labels
 ///
-/// Currently assumes a default activity name ([`ACTIVITY_NAME`])
+/// The activity labels are derived using an [`EventLogClassifier`] (see [`EventLogActivityProjection::classifier`]).
+/// By default (e.g., when using the `From` implementations), the [`EventLogClassifier::default`] is used,
+/// which only considers the activity name ([`ACTIVITY_NAME`](super::constants::ACTIVITY_NAME)).
+///
+/// To use other classifiers, see [`EventLogActivityProjection::from_event_log_with_classifier`]
+/// and [`EventLogActivityProjection::from_traces_with_classifier`].
 pub struct EventLogActivityProjection {
     /// All activities
     ///
@@ -32,6 +42,9 @@ pub struct EventLogActivityProjection {
     ///
     /// Each pair represents one activity projection and the number of occurences in the log
     pub traces: Vec<(Vec<usize>, u64)>,
+    /// The [`EventLogClassifier`] used to derive the activity labels
+    #[serde(default)]
+    pub classifier: EventLogClassifier,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -128,62 +141,51 @@ impl<'a, 'b> From<&'b mut super::stream_xes::XESParsingTraceStream<'a>>
     for EventLogActivityProjection
 {
     fn from(value: &mut super::stream_xes::XESParsingTraceStream<'a>) -> Self {
-        let mut act_to_index: HashMap<String, usize> = HashMap::new();
-        let mut activities: Vec<String> = Vec::new();
-        let mut traces: HashMap<Vec<usize>, u64> = HashMap::new();
-        for t in value {
-            let mut trace_acts: Vec<usize> = Vec::with_capacity(t.events.len());
-            for e in t.events {
-                let act = match e.attributes.get_by_key(ACTIVITY_NAME) {
-                    Some(act_attr) => match &act_attr.value {
-                        AttributeValue::String(s) => s.as_str(),
-                        _ => "No Activity",
-                    },
-                    None => "No Activity",
-                };
-                if let Some(index) = act_to_index.get(act) {
-                    trace_acts.push(*index);
-                } else {
-                    let new_act_index = activities.len();
-                    activities.push(act.to_string());
-                    act_to_index.insert(act.to_string(), new_act_index);
-                    trace_acts.push(new_act_index)
-                }
-            }
-
-            *traces.entry(trace_acts).or_insert(0) += 1;
-        }
-        Self {
-            activities,
-            act_to_index,
-            traces: traces.into_iter().collect(),
-        }
+        Self::from_traces_with_classifier(value, &EventLogClassifier::default(), &None)
     }
 }
 impl From<&EventLog> for EventLogActivityProjection {
     fn from(val: &EventLog) -> Self {
-        let acts_per_trace: Vec<Vec<String>> = val
+        Self::from_event_log_with_classifier(val, &EventLogClassifier::default())
+    }
+}
+
+/// Get the activity label of an event using the given classifier
+///
+/// Events for which none of the classifier attributes are present (as string values) are labeled `No Activity`
+fn get_activity_label(
+    classifier: &EventLogClassifier,
+    ev: &Event,
+    global_attrs: &Option<Vec<Attribute>>,
+) -> String {
+    let has_any_key = classifier.keys.iter().any(|k| {
+        ev.attributes
+            .get_by_key_or_global(k, global_attrs)
+            .and_then(|a| a.value.try_as_string())
+            .is_some()
+    });
+    if has_any_key {
+        classifier.get_class_identity_with_globals(ev, global_attrs)
+    } else {
+        NO_ACTIVITY.to_string()
+    }
+}
+
+impl EventLogActivityProjection {
+    ///
+    /// Construct an [`EventLogActivityProjection`] from an [`EventLog`] using the specified [`EventLogClassifier`] to derive the activity labels
+    ///
+    /// The classifier can, for instance, be one of the classifiers defined in the event log itself (see [`EventLog::get_classifier_by_name`]).
+    /// Global event attributes of the log are used as fallback for missing event attributes.
+    ///
+    pub fn from_event_log_with_classifier(log: &EventLog, classifier: &EventLogClassifier) -> Self {
+        let acts_per_trace: Vec<Vec<String>> = log
             .traces
             .par_iter()
             .map(|t| -> Vec<String> {
                 t.events
                     .iter()
-                    .map(|e| {
-                        match e
-                            .attributes
-                            .get_by_key(ACTIVITY_NAME)
-                            .cloned()
-                            .unwrap_or(Attribute {
-                                key: ACTIVITY_NAME.into(),
-                                value: AttributeValue::String("No Activity".into()),
-                                own_attributes: None,
-                            })
-                            .value
-                        {
-                            AttributeValue::String(s) => s,
-                            _ => "No Activity".into(),
-                        }
-                    })
+                    .map(|e| get_activity_label(classifier, e, &log.global_event_attrs))
                     .collect::<Vec<String>>()
             })
             .collect();
@@ -208,11 +210,49 @@ impl From<&EventLog> for EventLogActivityProjection {
             activities,
             act_to_index,
             traces: traces_set.into_iter().collect(),
+            classifier: classifier.clone(),
+        }
+    }
+
+    ///
+    /// Construct an [`EventLogActivityProjection`] from an iterator of [`Trace`]s using the specified [`EventLogClassifier`] to derive the activity labels
+    ///
+    /// Traces are processed one at a time, so this can also be used with a (constant memory) trace stream,
+    /// like [`super::stream_xes::XESParsingTraceStream`]. The global event attributes (e.g., from [`super::stream_xes::XESOuterLogData`]) are used as fallback for missing event attributes.
+    ///
+    pub fn from_traces_with_classifier<T: Borrow<Trace>, I: IntoIterator<Item = T>>(
+        traces: I,
+        classifier: &EventLogClassifier,
+        global_event_attrs: &Option<Vec<Attribute>>,
+    ) -> Self {
+        let mut act_to_index: HashMap<String, usize> = HashMap::new();
+        let mut activities: Vec<String> = Vec::new();
+        let mut trace_variants: HashMap<Vec<usize>, u64> = HashMap::new();
+        for t in traces {
+            let t: &Trace = t.borrow();
+            let mut trace_acts: Vec<usize> = Vec::with_capacity(t.events.len());
+            for e in &t.events {
+                let act = get_activity_label(classifier, e, global_event_attrs);
+                if let Some(index) = act_to_index.get(&act) {
+                    trace_acts.push(*index);
+                } else {
+                    let new_act_index = activities.len();
+                    activities.push(act.clone());
+                    act_to_index.insert(act, new_act_index);
+                    trace_acts.push(new_act_index)
+                }
+            }
+
+            *trace_variants.entry(trace_acts).or_insert(0) += 1;
+        }
+        Self {
+            activities,
+            act_to_index,
+            traces: trace_variants.into_iter().collect(),
+            classifier: classifier.clone(),
         }
     }
-}
 
-impl EventLogActivityProjection {
     /// Convenience function to get sorted activity name lists back from a list of `acts`
     pub fn acts_to_names(&self, acts: &[usize]) -> Vec<String> {
         let mut ret: Vec<String> = acts
@@ -222,6 +262,232 @@ impl EventLogActivityProjection {
         ret.sort();
         ret
     }
+
+    ///
+    /// Merge another [`EventLogActivityProjection`] into this one
+    ///
+    /// Activities of `other` are remapped to the activity indices of `self` (adding new activities if necessary)
+    /// and the counts of equal trace variants are added up.
+    ///
+    /// Fails (without modifying `self`) if the projections were constructed using different [`EventLogClassifier`]s,
+    /// as their activities are then not comparable.
+    ///
+    pub fn merge(
+        &mut self,
+        other: &EventLogActivityProjection,
+    ) -> Result<(), ActivityProjectionMergeError> {
+        if self.classifier != other.classifier {
+            return Err(ActivityProjectionMergeError::DifferentClassifiers {
+                classifier: self.classifier.clone(),
+                other_classifier: other.classifier.clone(),
+            });
+        }
+        let index_mapping: Vec<usize> = other
+            .activities
+            .iter()
+            .map(|act| match self.act_to_index.get(act) {
+                Some(i) => *i,
+                None => {
+                    let new_act_index = self.activities.len();
+                    self.activities.push(act.clone());
+                    self.act_to_index.insert(act.clone(), new_act_index);
+                    new_act_index
+                }
+            })
+            .collect();
+        let mut variants: HashMap<Vec<usize>, u64> = self.traces.drain(..).collect();
+        for (trace, count) in &other.traces {
+            let trace: Vec<usize> = trace.iter().map(|act| index_mapping[*act]).collect();
+            *variants.entry(trace).or_insert(0) += count;
+        }
+        self.traces = variants.into_iter().collect();
+        Ok(())
+    }
+
+    ///
+    /// Write this [`EventLogActivityProjection`] in a compact binary format
+    ///
+    /// Activity indices and counts are stored as variable-length integers, so the result is usually much smaller than, e.g., JSON.
+    ///
+    /// For the related import function, see [`EventLogActivityProjection::read_binary`].
+    ///
+    pub fn write_binary<W: Write>(&self, mut writer: W) -> Result<(), std::io::Error> {
+        writer.write_all(BINARY_MAGIC)?;
+        writer.write_all(&[BINARY_VERSION])?;
+        write_binary_str(&mut writer, &self.classifier.name)?;
+        write_varint(&mut writer, self.classifier.keys.len() as u64)?;
+        for key in &self.classifier.keys {
+            write_binary_str(&mut writer, key)?;
+        }
+        write_varint(&mut writer, self.activities.len() as u64)?;
+        for act in &self.activities {
+            write_binary_str(&mut writer, act)?;
+        }
+        write_varint(&mut writer, self.traces.len() as u64)?;
+        for (trace, count) in &self.traces {
+            write_varint(&mut writer, *count)?;
+            write_varint(&mut writer, trace.len() as u64)?;
+            for act in trace {
+                write_varint(&mut writer, *act as u64)?;
+            }
+        }
+        writer.flush()
+    }
+
+    ///
+    /// Read an [`EventLogActivityProjection`] from the compact binary format
+    ///
+    /// For the related export function, see [`EventLogActivityProjection::write_binary`].
+    ///
+    pub fn read_binary<R: Read>(mut reader: R) -> Result<Self, std::io::Error> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if &magic != BINARY_MAGIC || version[0] != BINARY_VERSION {
+            return Err(invalid_data(
+                "Not a binary activity projection (or unsupported version)",
+            ));
+        }
+        let name = read_binary_str(&mut reader)?;
+        let num_keys = read_varint(&mut reader)?;
+        let keys = (0..num_keys)
+            .map(|_| read_binary_str(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_activities = read_varint(&mut reader)?;
+        let activities = (0..num_activities)
+            .map(|_| read_binary_str(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_traces = read_varint(&mut reader)?;
+        let traces = (0..num_traces)
+            .map(|_| {
+                let count = read_varint(&mut reader)?;
+                let len = read_varint(&mut reader)?;
+                let trace = (0..len)
+                    .map(|_| {
+                        let act = read_varint(&mut reader)? as usize;
+                        if act >= activities.len() {
+                            return Err(invalid_data("Activity index out of bounds"));
+                        }
+                        Ok(act)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((trace, count))
+            })
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        let act_to_index = activities
+            .iter()
+            .enumerate()
+            .map(|(i, act)| (act.clone(), i))
+            .collect();
+        Ok(Self {
+            activities,
+            act_to_index,
+            traces,
+            classifier: EventLogClassifier { name, keys },
+        })
+    }
+
+    ///
+    /// Export this [`EventLogActivityProjection`] in the compact binary format to a file at the specified path
+    ///
+    /// See also [`EventLogActivityProjection::write_binary`].
+    ///
+    pub fn export_binary_to_path<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        self.write_binary(BufWriter::new(File::create(path)?))
+    }
+
+    ///
+    /// Import an [`EventLogActivityProjection`] in the compact binary format from a file at the specified path
+    ///
+    /// See also [`EventLogActivityProjection::read_binary`].
+    ///
+    pub fn import_binary_from_path<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        Self::read_binary(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Error when merging two [`EventLogActivityProjection`]s (see [`EventLogActivityProjection::merge`])
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActivityProjectionMergeError {
+    /// The projections were constructed using different [`EventLogClassifier`]s
+    DifferentClassifiers {
+        /// Classifier of the projection merged into
+        classifier: EventLogClassifier,
+        /// Classifier of the merged projection
+        other_classifier: EventLogClassifier,
+    },
+}
+
+impl std::fmt::Display for ActivityProjectionMergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivityProjectionMergeError::DifferentClassifiers {
+                classifier,
+                other_classifier,
+            } => write!(
+                f,
+                "Cannot merge activity projections with different classifiers ({:?} and {:?})",
+                classifier.name, other_classifier.name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ActivityProjectionMergeError {}
+
+/// Magic bytes at the start of the binary [`EventLogActivityProjection`] format
+const BINARY_MAGIC: &[u8; 4] = b"PMAP";
+/// Version of the binary [`EventLogActivityProjection`] format
+const BINARY_VERSION: u8 = 1;
+
+fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// Write an unsigned integer as LEB128 variable-length integer
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> Result<(), std::io::Error> {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+/// Read an unsigned LEB128 variable-length integer
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, std::io::Error> {
+    let mut value: u64 = 0;
+    let mut buf = [0u8; 1];
+    for shift in (0..64).step_by(7) {
+        reader.read_exact(&mut buf)?;
+        // Only the lowest bit of the tenth byte fits into a `u64`
+        if shift == 63 && buf[0] > 1 {
+            return Err(invalid_data("Variable-length integer overflows u64"));
+        }
+        value |= ((buf[0] & 0x7F) as u64) << shift;
+        if buf[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("Variable-length integer too long"))
+}
+
+fn write_binary_str<W: Write>(writer: &mut W, s: &str) -> Result<(), std::io::Error> {
+    write_varint(writer, s.len() as u64)?;
+    writer.write_all(s.as_bytes())
+}
+
+fn read_binary_str<R: Read>(reader: &mut R) -> Result<String, std::io::Error> {
+    let len = read_varint(reader)?;
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+    if buf.len() as u64 != len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(buf).map_err(|_| invalid_data("Invalid UTF-8 string"))
 }
 
 ///
@@ -287,3 +553,89 @@ pub fn add_start_end_acts(log: &mut EventLog) {
         t.events.push(end_event);
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projection(variants: Vec<(Vec<&str>, u64)>) -> EventLogActivityProjection {
+        let mut proj = EventLogActivityProjection::default();
+        for (trace, count) in variants {
+            let trace = trace
+                .into_iter()
+                .map(|act| match proj.act_to_index.get(act) {
+                    Some(i) => *i,
+                    None => {
+                        proj.activities.push(act.to_string());
+                        proj.act_to_index
+                            .insert(act.to_string(), proj.activities.len() - 1);
+                        proj.activities.len() - 1
+                    }
+                })
+                .collect();
+            proj.traces.push((trace, count));
+        }
+        proj
+    }
+
+    fn variant_counts(proj: &EventLogActivityProjection) -> HashMap<Vec<String>, u64> {
+        proj.traces
+            .iter()
+            .map(|(t, c)| (t.iter().map(|a| proj.activities[*a].clone()).collect(), *c))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_projections() {
+        let mut a = projection(vec![(vec!["a", "b", "c"], 3), (vec!["a", "c"], 1)]);
+        let b = projection(vec![(vec!["d", "a", "c"], 2), (vec!["a", "c"], 4)]);
+        a.merge(&b).unwrap();
+        let counts = variant_counts(&a);
+        assert_eq!(a.activities.len(), 4);
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&vec!["a".to_string(), "c".to_string()]], 5);
+        assert_eq!(
+            counts[&vec!["d".to_string(), "a".to_string(), "c".to_string()]],
+            2
+        );
+
+        let mut c = projection(vec![(vec!["a"], 1)]);
+        c.classifier = EventLogClassifier {
+            name: "Activity and Resource".to_string(),
+            keys: vec!["concept:name".to_string(), "org:resource".to_string()],
+        };
+        assert!(matches!(
+            a.merge(&c),
+            Err(ActivityProjectionMergeError::DifferentClassifiers { .. })
+        ));
+        assert_eq!(variant_counts(&a), counts);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let proj = projection(vec![(vec!["a", "b", "c"], 300), (vec!["a", "c"], 1)]);
+        let mut bytes: Vec<u8> = Vec::new();
+        proj.write_binary(&mut bytes).unwrap();
+        let imported = EventLogActivityProjection::read_binary(bytes.as_slice()).unwrap();
+        assert_eq!(imported.activities, proj.activities);
+        assert_eq!(imported.act_to_index, proj.act_to_index);
+        assert_eq!(imported.traces, proj.traces);
+        assert_eq!(imported.classifier, proj.classifier);
+
+        assert!(EventLogActivityProjection::read_binary(&bytes[..bytes.len() - 1]).is_err());
+        assert!(EventLogActivityProjection::read_binary(b"nope".as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_varint_bounds() {
+        for value in [0, 1, 127, 128, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value).unwrap();
+            assert_eq!(read_varint(&mut bytes.as_slice()).unwrap(), value);
+        }
+        // Tenth byte with more than the lowest bit set
+        let mut bytes = vec![0xFF; 9];
+        bytes.push(0x02);
+        assert!(read_varint(&mut bytes.as_slice()).is_err());
+    }
+}
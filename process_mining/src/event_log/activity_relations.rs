@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rayon::prelude::*;
+
+use super::activity_projection::EventLogActivityProjection;
+
+/// Count keys over all traces of an [`EventLogActivityProjection`] in parallel
+///
+/// The passed closure is called for every trace (together with its weight, i.e., number of occurrences)
+/// and adds the (weighted) counts of this trace to the passed [`HashMap`].
+fn count_in_parallel<K, F>(log: &EventLogActivityProjection, count_trace: F) -> HashMap<K, u64>
+where
+    K: Hash + Eq + Send,
+    F: Fn(&[usize], u64, &mut HashMap<K, u64>) + Sync,
+{
+    log.traces
+        .par_iter()
+        .fold(HashMap::<K, u64>::new, |mut map, (t, w)| {
+            count_trace(t, *w, &mut map);
+            map
+        })
+        .reduce(HashMap::new, |m1, m2| {
+            let (mut large, small) = if m1.len() < m2.len() {
+                (m2, m1)
+            } else {
+                (m1, m2)
+            };
+            for (k, v) in small {
+                *large.entry(k).or_default() += v;
+            }
+            large
+        })
+}
+
+impl EventLogActivityProjection {
+    /// Count the eventually-follows relations of all activities
+    ///
+    /// The count of `(a, b)` is the number of (weighted) pairs of events `e1`, `e2` in the same trace,
+    /// such that `e1` has activity `a`, `e2` has activity `b` and `e1` occurs (not necessarily directly) before `e2`.
+    ///
+    /// Runs in `O(n * d)` per trace, where `n` is the length of the trace and `d` the number of its distinct activities.
+    pub fn eventually_follows_counts(&self) -> HashMap<(usize, usize), u64> {
+        let num_acts = self.activities.len();
+        count_in_parallel(self, |trace, w, map| {
+            // Number of occurrences of each activity seen so far in the trace
+            let mut seen_counts = vec![0u64; num_acts];
+            let mut seen_acts: Vec<usize> = Vec::new();
+            for &b in trace {
+                for &a in &seen_acts {
+                    *map.entry((a, b)).or_default() += seen_counts[a] * w;
+                }
+                if seen_counts[b] == 0 {
+                    seen_acts.push(b);
+                }
+                seen_counts[b] += 1;
+            }
+        })
+    }
+
+    /// Count the follows relations of all activities within a window of `window_size` steps
+    ///
+    /// The count of `(a, b)` is the number of (weighted) pairs of events `e1`, `e2` in the same trace,
+    /// such that `e1` has activity `a`, `e2` has activity `b` and `e2` occurs at most `window_size` positions after `e1`.
+    ///
+    /// For a `window_size` of `1`, this corresponds to the directly-follows relation
+    /// (see [`ActivityProjectionDFG`](super::activity_projection::ActivityProjectionDFG)).
+    /// A `window_size` of `0` yields no relations.
+    pub fn windowed_follows_counts(&self, window_size: usize) -> HashMap<(usize, usize), u64> {
+        count_in_parallel(self, |trace, w, map| {
+            for (i, &a) in trace.iter().enumerate() {
+                for &b in trace.iter().skip(i + 1).take(window_size) {
+                    *map.entry((a, b)).or_default() += w;
+                }
+            }
+        })
+    }
+
+    /// Count all n-grams (i.e., sequences of `n` consecutive activities) in the traces
+    ///
+    /// Each occurrence of an n-gram in a trace is counted (weighted by the number of occurrences of the trace).
+    /// A `n` of `0` yields no n-grams.
+    pub fn ngram_counts(&self, n: usize) -> HashMap<Vec<usize>, u64> {
+        if n == 0 {
+            return HashMap::new();
+        }
+        count_in_parallel(self, |trace, w, map| {
+            for ngram in trace.windows(n) {
+                *map.entry(ngram.to_vec()).or_default() += w;
+            }
+        })
+    }
+
+    /// Get all n-grams (see [`EventLogActivityProjection::ngram_counts`]) occurring at least `min_count` times
+    ///
+    /// The result is sorted by count (descending) and n-gram (ascending).
+    pub fn frequent_ngrams(&self, n: usize, min_count: u64) -> Vec<(Vec<usize>, u64)> {
+        let mut ngrams: Vec<(Vec<usize>, u64)> = self
+            .ngram_counts(n)
+            .into_iter()
+            .filter(|(_, count)| *count >= min_count)
+            .collect();
+        ngrams.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        ngrams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::activity_projection::ActivityProjectionDFG;
+
+    fn sample_projection() -> EventLogActivityProjection {
+        let activities: Vec<String> = ["a", "b", "c"].iter().map(|a| a.to_string()).collect();
+        EventLogActivityProjection {
+            act_to_index: activities
+                .iter()
+                .enumerate()
+                .map(|(i, a)| (a.clone(), i))
+                .collect(),
+            activities,
+            traces: vec![(vec![0, 1, 0, 2], 3), (vec![0, 2], 2)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_eventually_follows_counts() {
+        let log = sample_projection();
+        let ef = log.eventually_follows_counts();
+        assert_eq!(ef[&(0, 1)], 3);
+        assert_eq!(ef[&(0, 0)], 3);
+        assert_eq!(ef[&(0, 2)], 2 * 3 + 2);
+        assert_eq!(ef[&(1, 0)], 3);
+        assert_eq!(ef[&(1, 2)], 3);
+        assert!(!ef.contains_key(&(2, 0)));
+        // Eventually-follows is equal to windowed follows with an unbounded window
+        assert_eq!(ef, log.windowed_follows_counts(usize::MAX));
+    }
+
+    #[test]
+    fn test_windowed_follows_counts() {
+        let log = sample_projection();
+        let dfg = ActivityProjectionDFG::from_event_log_projection(&log);
+        assert_eq!(log.windowed_follows_counts(1), dfg.edges);
+        let wf = log.windowed_follows_counts(2);
+        assert_eq!(wf[&(0, 0)], 3);
+        assert_eq!(wf[&(0, 2)], 3 + 2);
+        assert_eq!(wf[&(1, 2)], 3);
+        assert!(log.windowed_follows_counts(0).is_empty());
+    }
+
+    #[test]
+    fn test_ngram_counts() {
+        let log = sample_projection();
+        let bigrams = log.ngram_counts(2);
+        assert_eq!(bigrams[&vec![0, 2]], 3 + 2);
+        assert_eq!(bigrams[&vec![0, 1]], 3);
+        assert_eq!(log.ngram_counts(4).len(), 1);
+        assert!(log.ngram_counts(5).is_empty());
+        assert_eq!(
+            log.frequent_ngrams(2, 3),
+            vec![(vec![0, 2], 5), (vec![0, 1], 3), (vec![1, 0], 3)]
+        );
+    }
+}
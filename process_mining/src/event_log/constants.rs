@@ -19,3 +19,15 @@ pub const TRACE_ID_NAME: &str = "concept:name";
 ///
 /// Primarily used only for interoperability with `PM4Py`
 pub const PREFIXED_TRACE_ID_NAME: &str = "case:concept:name";
+/// Common field for event timestamps (as defined by the time XES extension)
+///
+/// __Usage Generally Discouraged__: _Instead, try to make the timestamp key configurable_
+///
+/// See also [`ACTIVITY_NAME`]
+pub const TIMESTAMP_NAME: &str = "time:timestamp";
+/// Common field for event resources (as defined by the organizational XES extension)
+///
+/// __Usage Generally Discouraged__: _Instead, try to make the resource key configurable_
+///
+/// See also [`ACTIVITY_NAME`]
+pub const RESOURCE_NAME: &str = "org:resource";
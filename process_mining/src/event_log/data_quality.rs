@@ -0,0 +1,496 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, FixedOffset, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    constants::{ACTIVITY_NAME, TIMESTAMP_NAME, TRACE_ID_NAME},
+    event_log_struct::EventLog,
+    Attribute, AttributeValue, Attributes, Event, Trace, XESEditableAttribute,
+};
+
+///
+/// Kinds of data-quality issues detected by the [`DataQualityChecker`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DataQualityIssueKind {
+    /// Timestamps that are exactly at midnight, hinting at day granularity
+    ///
+    /// Only reported for traces in which at least [`DataQualityOptions::coarse_timestamp_share`] of the timestamps are at midnight
+    CoarseTimestamps,
+    /// Events sharing the same timestamp with another event of the same trace
+    IdenticalTimestampsInTrace,
+    /// Events with a timestamp before the timestamp of a preceding event in the trace
+    UnorderedTimestamps,
+    /// Events without a (string) activity attribute
+    MissingActivity,
+    /// Events without a (date) timestamp attribute
+    MissingTimestamp,
+    /// Events which are exact duplicates of another event in the same trace (with or without timestamp)
+    DuplicateEvents,
+    /// Events with a timestamp after the reference time (e.g., now)
+    FutureTimestamps,
+    /// Events containing attribute values of type [`AttributeValue::None`] (e.g., from failed date parsing)
+    InvalidAttributeValues,
+}
+
+impl DataQualityIssueKind {
+    ///
+    /// Suggested fix for this kind of data-quality issue
+    ///
+    pub fn suggested_fix(&self) -> &'static str {
+        match self {
+            DataQualityIssueKind::CoarseTimestamps => "Check if the source system records time of day; otherwise, do not rely on event order or durations within a day",
+            DataQualityIssueKind::IdenticalTimestampsInTrace => "Add a secondary ordering criterion (e.g., lifecycle or position) or treat events with identical timestamps as concurrent",
+            DataQualityIssueKind::UnorderedTimestamps => "Sort the events of each trace by timestamp before discovery",
+            DataQualityIssueKind::MissingActivity => "Use an event classifier based on other attributes or filter out events without activity",
+            DataQualityIssueKind::MissingTimestamp => "Filter out events without timestamp or impute timestamps from neighboring events",
+            DataQualityIssueKind::DuplicateEvents => "Remove duplicate events (e.g., caused by repeated extraction)",
+            DataQualityIssueKind::FutureTimestamps => "Check the timezone and date format used during extraction and import",
+            DataQualityIssueKind::InvalidAttributeValues => "Check the date format of the XES file or provide a custom date format on import",
+        }
+    }
+}
+
+///
+/// A single detected data-quality issue
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataQualityIssue {
+    /// Kind of the issue
+    pub kind: DataQualityIssueKind,
+    /// Number of affected events
+    pub event_count: usize,
+    /// Number of affected cases
+    pub case_count: usize,
+    /// IDs of (some of) the affected cases
+    ///
+    /// Limited to [`DataQualityOptions::max_reported_cases`] entries
+    pub affected_cases: Vec<String>,
+    /// Suggested fix for the issue
+    pub suggested_fix: String,
+}
+
+///
+/// Report of the detected data-quality issues of an event log
+///
+/// Only contains issues which occurred at least once.
+///
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataQualityReport {
+    /// Number of checked traces
+    pub num_traces: usize,
+    /// Number of checked events
+    pub num_events: usize,
+    /// Detected issues
+    pub issues: Vec<DataQualityIssue>,
+}
+
+impl DataQualityReport {
+    /// Check if no data-quality issues were detected
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Get the detected issue of the given kind (if any)
+    pub fn get_issue(&self, kind: DataQualityIssueKind) -> Option<&DataQualityIssue> {
+        self.issues.iter().find(|i| i.kind == kind)
+    }
+}
+
+///
+/// Options for the data-quality assessment
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataQualityOptions {
+    /// Attribute key of the event timestamps (Default: [`TIMESTAMP_NAME`])
+    pub timestamp_key: String,
+    /// Attribute key of the event activities (Default: [`ACTIVITY_NAME`])
+    pub activity_key: String,
+    /// Attribute key of the trace/case IDs (Default: [`TRACE_ID_NAME`])
+    ///
+    /// Traces without this attribute are identified by their position in the log (e.g., `#3`)
+    pub case_id_key: String,
+    /// Timestamps after this reference time are reported as future timestamps
+    ///
+    /// If `None`, the current time is used
+    pub reference_time: Option<DateTime<FixedOffset>>,
+    /// Maximum number of affected case IDs to store per issue
+    pub max_reported_cases: usize,
+    /// Minimum share (between `0.0` and `1.0`) of the timestamps of a trace which have to be at midnight
+    /// to report them as [`DataQualityIssueKind::CoarseTimestamps`] (Default: `1.0`, i.e., all timestamps)
+    pub coarse_timestamp_share: f64,
+}
+
+impl Default for DataQualityOptions {
+    fn default() -> Self {
+        Self {
+            timestamp_key: TIMESTAMP_NAME.to_string(),
+            activity_key: ACTIVITY_NAME.to_string(),
+            case_id_key: TRACE_ID_NAME.to_string(),
+            reference_time: None,
+            max_reported_cases: 100,
+            coarse_timestamp_share: 1.0,
+        }
+    }
+}
+
+/// Activity and timestamp (if present) of an event
+type EventKey<'a> = (Option<&'a String>, Option<&'a DateTime<FixedOffset>>);
+
+#[derive(Debug, Default)]
+struct IssueCounter {
+    event_count: usize,
+    case_count: usize,
+    affected_cases: Vec<String>,
+}
+
+///
+/// Incremental data-quality checker
+///
+/// Traces are checked one at a time (see [`DataQualityChecker::check_trace`]),
+/// which allows checking trace streams (e.g., [`crate::event_log::stream_xes::XESParsingTraceStream`]) with constant memory.
+///
+/// Once all traces are checked, the [`DataQualityReport`] can be obtained using [`DataQualityChecker::into_report`].
+///
+/// For a complete [`EventLog`], see [`check_event_log_quality`].
+///
+#[derive(Debug)]
+pub struct DataQualityChecker {
+    options: DataQualityOptions,
+    reference_time: DateTime<FixedOffset>,
+    global_event_attrs: Option<Attributes>,
+    num_traces: usize,
+    num_events: usize,
+    counters: BTreeMap<DataQualityIssueKind, IssueCounter>,
+}
+
+impl DataQualityChecker {
+    ///
+    /// Create a new [`DataQualityChecker`]
+    ///
+    /// The global event attributes (if any) are used as default values for missing event attributes
+    /// (e.g., [`crate::event_log::stream_xes::XESOuterLogData::global_event_attrs`]).
+    ///
+    pub fn new(options: DataQualityOptions, global_event_attrs: Option<Attributes>) -> Self {
+        let reference_time = options
+            .reference_time
+            .unwrap_or_else(|| Utc::now().fixed_offset());
+        Self {
+            options,
+            reference_time,
+            global_event_attrs,
+            num_traces: 0,
+            num_events: 0,
+            counters: BTreeMap::new(),
+        }
+    }
+
+    fn get_event_attr<'a>(&'a self, e: &'a Event, key: &str) -> Option<&'a Attribute> {
+        e.attributes
+            .get_by_key_or_global(key, &self.global_event_attrs)
+    }
+
+    ///
+    /// Check a single [`Trace`] for data-quality issues
+    ///
+    pub fn check_trace(&mut self, trace: &Trace) {
+        let case_id = trace
+            .attributes
+            .get_by_key(&self.options.case_id_key)
+            .and_then(|a| a.value.try_as_string())
+            .cloned()
+            .unwrap_or_else(|| format!("#{}", self.num_traces));
+        self.num_traces += 1;
+        self.num_events += trace.events.len();
+
+        let mut trace_counts: HashMap<DataQualityIssueKind, usize> = HashMap::new();
+        let mut count = |kind: DataQualityIssueKind| *trace_counts.entry(kind).or_default() += 1;
+
+        let timestamps: Vec<Option<&DateTime<FixedOffset>>> = trace
+            .events
+            .iter()
+            .map(|e| {
+                self.get_event_attr(e, &self.options.timestamp_key)
+                    .and_then(|a| a.value.try_as_date())
+            })
+            .collect();
+        // Candidates for duplicates share the same activity and timestamp (if present)
+        let mut events_per_key: HashMap<EventKey<'_>, Vec<&Event>> = HashMap::new();
+        let mut events_per_timestamp: HashMap<&DateTime<FixedOffset>, usize> = HashMap::new();
+        let mut latest_timestamp: Option<&DateTime<FixedOffset>> = None;
+        let mut midnight_count = 0;
+        for (e, ts) in trace.events.iter().zip(&timestamps) {
+            let activity = self
+                .get_event_attr(e, &self.options.activity_key)
+                .and_then(|a| a.value.try_as_string());
+            if activity.is_none() {
+                count(DataQualityIssueKind::MissingActivity);
+            }
+            if e.attributes.iter().any(contains_invalid_value) {
+                count(DataQualityIssueKind::InvalidAttributeValues);
+            }
+            let candidates = events_per_key.entry((activity, *ts)).or_default();
+            if candidates.contains(&e) {
+                count(DataQualityIssueKind::DuplicateEvents);
+            }
+            candidates.push(e);
+            let Some(ts) = ts else {
+                count(DataQualityIssueKind::MissingTimestamp);
+                continue;
+            };
+            if ts.num_seconds_from_midnight() == 0 && ts.nanosecond() == 0 {
+                midnight_count += 1;
+            }
+            if *ts > &self.reference_time {
+                count(DataQualityIssueKind::FutureTimestamps);
+            }
+            if latest_timestamp.is_some_and(|latest| ts < &latest) {
+                count(DataQualityIssueKind::UnorderedTimestamps);
+            }
+            latest_timestamp = latest_timestamp.max(Some(ts));
+            *events_per_timestamp.entry(ts).or_default() += 1;
+        }
+        for events in events_per_timestamp.values() {
+            if *events > 1 {
+                for _ in 0..*events {
+                    count(DataQualityIssueKind::IdenticalTimestampsInTrace);
+                }
+            }
+        }
+        let timestamp_count = timestamps.iter().flatten().count();
+        if midnight_count > 0
+            && midnight_count as f64 >= self.options.coarse_timestamp_share * timestamp_count as f64
+        {
+            for _ in 0..midnight_count {
+                count(DataQualityIssueKind::CoarseTimestamps);
+            }
+        }
+
+        for (kind, event_count) in trace_counts {
+            let counter = self.counters.entry(kind).or_default();
+            counter.event_count += event_count;
+            counter.case_count += 1;
+            if counter.affected_cases.len() < self.options.max_reported_cases {
+                counter.affected_cases.push(case_id.clone());
+            }
+        }
+    }
+
+    ///
+    /// Finish checking and construct the [`DataQualityReport`]
+    ///
+    pub fn into_report(self) -> DataQualityReport {
+        DataQualityReport {
+            num_traces: self.num_traces,
+            num_events: self.num_events,
+            issues: self
+                .counters
+                .into_iter()
+                .map(|(kind, counter)| DataQualityIssue {
+                    kind,
+                    event_count: counter.event_count,
+                    case_count: counter.case_count,
+                    affected_cases: counter.affected_cases,
+                    suggested_fix: kind.suggested_fix().to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn contains_invalid_value(attr: &Attribute) -> bool {
+    let nested_invalid = match &attr.value {
+        AttributeValue::None() => return true,
+        AttributeValue::List(attrs) | AttributeValue::Container(attrs) => {
+            attrs.iter().any(contains_invalid_value)
+        }
+        _ => false,
+    };
+    nested_invalid
+        || attr
+            .own_attributes
+            .as_ref()
+            .is_some_and(|attrs| attrs.iter().any(contains_invalid_value))
+}
+
+///
+/// Check an [`EventLog`] for data-quality issues
+///
+/// For checking trace streams, see [`DataQualityChecker`].
+///
+pub fn check_event_log_quality(log: &EventLog, options: DataQualityOptions) -> DataQualityReport {
+    let mut checker = DataQualityChecker::new(options, log.global_event_attrs.clone());
+    log.traces.iter().for_each(|t| checker.check_trace(t));
+    checker.into_report()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(act: Option<&str>, ts: &str) -> Event {
+        let mut e = Event {
+            attributes: Vec::new(),
+        };
+        if let Some(act) = act {
+            e.attributes.add_to_attributes(
+                ACTIVITY_NAME.to_string(),
+                AttributeValue::String(act.to_string()),
+            );
+        }
+        e.attributes.add_to_attributes(
+            TIMESTAMP_NAME.to_string(),
+            AttributeValue::Date(DateTime::parse_from_rfc3339(ts).unwrap()),
+        );
+        e
+    }
+
+    fn trace(id: &str, events: Vec<Event>) -> Trace {
+        Trace {
+            attributes: vec![Attribute::new(
+                TRACE_ID_NAME.to_string(),
+                AttributeValue::String(id.to_string()),
+            )],
+            events,
+        }
+    }
+
+    #[test]
+    fn test_data_quality_report() {
+        let mut invalid_event = event(Some("d"), "2024-01-02T10:00:00+00:00");
+        invalid_event
+            .attributes
+            .add_to_attributes("other_date".to_string(), AttributeValue::None());
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![
+                trace(
+                    "clean",
+                    vec![
+                        event(Some("a"), "2024-01-01T10:00:00+00:00"),
+                        event(Some("b"), "2024-01-01T11:00:00+00:00"),
+                    ],
+                ),
+                trace(
+                    "messy",
+                    vec![
+                        event(Some("a"), "2024-01-02T00:00:00+00:00"),
+                        event(Some("c"), "2024-01-02T12:00:00+00:00"),
+                        event(Some("c"), "2024-01-02T12:00:00+00:00"),
+                        event(None, "2024-01-02T11:00:00+00:00"),
+                        invalid_event,
+                        event(Some("e"), "2099-01-01T10:00:00+00:00"),
+                    ],
+                ),
+            ],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+        let report = check_event_log_quality(
+            &log,
+            DataQualityOptions {
+                reference_time: Some(
+                    DateTime::parse_from_rfc3339("2025-01-01T00:00:00+00:00").unwrap(),
+                ),
+                ..Default::default()
+            },
+        );
+        assert_eq!(report.num_traces, 2);
+        assert_eq!(report.num_events, 8);
+        let count = |kind| report.get_issue(kind).map(|i| i.event_count);
+        // Only one of the timestamps of the messy trace is at midnight
+        assert_eq!(count(DataQualityIssueKind::CoarseTimestamps), None);
+        assert_eq!(
+            count(DataQualityIssueKind::IdenticalTimestampsInTrace),
+            Some(2)
+        );
+        assert_eq!(count(DataQualityIssueKind::DuplicateEvents), Some(1));
+        assert_eq!(count(DataQualityIssueKind::MissingActivity), Some(1));
+        assert_eq!(count(DataQualityIssueKind::UnorderedTimestamps), Some(2));
+        assert_eq!(count(DataQualityIssueKind::InvalidAttributeValues), Some(1));
+        assert_eq!(count(DataQualityIssueKind::FutureTimestamps), Some(1));
+        assert_eq!(count(DataQualityIssueKind::MissingTimestamp), None);
+        for issue in &report.issues {
+            assert_eq!(issue.affected_cases, vec!["messy".to_string()]);
+        }
+    }
+
+    #[test]
+    fn test_duplicates_without_timestamps() {
+        let mut without_timestamp = event(Some("a"), "2024-01-01T10:00:00+00:00");
+        without_timestamp.attributes.remove_with_key(TIMESTAMP_NAME);
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![trace(
+                "untimed",
+                vec![
+                    without_timestamp.clone(),
+                    event(Some("b"), "2024-01-01T11:00:00+00:00"),
+                    without_timestamp,
+                ],
+            )],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+        let report = check_event_log_quality(&log, DataQualityOptions::default());
+        let count = |kind| report.get_issue(kind).map(|i| i.event_count);
+        assert_eq!(count(DataQualityIssueKind::DuplicateEvents), Some(1));
+        assert_eq!(count(DataQualityIssueKind::MissingTimestamp), Some(2));
+        assert_eq!(
+            count(DataQualityIssueKind::IdenticalTimestampsInTrace),
+            None
+        );
+    }
+
+    #[test]
+    fn test_coarse_timestamps_share() {
+        let log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![
+                trace(
+                    "days",
+                    vec![
+                        event(Some("a"), "2024-01-01T00:00:00+00:00"),
+                        event(Some("b"), "2024-01-02T00:00:00+00:00"),
+                    ],
+                ),
+                trace(
+                    "mixed",
+                    vec![
+                        event(Some("a"), "2024-01-01T00:00:00+00:00"),
+                        event(Some("b"), "2024-01-01T09:30:00+00:00"),
+                        event(Some("c"), "2024-01-01T10:00:00+00:00"),
+                    ],
+                ),
+            ],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+        let report = check_event_log_quality(&log, DataQualityOptions::default());
+        let issue = report
+            .get_issue(DataQualityIssueKind::CoarseTimestamps)
+            .unwrap();
+        assert_eq!(issue.event_count, 2);
+        assert_eq!(issue.affected_cases, vec!["days".to_string()]);
+
+        let report = check_event_log_quality(
+            &log,
+            DataQualityOptions {
+                coarse_timestamp_share: 0.3,
+                ..Default::default()
+            },
+        );
+        let issue = report
+            .get_issue(DataQualityIssueKind::CoarseTimestamps)
+            .unwrap();
+        assert_eq!(issue.event_count, 3);
+        assert_eq!(issue.case_count, 2);
+    }
+}
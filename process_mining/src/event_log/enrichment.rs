@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    constants::{RESOURCE_NAME, TIMESTAMP_NAME},
+    event_log_struct::EventLog,
+    AttributeValue, Attributes, Trace, XESEditableAttribute,
+};
+
+///
+/// Options for enriching events with derived attributes
+///
+/// Each `*_key` field specifies the attribute key under which the corresponding derived value is stored.
+/// Setting a key to `None` disables computing this attribute.
+///
+/// All durations are stored as [`AttributeValue::Int`] in milliseconds.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnrichmentOptions {
+    /// Attribute key of the event timestamps (Default: [`TIMESTAMP_NAME`])
+    pub timestamp_key: String,
+    /// Attribute key of the event resources (Default: [`RESOURCE_NAME`])
+    pub resource_key: String,
+    /// Key for the elapsed time since the start of the case (in ms)
+    pub elapsed_time_key: Option<String>,
+    /// Key for the remaining time until the end of the case (in ms)
+    pub remaining_time_key: Option<String>,
+    /// Key for the time since the previous event in the case (in ms)
+    ///
+    /// The first event of a case gets a value of `0`
+    pub time_since_previous_key: Option<String>,
+    /// Key for the (zero-based) position of the event in its case
+    pub position_key: Option<String>,
+    /// Key for the weekday of the event timestamp (e.g., `Mon`)
+    pub weekday_key: Option<String>,
+    /// Key for the hour (`0`-`23`) of the event timestamp
+    pub hour_key: Option<String>,
+    /// Key for the number of concurrently running cases at the event timestamp
+    ///
+    /// _Note_: Only computed for a complete [`EventLog`] (see [`enrich_event_log`])
+    pub case_workload_key: Option<String>,
+    /// Key for the number of cases the event resource is concurrently active in at the event timestamp
+    ///
+    /// A resource is considered active in a case between its first and last event in that case.
+    ///
+    /// _Note_: Only computed for a complete [`EventLog`] (see [`enrich_event_log`])
+    pub resource_workload_key: Option<String>,
+}
+
+impl Default for EventEnrichmentOptions {
+    fn default() -> Self {
+        Self {
+            timestamp_key: TIMESTAMP_NAME.to_string(),
+            resource_key: RESOURCE_NAME.to_string(),
+            elapsed_time_key: Some("enrichment:elapsed_time".to_string()),
+            remaining_time_key: Some("enrichment:remaining_time".to_string()),
+            time_since_previous_key: Some("enrichment:time_since_previous".to_string()),
+            position_key: Some("enrichment:position".to_string()),
+            weekday_key: Some("enrichment:weekday".to_string()),
+            hour_key: Some("enrichment:hour".to_string()),
+            case_workload_key: Some("enrichment:case_workload".to_string()),
+            resource_workload_key: Some("enrichment:resource_workload".to_string()),
+        }
+    }
+}
+
+///
+/// Set an attribute value, replacing any existing value with the same key
+///
+fn set_attribute(attrs: &mut Attributes, key: &str, value: AttributeValue) {
+    match attrs.get_by_key_mut(key) {
+        Some(a) => a.value = value,
+        None => attrs.add_to_attributes(key.to_string(), value),
+    }
+}
+
+fn get_timestamps(
+    trace: &Trace,
+    timestamp_key: &str,
+    global_event_attrs: &Option<Attributes>,
+) -> Vec<Option<DateTime<FixedOffset>>> {
+    trace
+        .events
+        .iter()
+        .map(|e| {
+            e.attributes
+                .get_by_key_or_global(timestamp_key, global_event_attrs)
+                .and_then(|a| a.value.try_as_date())
+                .cloned()
+        })
+        .collect()
+}
+
+///
+/// Enrich all events of a single [`Trace`] with derived attributes
+///
+/// Only computes the trace-local attributes (i.e., all except case and resource workload).
+/// Timestamp-based attributes are skipped for events without a valid timestamp.
+/// Attributes missing on an event are looked up in `global_event_attrs` (e.g., [`EventLog::global_event_attrs`]).
+///
+pub fn enrich_trace(
+    trace: &mut Trace,
+    global_event_attrs: &Option<Attributes>,
+    options: &EventEnrichmentOptions,
+) {
+    let timestamps = get_timestamps(trace, &options.timestamp_key, global_event_attrs);
+    let case_start = timestamps.iter().flatten().min().cloned();
+    let case_end = timestamps.iter().flatten().max().cloned();
+    let mut prev_timestamp: Option<DateTime<FixedOffset>> = None;
+    for (i, (e, ts)) in trace.events.iter_mut().zip(timestamps).enumerate() {
+        if let Some(key) = &options.position_key {
+            set_attribute(&mut e.attributes, key, AttributeValue::Int(i as i64));
+        }
+        let Some(ts) = ts else {
+            continue;
+        };
+        if let (Some(key), Some(start)) = (&options.elapsed_time_key, case_start) {
+            let ms = (ts - start).num_milliseconds();
+            set_attribute(&mut e.attributes, key, AttributeValue::Int(ms));
+        }
+        if let (Some(key), Some(end)) = (&options.remaining_time_key, case_end) {
+            let ms = (end - ts).num_milliseconds();
+            set_attribute(&mut e.attributes, key, AttributeValue::Int(ms));
+        }
+        if let Some(key) = &options.time_since_previous_key {
+            let ms = prev_timestamp.map_or(0, |prev| (ts - prev).num_milliseconds());
+            set_attribute(&mut e.attributes, key, AttributeValue::Int(ms));
+        }
+        if let Some(key) = &options.weekday_key {
+            let weekday = ts.weekday().to_string();
+            set_attribute(&mut e.attributes, key, AttributeValue::String(weekday));
+        }
+        if let Some(key) = &options.hour_key {
+            set_attribute(
+                &mut e.attributes,
+                key,
+                AttributeValue::Int(ts.hour() as i64),
+            );
+        }
+        prev_timestamp = Some(ts);
+    }
+}
+
+///
+/// Enrich a stream of [`Trace`]s with derived attributes
+///
+/// Traces are enriched lazily (using [`enrich_trace`]), so this can be used in between, e.g.,
+/// a [`crate::event_log::stream_xes::XESParsingTraceStream`] and [`crate::export_xes_trace_stream_to_file`].
+/// For streamed XES files, the global event attributes are available in [`crate::event_log::stream_xes::XESOuterLogData`].
+///
+/// _Note_: Case and resource workload require knowledge of all cases and are thus _not_ computed on streams.
+///
+pub fn enrich_trace_stream<I>(
+    traces: I,
+    global_event_attrs: Option<Attributes>,
+    options: EventEnrichmentOptions,
+) -> impl Iterator<Item = Trace>
+where
+    I: Iterator<Item = Trace>,
+{
+    traces.map(move |mut t| {
+        enrich_trace(&mut t, &global_event_attrs, &options);
+        t
+    })
+}
+
+///
+/// Sorted start and end timestamps of a set of time intervals
+///
+/// Used to efficiently count the number of intervals active at a given point in time
+///
+#[derive(Debug, Default)]
+struct IntervalCounter {
+    starts: Vec<DateTime<FixedOffset>>,
+    ends: Vec<DateTime<FixedOffset>>,
+}
+
+impl IntervalCounter {
+    fn add(&mut self, start: DateTime<FixedOffset>, end: DateTime<FixedOffset>) {
+        self.starts.push(start);
+        self.ends.push(end);
+    }
+
+    fn finalize(&mut self) {
+        self.starts.sort();
+        self.ends.sort();
+    }
+
+    /// Number of intervals `[start,end]` containing `t` (requires [`IntervalCounter::finalize`] to be called before)
+    fn active_at(&self, t: &DateTime<FixedOffset>) -> i64 {
+        let started = self.starts.partition_point(|s| s <= t);
+        let ended = self.ends.partition_point(|e| e < t);
+        (started - ended) as i64
+    }
+}
+
+///
+/// Enrich all events of an [`EventLog`] with derived attributes
+///
+/// In addition to the trace-local attributes (see [`enrich_trace`]), this also computes
+/// the case and resource workload (if enabled in the passed [`EventEnrichmentOptions`]).
+///
+pub fn enrich_event_log(log: &mut EventLog, options: &EventEnrichmentOptions) {
+    let global_event_attrs = &log.global_event_attrs;
+    log.traces
+        .par_iter_mut()
+        .for_each(|t| enrich_trace(t, global_event_attrs, options));
+
+    if options.case_workload_key.is_none() && options.resource_workload_key.is_none() {
+        return;
+    }
+
+    let mut case_intervals = IntervalCounter::default();
+    let mut resource_intervals: HashMap<String, IntervalCounter> = HashMap::new();
+    for t in &log.traces {
+        let timestamps = get_timestamps(t, &options.timestamp_key, &log.global_event_attrs);
+        if let (Some(start), Some(end)) = (
+            timestamps.iter().flatten().min(),
+            timestamps.iter().flatten().max(),
+        ) {
+            case_intervals.add(*start, *end);
+        }
+        let mut resource_in_case: HashMap<&String, (DateTime<FixedOffset>, DateTime<FixedOffset>)> =
+            HashMap::new();
+        for (e, ts) in t.events.iter().zip(timestamps) {
+            let resource = e
+                .attributes
+                .get_by_key_or_global(&options.resource_key, &log.global_event_attrs)
+                .and_then(|a| a.value.try_as_string());
+            if let (Some(resource), Some(ts)) = (resource, ts) {
+                let interval = resource_in_case.entry(resource).or_insert((ts, ts));
+                interval.0 = interval.0.min(ts);
+                interval.1 = interval.1.max(ts);
+            }
+        }
+        for (resource, (start, end)) in resource_in_case {
+            resource_intervals
+                .entry(resource.clone())
+                .or_default()
+                .add(start, end);
+        }
+    }
+    case_intervals.finalize();
+    resource_intervals.values_mut().for_each(|c| c.finalize());
+
+    log.traces.par_iter_mut().for_each(|t| {
+        for e in t.events.iter_mut() {
+            let Some(ts) = e
+                .attributes
+                .get_by_key_or_global(&options.timestamp_key, global_event_attrs)
+                .and_then(|a| a.value.try_as_date())
+                .cloned()
+            else {
+                continue;
+            };
+            if let Some(key) = &options.case_workload_key {
+                let workload = case_intervals.active_at(&ts);
+                set_attribute(&mut e.attributes, key, AttributeValue::Int(workload));
+            }
+            if let Some(key) = &options.resource_workload_key {
+                let workload = e
+                    .attributes
+                    .get_by_key_or_global(&options.resource_key, global_event_attrs)
+                    .and_then(|a| a.value.try_as_string())
+                    .and_then(|r| resource_intervals.get(r))
+                    .map(|c| c.active_at(&ts));
+                if let Some(workload) = workload {
+                    set_attribute(&mut e.attributes, key, AttributeValue::Int(workload));
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::event_log::{Event, EventLog, Trace};
+
+    fn event(act: &str, ts: &str, resource: &str) -> Event {
+        let mut e = Event::new(act.to_string());
+        e.attributes.add_to_attributes(
+            TIMESTAMP_NAME.to_string(),
+            AttributeValue::Date(DateTime::parse_from_rfc3339(ts).unwrap()),
+        );
+        e.attributes.add_to_attributes(
+            RESOURCE_NAME.to_string(),
+            AttributeValue::String(resource.to_string()),
+        );
+        e
+    }
+
+    fn int_attr(e: &Event, key: &str) -> i64 {
+        *e.attributes
+            .get_by_key(key)
+            .and_then(|a| a.value.try_as_int())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_enrich_event_log() {
+        let mut log = EventLog {
+            attributes: Vec::new(),
+            traces: vec![
+                Trace {
+                    attributes: Vec::new(),
+                    events: vec![
+                        event("a", "2024-01-01T10:00:00+00:00", "Alice"),
+                        event("b", "2024-01-01T11:00:00+00:00", "Bob"),
+                        event("c", "2024-01-01T13:00:00+00:00", "Alice"),
+                    ],
+                },
+                Trace {
+                    attributes: Vec::new(),
+                    events: vec![
+                        event("a", "2024-01-01T12:00:00+00:00", "Alice"),
+                        event("c", "2024-01-01T14:00:00+00:00", "Alice"),
+                    ],
+                },
+            ],
+            extensions: None,
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        };
+        let options = EventEnrichmentOptions::default();
+        enrich_event_log(&mut log, &options);
+
+        let t1 = &log.traces[0];
+        let hour_ms = 60 * 60 * 1000;
+        assert_eq!(int_attr(&t1.events[1], "enrichment:position"), 1);
+        assert_eq!(int_attr(&t1.events[1], "enrichment:elapsed_time"), hour_ms);
+        assert_eq!(
+            int_attr(&t1.events[1], "enrichment:remaining_time"),
+            2 * hour_ms
+        );
+        assert_eq!(
+            int_attr(&t1.events[2], "enrichment:time_since_previous"),
+            2 * hour_ms
+        );
+        assert_eq!(int_attr(&t1.events[0], "enrichment:time_since_previous"), 0);
+        assert_eq!(int_attr(&t1.events[2], "enrichment:hour"), 13);
+        assert_eq!(
+            t1.events[0]
+                .attributes
+                .get_by_key("enrichment:weekday")
+                .and_then(|a| a.value.try_as_string())
+                .unwrap(),
+            "Mon"
+        );
+        // Both cases are running at 13:00
+        assert_eq!(int_attr(&t1.events[0], "enrichment:case_workload"), 1);
+        assert_eq!(int_attr(&t1.events[2], "enrichment:case_workload"), 2);
+        // Alice is active in both cases at 13:00, Bob only in one
+        assert_eq!(int_attr(&t1.events[2], "enrichment:resource_workload"), 2);
+        assert_eq!(int_attr(&t1.events[1], "enrichment:resource_workload"), 1);
+    }
+
+    #[test]
+    fn test_enrich_trace_stream_custom_keys() {
+        let traces = vec![Trace {
+            attributes: Vec::new(),
+            events: vec![
+                event("a", "2024-01-01T10:00:00+00:00", "Alice"),
+                event("b", "2024-01-01T10:30:00+00:00", "Bob"),
+            ],
+        }];
+        let options = EventEnrichmentOptions {
+            position_key: Some("pos".to_string()),
+            elapsed_time_key: None,
+            ..Default::default()
+        };
+        let enriched: Vec<Trace> = enrich_trace_stream(traces.into_iter(), None, options).collect();
+        let e = &enriched[0].events[1];
+        assert_eq!(int_attr(e, "pos"), 1);
+        assert!(e.attributes.get_by_key("enrichment:elapsed_time").is_none());
+        assert!(e
+            .attributes
+            .get_by_key("enrichment:case_workload")
+            .is_none());
+    }
+
+    #[test]
+    fn test_enrich_trace_global_timestamp() {
+        // The second event has no timestamp and falls back to the global default
+        let mut trace = Trace {
+            attributes: Vec::new(),
+            events: vec![
+                event("a", "2024-01-01T10:00:00+00:00", "Alice"),
+                Event::new("b".to_string()),
+            ],
+        };
+        let mut global_event_attrs = Vec::new();
+        global_event_attrs.add_to_attributes(
+            TIMESTAMP_NAME.to_string(),
+            AttributeValue::Date(
+                DateTime::parse_from_rfc3339("2024-01-01T12:00:00+00:00").unwrap(),
+            ),
+        );
+        enrich_trace(
+            &mut trace,
+            &Some(global_event_attrs),
+            &EventEnrichmentOptions::default(),
+        );
+        assert_eq!(
+            int_attr(&trace.events[1], "enrichment:elapsed_time"),
+            2 * 60 * 60 * 1000
+        );
+        assert_eq!(int_attr(&trace.events[1], "enrichment:hour"), 12);
+    }
+}
@@ -97,3 +97,60 @@ pub fn test_get_class_identity_complex() {
         assert!(trace_variants.contains(&example_variant))
     }
 }
+
+#[test]
+pub fn test_activity_projection_with_classifier() {
+    use crate::event_log::{
+        activity_projection::EventLogActivityProjection, event_log_struct::EventLogClassifier,
+        AttributeValue, Event, EventLog, Trace, XESEditableAttribute,
+    };
+    let event = |act: &str, lifecycle: &str| {
+        let mut e = Event::new(act.to_string());
+        e.attributes.add_to_attributes(
+            "lifecycle:transition".to_string(),
+            AttributeValue::String(lifecycle.to_string()),
+        );
+        e
+    };
+    let classifier = EventLogClassifier {
+        name: "Activity+Lifecycle".to_string(),
+        keys: vec![
+            "concept:name".to_string(),
+            "lifecycle:transition".to_string(),
+        ],
+    };
+    let log = EventLog {
+        attributes: Vec::new(),
+        traces: vec![
+            Trace {
+                attributes: Vec::new(),
+                events: vec![event("a", "start"), event("a", "complete")],
+            };
+            2
+        ],
+        extensions: None,
+        classifiers: Some(vec![classifier.clone()]),
+        global_trace_attrs: None,
+        global_event_attrs: None,
+    };
+
+    let proj = EventLogActivityProjection::from_event_log_with_classifier(
+        &log,
+        &log.get_classifier_by_name("Activity+Lifecycle").unwrap(),
+    );
+    assert_eq!(proj.classifier, classifier);
+    assert_eq!(
+        proj.acts_to_names(&[0, 1]),
+        vec!["a+complete".to_string(), "a+start".to_string()]
+    );
+    assert_eq!(proj.traces.len(), 1);
+    assert_eq!(proj.traces[0].1, 2);
+
+    let stream_proj =
+        EventLogActivityProjection::from_traces_with_classifier(&log.traces, &classifier, &None);
+    assert_eq!(stream_proj.activities, vec!["a+start", "a+complete"]);
+
+    let default_proj: EventLogActivityProjection = (&log).into();
+    assert_eq!(default_proj.activities, vec!["a"]);
+    assert_eq!(default_proj.classifier, EventLogClassifier::default());
+}
@@ -12,11 +12,17 @@
 pub mod event_log {
     /// Activity projection of event logs
     pub mod activity_projection;
+    /// Eventually-follows, windowed follows and n-gram relations of [`EventLogActivityProjection`](crate::EventLogActivityProjection)s
+    pub mod activity_relations;
     /// Constants
     pub mod constants;
+    /// Data-quality assessment of event logs
+    pub mod data_quality;
     /// Conversion of XES event data from/to polars `DataFrame`
     #[cfg(feature = "dataframes")]
     pub mod dataframe;
+    /// Enrichment of events with derived attributes (e.g., elapsed time or workload)
+    pub mod enrichment;
     /// [`EventLog`] struct and sub-structs
     pub mod event_log_struct;
     /// XES Export
@@ -54,6 +60,10 @@ pub mod utils;
 /// Petri nets
 ///
 pub mod petri_net {
+    /// Indexed adjacency (pre- and postsets) of [`PetriNet`]s for fast queries
+    pub mod adjacency;
+    /// Data Petri nets (guard expressions over case variables and data-aware firing)
+    pub mod data_petri_net;
     /// Export [`PetriNet`] to `.pnml`
     pub mod export_pnml;
     #[cfg(feature = "graphviz-export")]
@@ -66,8 +76,26 @@ pub mod petri_net {
     pub mod image_export;
     /// Import [`PetriNet`] from `.pnml`
     pub mod import_pnml;
+    /// Bounded language of [`PetriNet`]s (trace membership, enumeration of variants and trace counts per length)
+    pub mod language;
     /// [`PetriNet`] struct
     pub mod petri_net_struct;
+    /// Playout of [`PetriNet`]s to generate synthetic [`EventLog`](crate::EventLog)s
+    pub mod playout;
+    /// Language-preserving reduction rules for [`PetriNet`]s
+    pub mod reduction;
+    /// Workflow-net checks and soundness verification of [`PetriNet`]s
+    pub mod soundness;
+    /// Reachability and Karp–Miller coverability graphs of [`PetriNet`]s
+    pub mod state_space;
+    /// Stochastic labelled Petri nets (weights, `.slpn` import/export, weight estimation and trace probabilities)
+    pub mod stochastic;
+    /// Structural analysis of [`PetriNet`]s (incidence matrix, semiflows, siphons, traps and subclasses)
+    pub mod structural_analysis;
+    /// Export [`PetriNet`] to DOT, Mermaid or Cytoscape.js JSON (without graphviz)
+    pub mod text_export;
+    /// Token game on [`PetriNet`]s (enabled transitions, firing and replay of label sequences)
+    pub mod token_game;
 
     #[doc(inline)]
     pub use petri_net_struct::PetriNet;
@@ -77,8 +105,14 @@ pub mod petri_net {
 /// Directly-follows graph
 ///
 pub mod dfg {
+    /// Comparison of two [`DirectlyFollowsGraph`]s (e.g., frequency and performance differences)
+    pub mod comparison;
+    /// Conversion of [`DirectlyFollowsGraph`]s to [`PetriNet`](crate::PetriNet)s
+    pub mod conversion;
     /// [`DirectlyFollowsGraph`] struct
     pub mod dfg_struct;
+    /// Activity and path filtering of [`DirectlyFollowsGraph`]s
+    pub mod filtering;
     #[cfg(feature = "graphviz-export")]
     /// Export [`DirectlyFollowsGraph`] to images (SVG, PNG, ...)
     ///
@@ -87,11 +121,25 @@ pub mod dfg {
     /// Also requires an active graphviz installation in the PATH.
     /// See also <https://github.com/besok/graphviz-rust?tab=readme-ov-file#caveats> and <https://graphviz.org/download/>
     pub mod image_export;
+    /// Online (incremental) discovery of [`DirectlyFollowsGraph`]s over event streams
+    pub mod online;
+    /// Performance annotations of [`DirectlyFollowsGraph`]s
+    pub mod performance;
+    /// Export [`DirectlyFollowsGraph`] to DOT, Mermaid or Cytoscape.js JSON (without graphviz)
+    pub mod text_export;
 
     #[doc(inline)]
     pub use crate::dfg::dfg_struct::DirectlyFollowsGraph;
 }
 
+///
+/// Conformance checking
+///
+pub mod conformance {
+    /// Footprint matrices and footprint-based conformance checking
+    pub mod footprints;
+}
+
 use std::fs::File;
 use std::io::BufReader;
 use std::io::BufWriter;
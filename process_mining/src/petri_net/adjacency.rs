@@ -0,0 +1,417 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use super::{
+    petri_net_struct::{ArcType, Marking, PetriNet, PlaceID, TransitionID},
+    token_game::FiringError,
+};
+
+///
+/// Indexed view of the arcs of a [`PetriNet`]
+///
+/// Places and transitions are numbered densely (sorted by ID), and the pre- and postsets of all nodes
+/// are stored per node, so that all queries take time linear in the number of adjacent nodes
+/// (instead of scanning all arcs of the net, like, e.g., [`PetriNet::preset_of_transition`]).
+///
+/// The view is a snapshot: It has to be rebuilt (see [`PetriNet::adjacency`]) after modifying the net.
+///
+/// Multiple arcs between the same place and transition are merged: Their weights are summed up
+/// (for inhibitor arcs, the most restrictive weight applies).
+/// Adjacent nodes are listed in the order of their first arc in [`PetriNet::arcs`].
+///
+#[derive(Debug, Clone, Default)]
+pub struct PetriNetAdjacency {
+    places: Vec<PlaceID>,
+    transitions: Vec<TransitionID>,
+    place_index: HashMap<PlaceID, usize>,
+    transition_index: HashMap<TransitionID, usize>,
+    place_pre: Vec<Vec<(TransitionID, u64)>>,
+    place_post: Vec<Vec<(TransitionID, u64)>>,
+    transition_pre: Vec<Vec<(PlaceID, u64)>>,
+    transition_post: Vec<Vec<(PlaceID, u64)>>,
+    transition_inhibitors: Vec<Vec<(PlaceID, u64)>>,
+    transition_resets: Vec<Vec<PlaceID>>,
+}
+
+impl PetriNetAdjacency {
+    /// Build the indexed view of `net`
+    ///
+    /// Arcs referring to places or transitions not part of the net are ignored.
+    pub fn new(net: &PetriNet) -> Self {
+        let mut places: Vec<PlaceID> = net.places.keys().map(|p| PlaceID(*p)).collect();
+        places.sort();
+        let mut transitions: Vec<TransitionID> =
+            net.transitions.keys().map(|t| TransitionID(*t)).collect();
+        transitions.sort();
+        let place_index: HashMap<PlaceID, usize> =
+            places.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+        let transition_index: HashMap<TransitionID, usize> = transitions
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (*t, i))
+            .collect();
+        let mut res = Self {
+            place_pre: vec![Vec::new(); places.len()],
+            place_post: vec![Vec::new(); places.len()],
+            transition_pre: vec![Vec::new(); transitions.len()],
+            transition_post: vec![Vec::new(); transitions.len()],
+            transition_inhibitors: vec![Vec::new(); transitions.len()],
+            transition_resets: vec![Vec::new(); transitions.len()],
+            places,
+            transitions,
+            place_index,
+            transition_index,
+        };
+        // Positions of the (transition, place) pairs in the rows, to merge multiple arcs between them
+        let mut pre_positions: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        let mut post_positions: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        let mut inhibitor_positions: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut resets: HashSet<(usize, usize)> = HashSet::new();
+        for arc in &net.arcs {
+            let (p, t) = match arc.from_to {
+                ArcType::PlaceTransition(p, t)
+                | ArcType::TransitionPlace(t, p)
+                | ArcType::Inhibitor(p, t)
+                | ArcType::Reset(p, t) => (PlaceID(p), TransitionID(t)),
+            };
+            let (Some(&pi), Some(&ti)) = (res.place_index.get(&p), res.transition_index.get(&t))
+            else {
+                continue;
+            };
+            let w = arc.weight as u64;
+            match arc.from_to {
+                ArcType::PlaceTransition(_, _) => merge_weight(
+                    &mut pre_positions,
+                    (ti, pi),
+                    &mut res.transition_pre[ti],
+                    &mut res.place_post[pi],
+                    (p, t),
+                    w,
+                ),
+                ArcType::TransitionPlace(_, _) => merge_weight(
+                    &mut post_positions,
+                    (ti, pi),
+                    &mut res.transition_post[ti],
+                    &mut res.place_pre[pi],
+                    (p, t),
+                    w,
+                ),
+                ArcType::Inhibitor(_, _) => {
+                    let inhibitors = &mut res.transition_inhibitors[ti];
+                    match inhibitor_positions.entry((ti, pi)) {
+                        // Multiple inhibitor arcs: The most restrictive one applies
+                        Entry::Occupied(e) => {
+                            let w2 = &mut inhibitors[*e.get()].1;
+                            *w2 = (*w2).min(w);
+                        }
+                        Entry::Vacant(e) => {
+                            e.insert(inhibitors.len());
+                            inhibitors.push((p, w));
+                        }
+                    }
+                }
+                ArcType::Reset(_, _) => {
+                    if resets.insert((ti, pi)) {
+                        res.transition_resets[ti].push(p);
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// All places of the net (sorted by ID); the position of a place is its index
+    pub fn places(&self) -> &[PlaceID] {
+        &self.places
+    }
+
+    /// All transitions of the net (sorted by ID); the position of a transition is its index
+    pub fn transitions(&self) -> &[TransitionID] {
+        &self.transitions
+    }
+
+    /// Get the dense index of place `p` (or `None` if it is not part of the net)
+    pub fn place_index(&self, p: PlaceID) -> Option<usize> {
+        self.place_index.get(&p).copied()
+    }
+
+    /// Get the dense index of transition `t` (or `None` if it is not part of the net)
+    pub fn transition_index(&self, t: TransitionID) -> Option<usize> {
+        self.transition_index.get(&t).copied()
+    }
+
+    /// Get the preset of a place together with the arc weights
+    pub fn weighted_preset_of_place(&self, p: PlaceID) -> &[(TransitionID, u64)] {
+        row(&self.place_pre, self.place_index(p))
+    }
+
+    /// Get the postset of a place together with the arc weights
+    pub fn weighted_postset_of_place(&self, p: PlaceID) -> &[(TransitionID, u64)] {
+        row(&self.place_post, self.place_index(p))
+    }
+
+    /// Get the preset of a transition together with the arc weights
+    pub fn weighted_preset_of_transition(&self, t: TransitionID) -> &[(PlaceID, u64)] {
+        row(&self.transition_pre, self.transition_index(t))
+    }
+
+    /// Get the postset of a transition together with the arc weights
+    pub fn weighted_postset_of_transition(&self, t: TransitionID) -> &[(PlaceID, u64)] {
+        row(&self.transition_post, self.transition_index(t))
+    }
+
+    /// Get the inhibitor arcs of a transition as places with the arc weights
+    pub fn inhibitor_arcs_of_transition(&self, t: TransitionID) -> &[(PlaceID, u64)] {
+        row(&self.transition_inhibitors, self.transition_index(t))
+    }
+
+    /// Get all places connected to a transition via reset arcs
+    pub fn reset_places_of_transition(&self, t: TransitionID) -> &[PlaceID] {
+        row(&self.transition_resets, self.transition_index(t))
+    }
+
+    /// Get the preset of a place
+    pub fn preset_of_place(&self, p: PlaceID) -> impl Iterator<Item = TransitionID> + '_ {
+        self.weighted_preset_of_place(p).iter().map(|(t, _)| *t)
+    }
+
+    /// Get the postset of a place
+    pub fn postset_of_place(&self, p: PlaceID) -> impl Iterator<Item = TransitionID> + '_ {
+        self.weighted_postset_of_place(p).iter().map(|(t, _)| *t)
+    }
+
+    /// Get the preset of a transition
+    pub fn preset_of_transition(&self, t: TransitionID) -> impl Iterator<Item = PlaceID> + '_ {
+        self.weighted_preset_of_transition(t)
+            .iter()
+            .map(|(p, _)| *p)
+    }
+
+    /// Get the postset of a transition
+    pub fn postset_of_transition(&self, t: TransitionID) -> impl Iterator<Item = PlaceID> + '_ {
+        self.weighted_postset_of_transition(t)
+            .iter()
+            .map(|(p, _)| *p)
+    }
+
+    /// Check if transition `t` is enabled in `marking`
+    ///
+    /// A transition is enabled if every place in its preset contains at least as many tokens as the weight of the connecting arc,
+    /// and every place connected by an inhibitor arc contains fewer tokens than the weight of the inhibitor arc.
+    /// Returns `false` for transitions not part of the net.
+    pub fn is_enabled(&self, marking: &Marking, t: TransitionID) -> bool {
+        self.is_enabled_in(marking, t)
+    }
+
+    /// Get all transitions enabled in `marking` (sorted by ID)
+    pub fn enabled_transitions(&self, marking: &Marking) -> Vec<TransitionID> {
+        self.transitions
+            .iter()
+            .filter(|t| self.is_enabled(marking, **t))
+            .copied()
+            .collect()
+    }
+
+    /// Check if no transition is enabled in `marking`
+    pub fn is_dead_marking(&self, marking: &Marking) -> bool {
+        !self
+            .transitions
+            .iter()
+            .any(|t| self.is_enabled(marking, *t))
+    }
+
+    /// Fire transition `t` in `marking`, returning the resulting marking
+    ///
+    /// Tokens are consumed from the preset and produced in the postset of `t` according to the arc weights.
+    /// Places connected via reset arcs are emptied after consuming and before producing tokens.
+    /// Places without tokens are not contained in the resulting marking.
+    pub fn fire(&self, marking: &Marking, t: TransitionID) -> Result<Marking, FiringError> {
+        self.fire_in(marking, t)
+    }
+
+    /// Check if transition `t` is enabled in a marking with arbitrary token counts (see [`PetriNetAdjacency::is_enabled`])
+    pub(crate) fn is_enabled_in<T: Tokens>(
+        &self,
+        marking: &HashMap<PlaceID, T>,
+        t: TransitionID,
+    ) -> bool {
+        let Some(ti) = self.transition_index(t) else {
+            return false;
+        };
+        let tokens = |p: &PlaceID| marking.get(p).copied().unwrap_or(T::finite(0));
+        self.transition_pre[ti]
+            .iter()
+            .all(|(p, w)| tokens(p) >= T::finite(*w))
+            && self.transition_inhibitors[ti]
+                .iter()
+                .all(|(p, w)| tokens(p) < T::finite(*w))
+    }
+
+    /// Fire transition `t` in a marking with arbitrary token counts (see [`PetriNetAdjacency::fire`])
+    pub(crate) fn fire_in<T: Tokens>(
+        &self,
+        marking: &HashMap<PlaceID, T>,
+        t: TransitionID,
+    ) -> Result<HashMap<PlaceID, T>, FiringError> {
+        let ti = self
+            .transition_index(t)
+            .ok_or(FiringError::UnknownTransition(t))?;
+        if !self.is_enabled_in(marking, t) {
+            return Err(FiringError::NotEnabled(t));
+        }
+        let mut res = marking.clone();
+        for (p, w) in &self.transition_pre[ti] {
+            // Places without tokens are only in the preset with weight 0
+            if let Some(tokens) = res.get_mut(p) {
+                *tokens = tokens.sub(*w);
+            }
+        }
+        for p in &self.transition_resets[ti] {
+            res.remove(p);
+        }
+        for (p, w) in &self.transition_post[ti] {
+            let tokens = res.entry(*p).or_insert(T::finite(0));
+            *tokens = tokens.add(*w);
+        }
+        res.retain(|_, tokens| *tokens != T::finite(0));
+        Ok(res)
+    }
+}
+
+/// Number of tokens in a place, so that markings with different token counts share the same firing rule
+pub(crate) trait Tokens: Copy + PartialOrd {
+    /// The given (finite) number of tokens
+    fn finite(n: u64) -> Self;
+    /// Add `w` tokens
+    fn add(self, w: u64) -> Self;
+    /// Remove `w` tokens (only called with at least `w` tokens)
+    fn sub(self, w: u64) -> Self;
+}
+
+impl Tokens for u64 {
+    fn finite(n: u64) -> Self {
+        n
+    }
+
+    fn add(self, w: u64) -> Self {
+        self + w
+    }
+
+    fn sub(self, w: u64) -> Self {
+        self - w
+    }
+}
+
+/// Get the row of the node with index `i` (or an empty row for unknown nodes)
+fn row<T>(rows: &[Vec<T>], i: Option<usize>) -> &[T] {
+    i.map(|i| rows[i].as_slice()).unwrap_or_default()
+}
+
+/// Add an arc with weight `w` between a transition and a place to their rows,
+/// merging it with a previous arc between them (whose positions are stored in `positions`)
+fn merge_weight(
+    positions: &mut HashMap<(usize, usize), (usize, usize)>,
+    key: (usize, usize),
+    transition_row: &mut Vec<(PlaceID, u64)>,
+    place_row: &mut Vec<(TransitionID, u64)>,
+    (p, t): (PlaceID, TransitionID),
+    w: u64,
+) {
+    match positions.entry(key) {
+        Entry::Occupied(e) => {
+            let (i, j) = *e.get();
+            transition_row[i].1 += w;
+            place_row[j].1 += w;
+        }
+        Entry::Vacant(e) => {
+            e.insert((transition_row.len(), place_row.len()));
+            transition_row.push((p, w));
+            place_row.push((t, w));
+        }
+    }
+}
+
+impl PetriNet {
+    /// Build an indexed view of the arcs of the net for fast pre- and postset queries
+    ///
+    /// See [`PetriNetAdjacency`].
+    pub fn adjacency(&self) -> PetriNetAdjacency {
+        PetriNetAdjacency::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacency_queries() {
+        let mut net = PetriNet::new();
+        let [p0, p1, p2] = [(); 3].map(|_| net.add_place(None));
+        let t = net.add_transition(Some("a".to_string()), None);
+        let u = net.add_transition(None, None);
+        net.add_arc(ArcType::place_to_transition(p0, t), None);
+        net.add_arc(ArcType::place_to_transition(p0, t), Some(2));
+        net.add_arc(ArcType::transition_to_place(t, p1), None);
+        net.add_arc(ArcType::transition_to_place(t, p2), Some(3));
+        net.add_arc(ArcType::place_to_transition(p1, u), None);
+        net.add_arc(ArcType::inhibitor(p2, u), Some(4));
+        net.add_arc(ArcType::inhibitor(p2, u), Some(2));
+        net.add_arc(ArcType::reset(p0, u), None);
+        let adjacency = net.adjacency();
+
+        assert_eq!(adjacency.places().len(), 3);
+        assert_eq!(adjacency.transitions().len(), 2);
+        assert_eq!(adjacency.weighted_preset_of_transition(t), [(p0, 3)]);
+        assert_eq!(
+            adjacency.weighted_postset_of_transition(t),
+            [(p1, 1), (p2, 3)]
+        );
+        assert_eq!(adjacency.inhibitor_arcs_of_transition(u), [(p2, 2)]);
+        assert_eq!(adjacency.reset_places_of_transition(u), [p0]);
+        assert_eq!(adjacency.postset_of_place(p0).collect::<Vec<_>>(), [t]);
+        assert_eq!(adjacency.preset_of_place(p1).collect::<Vec<_>>(), [t]);
+        assert_eq!(adjacency.postset_of_place(p1).collect::<Vec<_>>(), [u]);
+        assert_eq!(adjacency.preset_of_place(p0).count(), 0);
+        // Unknown nodes have no neighbors
+        let unknown = TransitionID(uuid::Uuid::new_v4());
+        assert!(adjacency.weighted_preset_of_transition(unknown).is_empty());
+        assert_eq!(adjacency.transition_index(unknown), None);
+    }
+
+    #[test]
+    fn test_adjacency_agrees_with_net() {
+        let mut net = PetriNet::new();
+        let [p0, p1, p2] = [(); 3].map(|_| net.add_place(None));
+        let t = net.add_transition(None, None);
+        let u = net.add_transition(None, None);
+        net.add_arc(ArcType::place_to_transition(p0, t), Some(2));
+        net.add_arc(ArcType::transition_to_place(t, p1), None);
+        net.add_arc(ArcType::place_to_transition(p1, u), None);
+        net.add_arc(ArcType::transition_to_place(u, p2), None);
+        net.add_arc(ArcType::inhibitor(p0, u), None);
+        net.add_arc(ArcType::reset(p2, t), None);
+        let adjacency = net.adjacency();
+
+        for marking in [
+            Marking::from([(p0, 2)]),
+            Marking::from([(p0, 3), (p2, 1)]),
+            Marking::from([(p0, 2), (p1, 1)]),
+            Marking::from([(p1, 1)]),
+        ] {
+            assert_eq!(
+                adjacency.enabled_transitions(&marking),
+                net.enabled_transitions(&marking)
+            );
+            for t in [t, u] {
+                assert_eq!(adjacency.fire(&marking, t), net.fire(&marking, t));
+            }
+        }
+        assert!(adjacency.is_dead_marking(&Marking::from([(p0, 1), (p2, 1)])));
+        assert!(!adjacency.is_dead_marking(&Marking::from([(p1, 1)])));
+        let unknown = TransitionID(uuid::Uuid::new_v4());
+        assert_eq!(
+            adjacency.fire(&Marking::new(), unknown),
+            Err(FiringError::UnknownTransition(unknown))
+        );
+    }
+}
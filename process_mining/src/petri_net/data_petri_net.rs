@@ -0,0 +1,1005 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    petri_net_struct::{Marking, PetriNet, Transition, TransitionID},
+    token_game::FiringError,
+};
+
+/// Type of a [`DataVariable`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DataVariableType {
+    /// Boolean
+    Boolean,
+    /// Integer
+    Integer,
+    /// Floating point number
+    Float,
+    /// String
+    String,
+    /// Date (values are represented as [`DataValue::Integer`] milliseconds since the UNIX epoch)
+    Date,
+}
+
+impl DataVariableType {
+    /// Java class name used for this type in the `ProM` DPN format (e.g., `java.lang.Long`)
+    pub fn java_class_name(&self) -> &'static str {
+        match self {
+            DataVariableType::Boolean => "java.lang.Boolean",
+            DataVariableType::Integer => "java.lang.Long",
+            DataVariableType::Float => "java.lang.Double",
+            DataVariableType::String => "java.lang.String",
+            DataVariableType::Date => "java.util.Date",
+        }
+    }
+
+    /// Parse a Java class name used in the `ProM` DPN format
+    ///
+    /// Also accepts other integer and floating point classes (e.g., `java.lang.Integer` or `java.lang.Float`).
+    pub fn from_java_class_name(name: &str) -> Option<Self> {
+        match name {
+            "java.lang.Boolean" => Some(DataVariableType::Boolean),
+            "java.lang.Long" | "java.lang.Integer" | "java.lang.Short" | "java.lang.Byte" => {
+                Some(DataVariableType::Integer)
+            }
+            "java.lang.Double" | "java.lang.Float" => Some(DataVariableType::Float),
+            "java.lang.String" => Some(DataVariableType::String),
+            "java.util.Date" => Some(DataVariableType::Date),
+            _ => None,
+        }
+    }
+
+    /// Check if `value` is a valid value for a variable of this type
+    ///
+    /// Integers are also accepted for floating point and date variables.
+    pub fn accepts(&self, value: &DataValue) -> bool {
+        matches!(
+            (self, value),
+            (DataVariableType::Boolean, DataValue::Boolean(_))
+                | (
+                    DataVariableType::Integer | DataVariableType::Date,
+                    DataValue::Integer(_)
+                )
+                | (
+                    DataVariableType::Float,
+                    DataValue::Integer(_) | DataValue::Float(_)
+                )
+                | (DataVariableType::String, DataValue::String(_))
+        )
+    }
+
+    /// Parse a value of this type from a string (e.g., a `minValue` in PNML)
+    pub fn parse_value(&self, s: &str) -> Option<DataValue> {
+        let s = s.trim();
+        match self {
+            DataVariableType::Boolean => s.parse().ok().map(DataValue::Boolean),
+            DataVariableType::Integer | DataVariableType::Date => {
+                s.parse().ok().map(DataValue::Integer)
+            }
+            DataVariableType::Float => s.parse().ok().map(DataValue::Float),
+            DataVariableType::String => Some(DataValue::String(s.to_string())),
+        }
+    }
+}
+
+/// Value of a [`DataVariable`] (or of a sub-expression of a guard)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DataValue {
+    /// Boolean value
+    Boolean(bool),
+    /// Integer value
+    Integer(i64),
+    /// Floating point value
+    Float(f64),
+    /// String value
+    String(String),
+}
+
+impl DataValue {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            DataValue::Integer(i) => Some(*i as f64),
+            DataValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            DataValue::Boolean(_) => "boolean",
+            DataValue::Integer(_) => "integer",
+            DataValue::Float(_) => "float",
+            DataValue::String(_) => "string",
+        }
+    }
+}
+
+impl std::fmt::Display for DataValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataValue::Boolean(b) => write!(f, "{b}"),
+            DataValue::Integer(i) => write!(f, "{i}"),
+            DataValue::Float(x) => write!(f, "{x:?}"),
+            DataValue::String(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// Case variable of a data Petri net
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataVariable {
+    /// Name of the variable (as used in guards)
+    pub name: String,
+    /// Type of the variable
+    pub variable_type: DataVariableType,
+    /// Minimal value (if specified)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<DataValue>,
+    /// Maximal value (if specified)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<DataValue>,
+}
+
+impl DataVariable {
+    /// Create a new variable without minimal or maximal value
+    pub fn new<S: Into<String>>(name: S, variable_type: DataVariableType) -> Self {
+        Self {
+            name: name.into(),
+            variable_type,
+            min_value: None,
+            max_value: None,
+        }
+    }
+}
+
+/// Assignment of variable names to values
+pub type Valuation = HashMap<String, DataValue>;
+
+/// Binary operator in an [`Expression`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BinaryOperator {
+    /// Logical or (`||`)
+    Or,
+    /// Logical and (`&&`)
+    And,
+    /// Equality (`==`)
+    Eq,
+    /// Inequality (`!=`)
+    Ne,
+    /// Less than (`<`)
+    Lt,
+    /// Less than or equal (`<=`)
+    Le,
+    /// Greater than (`>`)
+    Gt,
+    /// Greater than or equal (`>=`)
+    Ge,
+    /// Addition (`+`)
+    Add,
+    /// Subtraction (`-`)
+    Sub,
+    /// Multiplication (`*`)
+    Mul,
+    /// Division (`/`)
+    Div,
+}
+
+impl BinaryOperator {
+    /// Symbol of the operator
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOperator::Or => "||",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Eq => "==",
+            BinaryOperator::Ne => "!=",
+            BinaryOperator::Lt => "<",
+            BinaryOperator::Le => "<=",
+            BinaryOperator::Gt => ">",
+            BinaryOperator::Ge => ">=",
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+        }
+    }
+}
+
+///
+/// Guard expression over case variables
+///
+/// The syntax follows the guards used in `ProM` data Petri nets, e.g., `(amount > 5000) && (decision' == "accept")`:
+///
+/// - Literals: integers, floats, strings in double quotes (with `\"` and `\\` escapes), `true` and `false`
+/// - Variables: names of [`DataVariable`]s; a trailing `'` refers to the value _written_ by the transition
+/// - Operators (by increasing precedence): `||`, `&&`, comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`), `+`/`-`, `*`/`/`, and the unary `!` and `-`
+///
+/// Expressions are (de-)serialized as strings (see [`Expression::parse`] and the [`std::fmt::Display`] implementation).
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Expression {
+    /// Constant value
+    Literal(DataValue),
+    /// Variable
+    Variable {
+        /// Name of the variable
+        name: String,
+        /// Whether the value written by the transition is referred to (`name'`)
+        primed: bool,
+    },
+    /// Logical negation
+    Not(Box<Expression>),
+    /// Arithmetic negation
+    Negate(Box<Expression>),
+    /// Binary operation
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+}
+
+/// Error when parsing or evaluating an [`Expression`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionError {
+    /// Syntax error at the given (byte) position of the input
+    Parse {
+        /// Byte position in the input
+        position: usize,
+        /// Description of the error
+        message: String,
+    },
+    /// A variable without a value was referenced
+    UndefinedVariable(String),
+    /// An operator was applied to values of unsupported types
+    TypeMismatch(String),
+    /// Division by zero
+    DivisionByZero,
+}
+
+impl std::fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpressionError::Parse { position, message } => {
+                write!(
+                    f,
+                    "Failed to parse expression at position {position}: {message}"
+                )
+            }
+            ExpressionError::UndefinedVariable(name) => write!(f, "Undefined variable {name:?}"),
+            ExpressionError::TypeMismatch(msg) => write!(f, "Type mismatch: {msg}"),
+            ExpressionError::DivisionByZero => write!(f, "Division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Value(DataValue),
+    Identifier(String, bool),
+    Operator(&'static str),
+    LeftParen,
+    RightParen,
+}
+
+const OPERATORS: [&str; 15] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "!", "(", ")",
+];
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let error = |position: usize, message: &str| ExpressionError::Parse {
+        position,
+        message: message.to_string(),
+    };
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        {
+            let mut is_float = false;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'0'..=b'9' => i += 1,
+                    b'.' => {
+                        is_float = true;
+                        i += 1;
+                    }
+                    b'e' | b'E' => {
+                        is_float = true;
+                        i += 1;
+                        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+                            i += 1;
+                        }
+                    }
+                    _ => break,
+                }
+            }
+            let number = &input[start..i];
+            let value = if is_float {
+                number.parse().ok().map(DataValue::Float)
+            } else {
+                number.parse().ok().map(DataValue::Integer)
+            };
+            tokens.push((
+                start,
+                Token::Value(value.ok_or_else(|| error(start, "invalid number"))?),
+            ));
+        } else if c == b'"' {
+            let mut s = String::new();
+            let mut chars = input[i + 1..].char_indices();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => s.push(escaped),
+                        None => return Err(error(start, "unterminated string")),
+                    },
+                    Some((offset, '"')) => {
+                        i += offset + 2;
+                        break;
+                    }
+                    Some((_, c)) => s.push(c),
+                    None => return Err(error(start, "unterminated string")),
+                }
+            }
+            tokens.push((start, Token::Value(DataValue::String(s))));
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'.' | b':'))
+            {
+                i += 1;
+            }
+            let name = &input[start..i];
+            let primed = bytes.get(i) == Some(&b'\'');
+            if primed {
+                i += 1;
+            }
+            tokens.push((
+                start,
+                match name {
+                    "true" if !primed => Token::Value(DataValue::Boolean(true)),
+                    "false" if !primed => Token::Value(DataValue::Boolean(false)),
+                    _ => Token::Identifier(name.to_string(), primed),
+                },
+            ));
+        } else if let Some(op) = OPERATORS.iter().find(|op| input[i..].starts_with(*op)) {
+            i += op.len();
+            tokens.push((
+                start,
+                match *op {
+                    "(" => Token::LeftParen,
+                    ")" => Token::RightParen,
+                    op => Token::Operator(op),
+                },
+            ));
+        } else {
+            return Err(error(start, "unexpected character"));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens of an expression
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    input_len: usize,
+}
+
+impl Parser {
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(p, _)| *p)
+            .unwrap_or(self.input_len)
+    }
+
+    fn error(&self, message: &str) -> ExpressionError {
+        ExpressionError::Parse {
+            position: self.position(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Consume the next token if it is one of the given operators
+    fn operator(&mut self, ops: &[(&str, BinaryOperator)]) -> Option<BinaryOperator> {
+        if let Some((_, Token::Operator(op))) = self.tokens.get(self.pos) {
+            if let Some((_, bin_op)) = ops.iter().find(|(o, _)| o == op) {
+                self.pos += 1;
+                return Some(*bin_op);
+            }
+        }
+        None
+    }
+
+    fn binary_level(
+        &mut self,
+        ops: &[(&str, BinaryOperator)],
+        next: fn(&mut Self) -> Result<Expression, ExpressionError>,
+    ) -> Result<Expression, ExpressionError> {
+        let mut lhs = next(self)?;
+        while let Some(op) = self.operator(ops) {
+            let rhs = next(self)?;
+            lhs = Expression::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn or(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary_level(&[("||", BinaryOperator::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary_level(&[("&&", BinaryOperator::And)], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary_level(
+            &[
+                ("==", BinaryOperator::Eq),
+                ("!=", BinaryOperator::Ne),
+                ("<=", BinaryOperator::Le),
+                (">=", BinaryOperator::Ge),
+                ("<", BinaryOperator::Lt),
+                (">", BinaryOperator::Gt),
+            ],
+            Self::additive,
+        )
+    }
+
+    fn additive(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary_level(
+            &[("+", BinaryOperator::Add), ("-", BinaryOperator::Sub)],
+            Self::multiplicative,
+        )
+    }
+
+    fn multiplicative(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary_level(
+            &[("*", BinaryOperator::Mul), ("/", BinaryOperator::Div)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expression, ExpressionError> {
+        match self.tokens.get(self.pos) {
+            Some((_, Token::Operator("!"))) => {
+                self.pos += 1;
+                Ok(Expression::Not(Box::new(self.unary()?)))
+            }
+            Some((_, Token::Operator("-"))) => {
+                self.pos += 1;
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, ExpressionError> {
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        match token {
+            Some(Token::Value(v)) => {
+                self.pos += 1;
+                Ok(Expression::Literal(v))
+            }
+            Some(Token::Identifier(name, primed)) => {
+                self.pos += 1;
+                Ok(Expression::Variable { name, primed })
+            }
+            Some(Token::LeftParen) => {
+                self.pos += 1;
+                let inner = self.or()?;
+                if self.tokens.get(self.pos).map(|(_, t)| t) != Some(&Token::RightParen) {
+                    return Err(self.error("expected ')'"));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            _ => Err(self.error("expected a value, variable or '('")),
+        }
+    }
+}
+
+/// Compare two values (numbers with numbers, strings with strings and booleans with booleans)
+fn compare(lhs: &DataValue, rhs: &DataValue) -> Result<Option<Ordering>, ExpressionError> {
+    match (lhs, rhs) {
+        (DataValue::Integer(a), DataValue::Integer(b)) => Ok(Some(a.cmp(b))),
+        (DataValue::String(a), DataValue::String(b)) => Ok(Some(a.cmp(b))),
+        (DataValue::Boolean(a), DataValue::Boolean(b)) => Ok(Some(a.cmp(b))),
+        _ => match (lhs.as_f64(), rhs.as_f64()) {
+            (Some(a), Some(b)) => Ok(a.partial_cmp(&b)),
+            _ => Err(ExpressionError::TypeMismatch(format!(
+                "cannot compare {} with {}",
+                lhs.type_name(),
+                rhs.type_name()
+            ))),
+        },
+    }
+}
+
+fn arithmetic(
+    op: BinaryOperator,
+    lhs: &DataValue,
+    rhs: &DataValue,
+) -> Result<DataValue, ExpressionError> {
+    if let (DataValue::Integer(a), DataValue::Integer(b)) = (lhs, rhs) {
+        let res = match op {
+            BinaryOperator::Add => a.checked_add(*b),
+            BinaryOperator::Sub => a.checked_sub(*b),
+            BinaryOperator::Mul => a.checked_mul(*b),
+            _ => None,
+        };
+        if let Some(res) = res {
+            return Ok(DataValue::Integer(res));
+        }
+    }
+    if let (DataValue::String(a), DataValue::String(b), BinaryOperator::Add) = (lhs, rhs, op) {
+        return Ok(DataValue::String(format!("{a}{b}")));
+    }
+    match (lhs.as_f64(), rhs.as_f64()) {
+        (Some(a), Some(b)) => Ok(DataValue::Float(match op {
+            BinaryOperator::Add => a + b,
+            BinaryOperator::Sub => a - b,
+            BinaryOperator::Mul => a * b,
+            _ if b == 0.0 => return Err(ExpressionError::DivisionByZero),
+            _ => a / b,
+        })),
+        _ => Err(ExpressionError::TypeMismatch(format!(
+            "cannot apply {} to {} and {}",
+            op.symbol(),
+            lhs.type_name(),
+            rhs.type_name()
+        ))),
+    }
+}
+
+fn as_bool(value: DataValue, context: &str) -> Result<bool, ExpressionError> {
+    match value {
+        DataValue::Boolean(b) => Ok(b),
+        v => Err(ExpressionError::TypeMismatch(format!(
+            "{context} expects boolean, got {}",
+            v.type_name()
+        ))),
+    }
+}
+
+impl Expression {
+    /// Parse an expression (see [`Expression`] for the syntax)
+    pub fn parse(input: &str) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            input_len: input.len(),
+        };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("unexpected token"));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate the expression
+    ///
+    /// Unprimed variables are looked up in `valuation`.
+    /// Primed variables (`x'`) are looked up in `written`, falling back to `valuation` (i.e., the value is not changed).
+    ///
+    /// Integer arithmetic stays integer (except for division and on overflow, which yield floats).
+    /// `&&` and `||` are short-circuiting.
+    pub fn evaluate(
+        &self,
+        valuation: &Valuation,
+        written: &Valuation,
+    ) -> Result<DataValue, ExpressionError> {
+        match self {
+            Expression::Literal(v) => Ok(v.clone()),
+            Expression::Variable { name, primed } => {
+                let value = if *primed {
+                    written.get(name).or_else(|| valuation.get(name))
+                } else {
+                    valuation.get(name)
+                };
+                value
+                    .cloned()
+                    .ok_or_else(|| ExpressionError::UndefinedVariable(name.clone()))
+            }
+            Expression::Not(inner) => Ok(DataValue::Boolean(!as_bool(
+                inner.evaluate(valuation, written)?,
+                "!",
+            )?)),
+            Expression::Negate(inner) => match inner.evaluate(valuation, written)? {
+                DataValue::Integer(i) => Ok(i
+                    .checked_neg()
+                    .map(DataValue::Integer)
+                    .unwrap_or(DataValue::Float(-(i as f64)))),
+                DataValue::Float(f) => Ok(DataValue::Float(-f)),
+                v => Err(ExpressionError::TypeMismatch(format!(
+                    "- expects a number, got {}",
+                    v.type_name()
+                ))),
+            },
+            Expression::Binary(op, lhs, rhs) => {
+                let lhs = lhs.evaluate(valuation, written)?;
+                match op {
+                    BinaryOperator::Or | BinaryOperator::And => {
+                        let lhs = as_bool(lhs, op.symbol())?;
+                        if lhs == (*op == BinaryOperator::Or) {
+                            return Ok(DataValue::Boolean(lhs));
+                        }
+                        Ok(DataValue::Boolean(as_bool(
+                            rhs.evaluate(valuation, written)?,
+                            op.symbol(),
+                        )?))
+                    }
+                    BinaryOperator::Eq
+                    | BinaryOperator::Ne
+                    | BinaryOperator::Lt
+                    | BinaryOperator::Le
+                    | BinaryOperator::Gt
+                    | BinaryOperator::Ge => {
+                        let ord = compare(&lhs, &rhs.evaluate(valuation, written)?)?;
+                        Ok(DataValue::Boolean(match op {
+                            BinaryOperator::Eq => ord == Some(Ordering::Equal),
+                            BinaryOperator::Ne => ord != Some(Ordering::Equal),
+                            BinaryOperator::Lt => ord == Some(Ordering::Less),
+                            BinaryOperator::Le => {
+                                matches!(ord, Some(Ordering::Less | Ordering::Equal))
+                            }
+                            BinaryOperator::Gt => ord == Some(Ordering::Greater),
+                            _ => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+                        }))
+                    }
+                    _ => arithmetic(*op, &lhs, &rhs.evaluate(valuation, written)?),
+                }
+            }
+        }
+    }
+
+    /// Evaluate the expression as a guard (see [`Expression::evaluate`]), which must result in a boolean
+    pub fn evaluate_guard(
+        &self,
+        valuation: &Valuation,
+        written: &Valuation,
+    ) -> Result<bool, ExpressionError> {
+        as_bool(self.evaluate(valuation, written)?, "guard")
+    }
+
+    /// Get the names of all variables referenced in the expression (sorted, without duplicates)
+    ///
+    /// The boolean indicates whether the variable is referenced primed (i.e., the written value).
+    pub fn variables(&self) -> Vec<(String, bool)> {
+        fn collect(e: &Expression, res: &mut Vec<(String, bool)>) {
+            match e {
+                Expression::Literal(_) => {}
+                Expression::Variable { name, primed } => res.push((name.clone(), *primed)),
+                Expression::Not(inner) | Expression::Negate(inner) => collect(inner, res),
+                Expression::Binary(_, lhs, rhs) => {
+                    collect(lhs, res);
+                    collect(rhs, res);
+                }
+            }
+        }
+        let mut res = Vec::new();
+        collect(self, &mut res);
+        res.sort();
+        res.dedup();
+        res
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Literal(DataValue::String(s)) => {
+                write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            Expression::Literal(v) => write!(f, "{v}"),
+            Expression::Variable { name, primed } => {
+                write!(f, "{name}{}", if *primed { "'" } else { "" })
+            }
+            Expression::Not(inner) => write!(f, "!{inner}"),
+            Expression::Negate(inner) => write!(f, "-{inner}"),
+            Expression::Binary(op, lhs, rhs) => write!(f, "({lhs} {} {rhs})", op.symbol()),
+        }
+    }
+}
+
+impl std::str::FromStr for Expression {
+    type Err = ExpressionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Expression::parse(s)
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = ExpressionError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Expression::parse(&value)
+    }
+}
+
+impl From<Expression> for String {
+    fn from(value: Expression) -> Self {
+        value.to_string()
+    }
+}
+
+/// Error when firing a transition in a data Petri net (see [`PetriNet::fire_with_data`])
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataFiringError {
+    /// The transition cannot fire based on the marking (control-flow)
+    Firing(FiringError),
+    /// The guard of the transition evaluated to `false`
+    GuardNotSatisfied(TransitionID),
+    /// The guard of the transition could not be evaluated
+    Guard(TransitionID, ExpressionError),
+    /// A variable was written which the transition does not write
+    UnexpectedWrite(TransitionID, String),
+    /// A value of the wrong type was written to a variable
+    InvalidValue {
+        /// Variable name
+        variable: String,
+        /// Written value
+        value: DataValue,
+    },
+}
+
+impl std::fmt::Display for DataFiringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataFiringError::Firing(e) => write!(f, "{e}"),
+            DataFiringError::GuardNotSatisfied(t) => {
+                write!(f, "Guard of transition {} is not satisfied", t.0)
+            }
+            DataFiringError::Guard(t, e) => {
+                write!(f, "Guard of transition {} could not be evaluated: {e}", t.0)
+            }
+            DataFiringError::UnexpectedWrite(t, var) => {
+                write!(f, "Transition {} does not write variable {var:?}", t.0)
+            }
+            DataFiringError::InvalidValue { variable, value } => {
+                write!(f, "Invalid value {value} for variable {variable:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataFiringError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataFiringError::Firing(e) => Some(e),
+            DataFiringError::Guard(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<FiringError> for DataFiringError {
+    fn from(e: FiringError) -> Self {
+        Self::Firing(e)
+    }
+}
+
+/// State of a data Petri net: A [`Marking`] together with a [`Valuation`] of the case variables
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DataState {
+    /// Marking
+    pub marking: Marking,
+    /// Current values of the variables (unassigned variables are not contained)
+    pub valuation: Valuation,
+}
+
+impl PetriNet {
+    /// Check if this net has any data perspective (variables, guards or variable writes)
+    pub fn is_data_petri_net(&self) -> bool {
+        !self.variables.is_empty()
+            || self
+                .transitions
+                .values()
+                .any(|t| t.guard.is_some() || !t.write_variables.is_empty())
+    }
+
+    /// Get the [`DataVariable`] with the given name
+    pub fn variable(&self, name: &str) -> Option<&DataVariable> {
+        self.variables.iter().find(|v| v.name == name)
+    }
+
+    /// Get the initial [`DataState`]: The initial marking (see [`PetriNet::initial_marking_or_empty`]) and the given valuation
+    pub fn initial_data_state(&self, valuation: Valuation) -> DataState {
+        DataState {
+            marking: self.initial_marking_or_empty(),
+            valuation,
+        }
+    }
+
+    /// Check if transition `t` is enabled in `state` when writing the variable values `written`
+    ///
+    /// Requires `t` to be enabled in the marking (see [`PetriNet::is_enabled`]), `written` to be a valid write of `t`
+    /// (see [`PetriNet::fire_with_data`]) and the guard of `t` (if any) to evaluate to `true`.
+    /// Written variables which are not assigned in `written` keep their current value (also when evaluating the guard).
+    /// Returns an error if the guard cannot be evaluated (e.g., because a variable is not assigned).
+    pub fn is_enabled_with_data(
+        &self,
+        state: &DataState,
+        t: TransitionID,
+        written: &Valuation,
+    ) -> Result<bool, ExpressionError> {
+        if !self.is_enabled(&state.marking, t) || self.check_writes(t, written).is_err() {
+            return Ok(false);
+        }
+        match self.transitions.get(&t.0).and_then(|t| t.guard.as_ref()) {
+            Some(guard) => guard.evaluate_guard(&state.valuation, written),
+            None => Ok(true),
+        }
+    }
+
+    /// Fire transition `t` in `state` writing the variable values `written`, returning the resulting state
+    ///
+    /// Only variables in the `write_variables` of `t` can be written, and written values must match the type of the
+    /// corresponding [`DataVariable`] (if declared).
+    /// See [`PetriNet::is_enabled_with_data`] for when a transition is enabled.
+    pub fn fire_with_data(
+        &self,
+        state: &DataState,
+        t: TransitionID,
+        written: &Valuation,
+    ) -> Result<DataState, DataFiringError> {
+        let transition = self.check_writes(t, written)?;
+        let marking = self.fire(&state.marking, t)?;
+        if let Some(guard) = &transition.guard {
+            let satisfied = guard
+                .evaluate_guard(&state.valuation, written)
+                .map_err(|e| DataFiringError::Guard(t, e))?;
+            if !satisfied {
+                return Err(DataFiringError::GuardNotSatisfied(t));
+            }
+        }
+        let mut valuation = state.valuation.clone();
+        valuation.extend(written.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Ok(DataState { marking, valuation })
+    }
+
+    /// Check that `t` exists and may write the variable values `written`, returning the transition
+    ///
+    /// Only variables in the `write_variables` of `t` can be written, with values matching the type of the
+    /// corresponding [`DataVariable`] (if declared).
+    fn check_writes(
+        &self,
+        t: TransitionID,
+        written: &Valuation,
+    ) -> Result<&Transition, DataFiringError> {
+        let transition = self
+            .transitions
+            .get(&t.0)
+            .ok_or(FiringError::UnknownTransition(t))?;
+        for (name, value) in written {
+            if !transition.write_variables.contains(name) {
+                return Err(DataFiringError::UnexpectedWrite(t, name.clone()));
+            }
+            if let Some(var) = self.variable(name) {
+                if !var.variable_type.accepts(value) {
+                    return Err(DataFiringError::InvalidValue {
+                        variable: name.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        Ok(transition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::petri_net_struct::ArcType;
+
+    #[test]
+    fn test_expression_parse_and_evaluate() {
+        let guard =
+            Expression::parse(r#"(amount > 5000 && decision' == "accept") || !vip"#).unwrap();
+        assert_eq!(
+            guard.to_string(),
+            r#"(((amount > 5000) && (decision' == "accept")) || !vip)"#
+        );
+        assert_eq!(Expression::parse(&guard.to_string()).unwrap(), guard);
+        assert_eq!(
+            guard.variables(),
+            vec![
+                ("amount".to_string(), false),
+                ("decision".to_string(), true),
+                ("vip".to_string(), false)
+            ]
+        );
+
+        let valuation = Valuation::from([
+            ("amount".to_string(), DataValue::Float(6000.5)),
+            ("vip".to_string(), DataValue::Boolean(true)),
+        ]);
+        let accept = Valuation::from([(
+            "decision".to_string(),
+            DataValue::String("accept".to_string()),
+        )]);
+        assert_eq!(guard.evaluate_guard(&valuation, &accept), Ok(true));
+        assert_eq!(
+            guard.evaluate_guard(&valuation, &Valuation::new()),
+            Err(ExpressionError::UndefinedVariable("decision".to_string()))
+        );
+
+        let arithmetic = Expression::parse("-2 * (3 + x) / 4 - 1.5e1").unwrap();
+        let x = Valuation::from([("x".to_string(), DataValue::Integer(1))]);
+        assert_eq!(
+            arithmetic.evaluate(&x, &Valuation::new()),
+            Ok(DataValue::Float(-17.0))
+        );
+        // Integer overflow yields floats
+        let min = Valuation::from([("x".to_string(), DataValue::Integer(i64::MIN))]);
+        assert_eq!(
+            Expression::parse("-x")
+                .unwrap()
+                .evaluate(&min, &Valuation::new()),
+            Ok(DataValue::Float(-(i64::MIN as f64)))
+        );
+        assert!(matches!(
+            Expression::parse("(a > 1"),
+            Err(ExpressionError::Parse { position: 6, .. })
+        ));
+        assert!(matches!(
+            Expression::parse("x > \"b\"")
+                .unwrap()
+                .evaluate(&x, &Valuation::new()),
+            Err(ExpressionError::TypeMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_fire_with_data() {
+        let mut net = PetriNet::new();
+        let p1 = net.add_place(None);
+        let p2 = net.add_place(None);
+        let t = net.add_transition(Some("approve".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p1, t), None);
+        net.add_arc(ArcType::transition_to_place(t, p2), None);
+        net.initial_marking = Some(Marking::from([(p1, 1)]));
+        net.variables
+            .push(DataVariable::new("amount", DataVariableType::Integer));
+        let transition = net.transitions.get_mut(&t.0).unwrap();
+        transition.guard = Some(Expression::parse("amount' <= 1000").unwrap());
+        transition.write_variables = vec!["amount".to_string()];
+        assert!(net.is_data_petri_net());
+
+        let state = net.initial_data_state(Valuation::new());
+        let small = Valuation::from([("amount".to_string(), DataValue::Integer(500))]);
+        let large = Valuation::from([("amount".to_string(), DataValue::Integer(5000))]);
+        assert_eq!(net.is_enabled_with_data(&state, t, &small), Ok(true));
+        assert_eq!(net.is_enabled_with_data(&state, t, &large), Ok(false));
+        assert_eq!(
+            net.fire_with_data(&state, t, &large),
+            Err(DataFiringError::GuardNotSatisfied(t))
+        );
+        // Invalid writes are rejected consistently
+        let invalid = Valuation::from([("amount".to_string(), DataValue::Boolean(true))]);
+        assert_eq!(net.is_enabled_with_data(&state, t, &invalid), Ok(false));
+        assert!(matches!(
+            net.fire_with_data(&state, t, &invalid),
+            Err(DataFiringError::InvalidValue { .. })
+        ));
+        let unexpected = Valuation::from([("other".to_string(), DataValue::Integer(1))]);
+        assert_eq!(net.is_enabled_with_data(&state, t, &unexpected), Ok(false));
+        assert_eq!(
+            net.fire_with_data(&state, t, &unexpected),
+            Err(DataFiringError::UnexpectedWrite(t, "other".to_string()))
+        );
+
+        let next = net.fire_with_data(&state, t, &small).unwrap();
+        assert_eq!(next.marking, Marking::from([(p2, 1)]));
+        assert_eq!(next.valuation["amount"], DataValue::Integer(500));
+        assert_eq!(
+            net.fire_with_data(&next, t, &small),
+            Err(DataFiringError::Firing(FiringError::NotEnabled(t)))
+        );
+    }
+}
@@ -1,16 +1,156 @@
-use std::{fs::File, io::Write};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::Write,
+};
 
 use quick_xml::{events::BytesText, Writer};
 use uuid::Uuid;
 
 use crate::utils::xml_utils::XMLWriterWrapper;
 
-use super::petri_net_struct::PetriNet;
+use super::petri_net_struct::{ArcGraphics, ArcType, NodeGraphics, PetriNet, Position};
 const OK: Result<(), quick_xml::Error> = Ok::<(), quick_xml::Error>(());
 
+/// Get the PNML IDs of all places and transitions
+///
+/// Original IDs are used if available (and unique), otherwise the UUIDs.
+fn pnml_node_ids(pn: &PetriNet) -> HashMap<Uuid, String> {
+    let mut nodes: Vec<(&Uuid, Option<&String>)> = pn
+        .places
+        .iter()
+        .map(|(id, p)| (id, p.original_id.as_ref()))
+        .chain(
+            pn.transitions
+                .iter()
+                .map(|(id, t)| (id, t.original_id.as_ref())),
+        )
+        .collect();
+    nodes.sort();
+    let uuids: HashSet<String> = nodes.iter().map(|(id, _)| id.to_string()).collect();
+    let ids = unique_pnml_ids(
+        nodes
+            .iter()
+            .map(|(id, original_id)| (*original_id, id.to_string())),
+        &uuids,
+        HashSet::new(),
+    );
+    nodes.into_iter().map(|(id, _)| *id).zip(ids).collect()
+}
+
+/// Get the PNML IDs of all arcs (in the order of [`PetriNet::arcs`])
+///
+/// Original IDs are used if available and unique (also among the node IDs), otherwise an ID derived from the
+/// source, target and type of the arc.
+fn pnml_arc_ids(pn: &PetriNet, node_ids: &HashMap<Uuid, String>) -> Vec<String> {
+    unique_pnml_ids(
+        pn.arcs.iter().map(|arc| {
+            let (from, to) = arc.from_to.endpoints();
+            let arc_type = match arc.from_to {
+                ArcType::Inhibitor(_, _) => "inhibitor",
+                ArcType::Reset(_, _) => "reset",
+                ArcType::PlaceTransition(_, _) | ArcType::TransitionPlace(_, _) => "",
+            };
+            (
+                arc.original_id.as_ref(),
+                node_ids[&from].clone() + &node_ids[&to] + arc_type,
+            )
+        }),
+        &HashSet::new(),
+        node_ids.values().cloned().collect(),
+    )
+}
+
+/// Assign unique IDs, preferring original IDs over fallback IDs
+///
+/// Original IDs are used unless they are `reserved` (for fallback IDs) or already `used` (e.g., by a previous element).
+/// All other elements get their fallback ID, with a numeric suffix if it is already taken.
+fn unique_pnml_ids<'a>(
+    ids: impl Iterator<Item = (Option<&'a String>, String)>,
+    reserved: &HashSet<String>,
+    mut used: HashSet<String>,
+) -> Vec<String> {
+    let ids: Vec<(Option<String>, String)> = ids
+        .map(|(original_id, fallback)| match original_id {
+            Some(o) if !reserved.contains(o) && used.insert(o.clone()) => {
+                (Some(o.clone()), fallback)
+            }
+            _ => (None, fallback),
+        })
+        .collect();
+    ids.into_iter()
+        .map(|(original_id, fallback)| {
+            original_id.unwrap_or_else(|| {
+                let mut id = fallback.clone();
+                let mut suffix = 1;
+                while !used.insert(id.clone()) {
+                    suffix += 1;
+                    id = format!("{fallback}_{suffix}");
+                }
+                id
+            })
+        })
+        .collect()
+}
+
+/// Write a `<position>` or `<dimension>` element
+fn write_position<W: Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    position: &Position,
+) -> Result<(), quick_xml::Error> {
+    writer
+        .create_element(name)
+        .with_attributes(vec![
+            ("x", position.x.to_string().as_str()),
+            ("y", position.y.to_string().as_str()),
+        ])
+        .write_empty()?;
+    OK
+}
+
+/// Write the `<graphics>` element of a place or transition
+fn write_node_graphics<W: Write>(
+    writer: &mut Writer<W>,
+    graphics: &NodeGraphics,
+) -> Result<(), quick_xml::Error> {
+    writer
+        .create_element("graphics")
+        .write_inner_content(|writer| {
+            if let Some(position) = &graphics.position {
+                write_position(writer, "position", position)?;
+            }
+            if let Some(dimension) = &graphics.dimension {
+                write_position(writer, "dimension", dimension)?;
+            }
+            OK
+        })?;
+    OK
+}
+
+/// Write the `<graphics>` element of an arc
+fn write_arc_graphics<W: Write>(
+    writer: &mut Writer<W>,
+    graphics: &ArcGraphics,
+) -> Result<(), quick_xml::Error> {
+    writer
+        .create_element("graphics")
+        .write_inner_content(|writer| {
+            for position in &graphics.positions {
+                write_position(writer, "position", position)?;
+            }
+            OK
+        })?;
+    OK
+}
+
 ///
 /// Export a [`PetriNet`] to the PNML format and write the result to the provided writer which implements into [`quick_xml::Writer`] / [`std::io::Write`]
 ///
+/// Original IDs, place names and layout information (e.g., from [`super::import_pnml::import_pnml`]) are written if available.
+/// Inhibitor and reset arcs are marked using an `<arctype>` element (as used by `ProM`).
+/// Variables, guards and variable writes of data Petri nets are written in the `ProM` DPN format.
+///
 pub fn export_petri_net_to_pnml<'a, W>(
     pn: &PetriNet,
     into_writer: impl Into<XMLWriterWrapper<'a, W>>,
@@ -20,6 +160,8 @@ where
 {
     let mut xml_writer: XMLWriterWrapper<'_, W> = into_writer.into();
     let writer = xml_writer.to_xml_writer();
+    let node_ids = pnml_node_ids(pn);
+    let arc_ids = pnml_arc_ids(pn, &node_ids);
     writer
         .create_element("pnml")
         .write_inner_content(|writer| {
@@ -40,7 +182,7 @@ where
                             pn.places.iter().for_each(|(id, place)| {
                                 writer
                                     .create_element("place")
-                                    .with_attribute(("id", id.to_string().as_str()))
+                                    .with_attribute(("id", node_ids[id].as_str()))
                                     .write_inner_content(|writer| {
                                         writer
                                             .create_element("name")
@@ -48,12 +190,18 @@ where
                                                 writer
                                                     .create_element("text")
                                                     .write_text_content(BytesText::new(
-                                                        id.to_string().as_str(),
+                                                        place
+                                                            .name
+                                                            .as_ref()
+                                                            .unwrap_or(&node_ids[id]),
                                                     ))
                                                     .unwrap();
                                                 OK
                                             })
                                             .unwrap();
+                                        if let Some(graphics) = &place.graphics {
+                                            write_node_graphics(writer, graphics).unwrap();
+                                        }
                                         if let Some(initial_marking) = pn.initial_marking.clone() {
                                             if initial_marking.contains_key(&place.into()) {
                                                 let tokens =
@@ -78,9 +226,11 @@ where
                                     .unwrap();
                             });
                             pn.transitions.iter().for_each(|(id, transition)| {
+                                let guard = transition.guard.as_ref().map(|g| g.to_string());
                                 writer
                                     .create_element("transition")
-                                    .with_attribute(("id", id.to_string().as_str()))
+                                    .with_attribute(("id", node_ids[id].as_str()))
+                                    .with_attributes(guard.as_deref().map(|guard| ("guard", guard)))
                                     .write_inner_content(|writer| {
                                         writer
                                             .create_element("name")
@@ -98,6 +248,9 @@ where
                                                 OK
                                             })
                                             .unwrap();
+                                        if let Some(graphics) = &transition.graphics {
+                                            write_node_graphics(writer, graphics).unwrap();
+                                        }
                                         if transition.label.is_none() {
                                             // TODO: Add  something like <toolspecific tool="ProM" version="6.4" activity="$invisible$" localNodeID="..."/>
                                             writer
@@ -114,28 +267,31 @@ where
                                                 .write_empty()
                                                 .unwrap();
                                         }
+                                        for variable in &transition.write_variables {
+                                            writer
+                                                .create_element("writeVariable")
+                                                .write_text_content(BytesText::new(variable))
+                                                .unwrap();
+                                        }
                                         OK
                                     })
                                     .unwrap();
                             });
-                            pn.arcs.iter().for_each(|arc| {
-                                let (source_id, target_id) = match arc.from_to {
-                                    super::petri_net_struct::ArcType::PlaceTransition(from, to) => {
-                                        (from, to)
-                                    }
-                                    super::petri_net_struct::ArcType::TransitionPlace(from, to) => {
-                                        (from, to)
-                                    }
+                            pn.arcs.iter().zip(&arc_ids).for_each(|(arc, arc_id)| {
+                                let (from, to) = arc.from_to.endpoints();
+                                let (source_id, target_id) = (&node_ids[&from], &node_ids[&to]);
+                                // Arc type (ProM convention: <arctype><text>inhibitor</text></arctype>)
+                                let arc_type = match arc.from_to {
+                                    ArcType::Inhibitor(_, _) => Some("inhibitor"),
+                                    ArcType::Reset(_, _) => Some("reset"),
+                                    ArcType::PlaceTransition(_, _)
+                                    | ArcType::TransitionPlace(_, _) => None,
                                 };
                                 writer
                                     .create_element("arc")
-                                    .with_attribute((
-                                        "id",
-                                        (source_id.to_string() + target_id.to_string().as_str())
-                                            .as_str(),
-                                    ))
-                                    .with_attribute(("source", source_id.to_string().as_str()))
-                                    .with_attribute(("target", target_id.to_string().as_str()))
+                                    .with_attribute(("id", arc_id.as_str()))
+                                    .with_attribute(("source", source_id.as_str()))
+                                    .with_attribute(("target", target_id.as_str()))
                                     .write_inner_content(|w| {
                                         w.create_element("inscription")
                                             .write_inner_content(|w| {
@@ -147,6 +303,21 @@ where
                                                 OK
                                             })
                                             .unwrap();
+                                        if let Some(graphics) = &arc.graphics {
+                                            write_arc_graphics(w, graphics).unwrap();
+                                        }
+                                        if let Some(arc_type) = arc_type {
+                                            w.create_element("arctype")
+                                                .write_inner_content(|w| {
+                                                    w.create_element("text")
+                                                        .write_text_content(BytesText::new(
+                                                            arc_type,
+                                                        ))
+                                                        .unwrap();
+                                                    OK
+                                                })
+                                                .unwrap();
+                                        }
                                         OK
                                     })
                                     .unwrap();
@@ -168,7 +339,13 @@ where
                                                     .create_element("place")
                                                     .with_attribute((
                                                         "idref",
-                                                        place_id.get_uuid().to_string().as_str(),
+                                                        node_ids
+                                                            .get(&place_id.get_uuid())
+                                                            .cloned()
+                                                            .unwrap_or(
+                                                                place_id.get_uuid().to_string(),
+                                                            )
+                                                            .as_str(),
                                                     ))
                                                     .write_inner_content(|writer| {
                                                         writer
@@ -190,6 +367,44 @@ where
                             .unwrap();
                     }
 
+                    if !pn.variables.is_empty() {
+                        writer
+                            .create_element("variables")
+                            .write_inner_content(|writer| {
+                                pn.variables.iter().for_each(|variable| {
+                                    let min_value =
+                                        variable.min_value.as_ref().map(|v| v.to_string());
+                                    let max_value =
+                                        variable.max_value.as_ref().map(|v| v.to_string());
+                                    writer
+                                        .create_element("variable")
+                                        .with_attribute((
+                                            "type",
+                                            variable.variable_type.java_class_name(),
+                                        ))
+                                        .with_attributes(
+                                            min_value
+                                                .as_deref()
+                                                .map(|v| ("minValue", v))
+                                                .into_iter()
+                                                .chain(
+                                                    max_value.as_deref().map(|v| ("maxValue", v)),
+                                                ),
+                                        )
+                                        .write_inner_content(|writer| {
+                                            writer
+                                                .create_element("name")
+                                                .write_text_content(BytesText::new(&variable.name))
+                                                .unwrap();
+                                            OK
+                                        })
+                                        .unwrap();
+                                });
+                                OK
+                            })
+                            .unwrap();
+                    }
+
                     // </net>
                     OK
                 })
@@ -250,4 +465,150 @@ mod test {
         println!("file:///{}", export_path.to_string_lossy());
         Ok(())
     }
+
+    #[test]
+    fn test_export_pnml_preserves_ids_names_and_graphics() {
+        use crate::petri_net::import_pnml::import_pnml_reader;
+        use crate::petri_net::petri_net_struct::{
+            ArcGraphics, ArcType, Marking, NodeGraphics, PetriNet, Position,
+        };
+
+        let mut pn = PetriNet::new();
+        let p = pn.add_place(None);
+        let t = pn.add_transition(Some("a".to_string()), None);
+        pn.add_arc(ArcType::place_to_transition(p, t), None);
+        let place = pn.places.get_mut(&p.0).unwrap();
+        place.original_id = Some("p_start".to_string());
+        place.name = Some("Start".to_string());
+        place.graphics = Some(NodeGraphics {
+            position: Some(Position { x: 10.0, y: 20.5 }),
+            dimension: Some(Position { x: 40.0, y: 40.0 }),
+        });
+        pn.transitions.get_mut(&t.0).unwrap().original_id = Some("t_a".to_string());
+        let arc = pn.arcs.last_mut().unwrap();
+        arc.original_id = Some("arc_1".to_string());
+        arc.graphics = Some(ArcGraphics {
+            positions: vec![Position { x: 30.0, y: 25.0 }],
+        });
+        pn.initial_marking = Some(Marking::from([(p, 1)]));
+        pn.final_markings = Some(vec![Marking::from([(p, 1)])]);
+
+        let mut pnml: Vec<u8> = Vec::new();
+        export_petri_net_to_pnml(&pn, &mut pnml).unwrap();
+        let pnml_str = String::from_utf8(pnml.clone()).unwrap();
+        assert!(pnml_str.contains("<place id=\"p_start\">"));
+        assert!(pnml_str.contains("idref=\"p_start\""));
+
+        let imported = import_pnml_reader(&mut pnml.as_slice()).unwrap();
+        let place = imported.places.values().next().unwrap();
+        assert_eq!(place.original_id.as_deref(), Some("p_start"));
+        assert_eq!(place.name.as_deref(), Some("Start"));
+        assert_eq!(place.graphics, pn.places[&p.0].graphics);
+        let transition = imported.transitions.values().next().unwrap();
+        assert_eq!(transition.original_id.as_deref(), Some("t_a"));
+        assert_eq!(transition.label.as_deref(), Some("a"));
+        assert_eq!(imported.arcs[0].original_id.as_deref(), Some("arc_1"));
+        assert_eq!(imported.arcs[0].graphics, pn.arcs[0].graphics);
+        assert_eq!(imported.final_markings.unwrap()[0].len(), 1);
+    }
+
+    #[test]
+    fn test_export_pnml_unique_arc_ids() {
+        use crate::petri_net::petri_net_struct::{ArcType, PetriNet};
+        use std::collections::HashSet;
+
+        let mut pn = PetriNet::new();
+        let p = pn.add_place(None);
+        let t = pn.add_transition(Some("a".to_string()), None);
+        pn.places.get_mut(&p.0).unwrap().original_id = Some("p".to_string());
+        pn.transitions.get_mut(&t.0).unwrap().original_id = Some("t".to_string());
+        // Parallel arcs without original IDs
+        pn.add_arc(ArcType::place_to_transition(p, t), None);
+        pn.add_arc(ArcType::place_to_transition(p, t), None);
+        // Original IDs colliding with a node and with each other
+        for original_id in ["t", "arc", "arc", "pt"] {
+            pn.add_arc(ArcType::transition_to_place(t, p), None);
+            pn.arcs.last_mut().unwrap().original_id = Some(original_id.to_string());
+        }
+
+        let mut pnml: Vec<u8> = Vec::new();
+        export_petri_net_to_pnml(&pn, &mut pnml).unwrap();
+        let pnml = String::from_utf8(pnml).unwrap();
+        let ids: Vec<&str> = pnml
+            .split(" id=\"")
+            .skip(1)
+            .map(|s| s.split('"').next().unwrap())
+            .collect();
+        let unique: HashSet<&str> = ids.iter().copied().collect();
+        assert_eq!(ids.len(), unique.len(), "Duplicate IDs in {ids:?}");
+        assert!(unique.contains("arc"));
+        assert!(unique.contains("pt"));
+    }
+
+    #[test]
+    fn test_export_pnml_inhibitor_and_reset_arcs() {
+        use crate::petri_net::import_pnml::import_pnml_reader;
+        use crate::petri_net::petri_net_struct::{ArcType, PetriNet};
+
+        let mut pn = PetriNet::new();
+        let p = pn.add_place(None);
+        let t = pn.add_transition(Some("a".to_string()), None);
+        pn.add_arc(ArcType::inhibitor(p, t), Some(2));
+        pn.add_arc(ArcType::reset(p, t), None);
+        pn.add_arc(ArcType::place_to_transition(p, t), None);
+
+        let mut pnml: Vec<u8> = Vec::new();
+        export_petri_net_to_pnml(&pn, &mut pnml).unwrap();
+        let imported = import_pnml_reader(&mut pnml.as_slice()).unwrap();
+        assert_eq!(imported.arcs.len(), 3);
+        assert!(imported
+            .arcs
+            .iter()
+            .any(|arc| matches!(arc.from_to, ArcType::Inhibitor(_, _)) && arc.weight == 2));
+        assert!(imported
+            .arcs
+            .iter()
+            .any(|arc| matches!(arc.from_to, ArcType::Reset(_, _))));
+        assert!(imported
+            .arcs
+            .iter()
+            .any(|arc| matches!(arc.from_to, ArcType::PlaceTransition(_, _))));
+
+        // PIPE/TAPAAL convention
+        let pnml = r#"<pnml><net><page>
+            <place id="p"/><transition id="t"/>
+            <arc id="a" source="p" target="t"><type value="inhibitor"/></arc>
+        </page></net></pnml>"#;
+        let imported = import_pnml_reader(&mut pnml.as_bytes()).unwrap();
+        assert!(matches!(imported.arcs[0].from_to, ArcType::Inhibitor(_, _)));
+    }
+
+    #[test]
+    fn test_export_pnml_data_petri_net() {
+        use crate::petri_net::data_petri_net::{
+            DataValue, DataVariable, DataVariableType, Expression,
+        };
+        use crate::petri_net::import_pnml::import_pnml_reader;
+        use crate::petri_net::petri_net_struct::PetriNet;
+
+        let mut pn = PetriNet::new();
+        let t = pn.add_transition(Some("check".to_string()), None);
+        let mut amount = DataVariable::new("amount", DataVariableType::Float);
+        amount.min_value = Some(DataValue::Float(0.0));
+        pn.variables.push(amount);
+        pn.variables
+            .push(DataVariable::new("decision", DataVariableType::String));
+        let transition = pn.transitions.get_mut(&t.0).unwrap();
+        transition.guard =
+            Some(Expression::parse(r#"amount > 10.5 && decision' != "<none>""#).unwrap());
+        transition.write_variables = vec!["decision".to_string()];
+
+        let mut pnml: Vec<u8> = Vec::new();
+        export_petri_net_to_pnml(&pn, &mut pnml).unwrap();
+        let imported = import_pnml_reader(&mut pnml.as_slice()).unwrap();
+        assert_eq!(imported.variables, pn.variables);
+        let imported_transition = imported.transitions.values().next().unwrap();
+        assert_eq!(imported_transition.guard, pn.transitions[&t.0].guard);
+        assert_eq!(imported_transition.write_variables, vec!["decision"]);
+    }
 }
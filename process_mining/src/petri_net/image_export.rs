@@ -37,48 +37,70 @@ pub fn export_petri_net_image<P: AsRef<std::path::Path>>(
 /// Also see [`export_petri_net_image`], as well as [`export_petri_net_image_svg`] and [`export_petri_net_image_png`]
 ///
 pub fn export_petri_net_to_dot_graph(net: &PetriNet, dpi_factor: Option<f32>) -> Graph {
-    let place_nodes: Vec<_> = net
-        .places
+    let adjacency = net.adjacency();
+    let place_nodes: Vec<_> = adjacency
+        .places()
         .iter()
-        .map(|(p_id, p)| {
-            let symbol = if net.is_in_initial_marking(&p.into()) {
+        .map(|p| {
+            let p_id = p.0;
+            let symbol = if net.is_in_initial_marking(p) {
                 "●"
             } else {
                 ""
             };
-            let shape = if net.is_in_a_final_marking(&p.into()) {"doublecircle"} else {"circle"};
-            let size = if net.is_in_a_final_marking(&p.into()) {0.4} else {0.5};
+            let shape = if net.is_in_a_final_marking(p) {"doublecircle"} else {"circle"};
+            let size = if net.is_in_a_final_marking(p) {0.4} else {0.5};
             stmt!(node!(esc p_id; attr!("label", esc symbol), attr!("shape",shape), attr!("fixedsize",true), attr!("width",size), attr!("height",size)))
         }).collect();
 
-    let transition_nodes: Vec<_> = net
-        .transitions
+    let transition_nodes: Vec<_> = adjacency
+        .transitions()
         .iter()
-        .map(|(t_id, t)| {
-            let label = t.label.as_ref().cloned().unwrap_or_default();
+        .map(|t| {
+            let t_id = t.0;
+            let label = net.transitions[&t_id].label.as_ref().cloned().unwrap_or_default();
             let (font_size,width) = (12,1);
-            let fill_color = if t.label.is_none() { "black" } else { "white" };
+            let fill_color = if net.transitions[&t_id].label.is_none() { "black" } else { "white" };
             stmt!(node!(esc t_id; attr!("label", esc label), attr!("shape","box"), attr!("fontsize",font_size),attr!("style","filled"), attr!("fillcolor",fill_color), attr!("width",width), attr!("height",0.5)))
         }).collect();
 
-    let arcs: Vec<_> = net
-        .arcs
+    // Inhibitor arcs end in a circle, reset arcs in a double arrowhead
+    let arc = |from_id: Uuid, to_id: Uuid, weight: u64, arrowhead: Option<&str>| {
+        let mut attrs = if weight == 1 {
+            Vec::default()
+        } else {
+            vec![attr!("label", (format!("{}", weight)))]
+        };
+        if let Some(arrowhead) = arrowhead {
+            attrs.push(attr!("arrowhead", arrowhead));
+        }
+        stmt!(edge!(node_id!(esc from_id) => node_id!(esc to_id), attrs))
+    };
+    let arcs: Vec<_> = adjacency
+        .transitions()
         .iter()
-        .map(|arc| {
-            let (from_id, to_id) = match arc.from_to {
-                super::petri_net_struct::ArcType::PlaceTransition(place_id, transition_id) => {
-                    (place_id, transition_id)
-                }
-                super::petri_net_struct::ArcType::TransitionPlace(transition_id, place_id) => {
-                    (transition_id, place_id)
-                }
-            };
-            let attrs = if arc.weight == 1 {
-                Vec::default()
-            } else {
-                vec![attr!("label", (format!("{}", arc.weight)))]
-            };
-            stmt!(edge!(node_id!(esc from_id) => node_id!(esc to_id), attrs))
+        .flat_map(|t| {
+            let preset = adjacency
+                .weighted_preset_of_transition(*t)
+                .iter()
+                .map(|(p, w)| arc(p.0, t.0, *w, None));
+            let postset = adjacency
+                .weighted_postset_of_transition(*t)
+                .iter()
+                .map(|(p, w)| arc(t.0, p.0, *w, None));
+            let inhibitors = adjacency
+                .inhibitor_arcs_of_transition(*t)
+                .iter()
+                .map(|(p, w)| arc(p.0, t.0, *w, Some("odot")));
+            let resets = adjacency
+                .reset_places_of_transition(*t)
+                .iter()
+                .map(|p| arc(p.0, t.0, 1, Some("normalnormal")));
+            preset
+                .chain(postset)
+                .chain(inhibitors)
+                .chain(resets)
+                .collect::<Vec<_>>()
         })
         .collect();
 
@@ -5,7 +5,8 @@ use uuid::Uuid;
 
 use crate::PetriNet;
 
-use super::petri_net_struct::{ArcType, Marking, PlaceID};
+use super::data_petri_net::{DataVariable, DataVariableType, Expression};
+use super::petri_net_struct::{ArcGraphics, ArcType, Marking, NodeGraphics, PlaceID, Position};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Mode {
@@ -22,6 +23,38 @@ enum Mode {
     FinalMarkingMarkingPlace,
     Arc,
     ArcInscription,
+    PlaceGraphics,
+    TransitionGraphics,
+    ArcGraphics,
+    ArcTypeText,
+    TransitionWriteVariable,
+    Variables,
+    Variable,
+    VariableName,
+}
+
+/// Arc encountered during parsing, added to the [`PetriNet`] once all nodes are known
+struct ParsedArc {
+    source_id: String,
+    target_id: String,
+    weight: u32,
+    original_id: Option<String>,
+    graphics: Option<ArcGraphics>,
+    /// Arc type (e.g., `inhibitor` or `reset`), if specified
+    arc_type: Option<String>,
+}
+
+/// Parse a [`Position`] from the `x` and `y` attributes of an XML element
+fn parse_position(b: &quick_xml::events::BytesStart<'_>) -> Option<Position> {
+    let coordinate = |key: &str| -> Option<f64> {
+        read_to_string(&mut b.try_get_attribute(key).ok()??.value.as_ref())
+            .parse()
+            .ok()
+    };
+    Some(Position {
+        x: coordinate("x")?,
+        y: coordinate("y")?,
+    })
 }
 
 fn read_to_string(x: &mut &[u8]) -> String {
@@ -45,6 +78,19 @@ pub enum PNMLParseError {
     InvalidKeyValue(&'static str),
     /// Encountered no PNML tag (i.e., the parsed data was not a PNML file)
     NoPNMLTag,
+    /// Unsupported guard of a data Petri net transition (with the original transition ID and the guard included)
+    ///
+    /// Only returned if [`PNMLImportOptions::reject_unsupported_data`] is set.
+    UnsupportedGuard {
+        /// Original ID of the transition
+        transition: String,
+        /// The unsupported guard
+        guard: String,
+    },
+    /// Unsupported (or missing) type of a data Petri net variable (with the type included)
+    ///
+    /// Only returned if [`PNMLImportOptions::reject_unsupported_data`] is set.
+    UnsupportedVariableType(String),
 }
 
 impl std::fmt::Display for PNMLParseError {
@@ -83,6 +129,18 @@ impl From<QuickXMLError> for PNMLParseError {
     }
 }
 
+///
+/// Options for PNML Import (see [`import_pnml_with_options`])
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PNMLImportOptions {
+    /// Fail with a [`PNMLParseError`] on data Petri net elements which cannot be imported (unsupported guards or variable types)
+    ///
+    /// Otherwise (default), such guards and variables are skipped with a warning printed to stderr,
+    /// i.e., the transition is imported without guard and the variable is not declared.
+    pub reject_unsupported_data: bool,
+}
+
 ///
 /// Import a PNML file from the given XML reader ([`quick_xml::Reader`])
 ///
@@ -93,14 +151,32 @@ impl From<QuickXMLError> for PNMLParseError {
 ///
 /// - Places, Transitions
 /// - Silent transitions (toolspecific tag)
-/// - Basics arcs (i.e., not inhibitor arcs, etc.)
+/// - Basics arcs, as well as inhibitor and reset arcs (`<arctype>` as used by `ProM` or `<type value="..."/>`)
 /// - Arc weights
 /// - A single initial marking
 /// - Multiple final markings
+/// - Original IDs, place names and layout information (`<graphics>` positions and dimensions of nodes and arcs)
+/// - Data Petri nets in the `ProM` DPN format (`<variables>`, `guard` attributes and `<writeVariable>`s of transitions)
+///
 ///
+/// Unsupported guards and variable types are skipped (see [`import_pnml_with_options`] to reject them instead).
 ///
 /// Also see [`import_pnml_reader`] for an alternative version of this function, which takes a (standard) buffered reader implementing [`std::io::BufRead`] instead
 pub fn import_pnml<T>(reader: &mut Reader<T>) -> Result<PetriNet, PNMLParseError>
+where
+    T: BufRead,
+{
+    import_pnml_with_options(reader, &PNMLImportOptions::default())
+}
+
+///
+/// Import a PNML file from the given XML reader ([`quick_xml::Reader`]) using the passed [`PNMLImportOptions`]
+///
+/// See [`import_pnml`] for the supported Petri net features.
+pub fn import_pnml_with_options<T>(
+    reader: &mut Reader<T>,
+    options: &PNMLImportOptions,
+) -> Result<PetriNet, PNMLParseError>
 where
     T: BufRead,
 {
@@ -118,7 +194,10 @@ where
 
     let mut current_id: Option<Uuid> = None;
 
-    let mut arcs: Vec<(String, String, u32)> = Vec::new();
+    let mut arcs: Vec<ParsedArc> = Vec::new();
+
+    // Data variable currently being parsed (`None` if it is skipped)
+    let mut current_variable: Option<DataVariable> = None;
 
     loop {
         match reader.read_event_into(&mut buf)? {
@@ -163,8 +242,9 @@ where
                         let place_id_str = read_to_string(&mut place_id.value.as_ref());
                         let uuid = Uuid::new_v4();
                         current_id = Some(uuid);
-                        id_map.insert(place_id_str, uuid);
+                        id_map.insert(place_id_str.clone(), uuid);
                         pn.add_place(Some(uuid));
+                        pn.places.get_mut(&uuid).unwrap().original_id = Some(place_id_str);
                     }
                 }
                 b"transition" => {
@@ -176,8 +256,67 @@ where
                     let trans_id_str = read_to_string(&mut trans_id.value.as_ref());
                     let uuid = Uuid::new_v4();
                     current_id = Some(uuid);
-                    id_map.insert(trans_id_str, uuid);
+                    id_map.insert(trans_id_str.clone(), uuid);
                     pn.add_transition(Some(String::new()), Some(uuid));
+                    let trans = pn.transitions.get_mut(&uuid).unwrap();
+                    trans.original_id = Some(trans_id_str.clone());
+                    // Data Petri nets (ProM DPN): <transition guard="...">
+                    // Unsupported guards are rejected or skipped (i.e., the transition is not guarded)
+                    if let Some(guard) = b.try_get_attribute("guard").unwrap_or_default() {
+                        match guard.unescape_value().map(|g| Expression::parse(&g)) {
+                            Ok(Ok(guard)) => trans.guard = Some(guard),
+                            Ok(Err(_)) if guard.value.iter().all(u8::is_ascii_whitespace) => {}
+                            _ => {
+                                let guard = read_to_string(&mut guard.value.as_ref());
+                                if options.reject_unsupported_data {
+                                    return Err(PNMLParseError::UnsupportedGuard {
+                                        transition: trans_id_str,
+                                        guard,
+                                    });
+                                }
+                                eprintln!(
+                                    "Skipping unsupported guard {guard:?} of transition {trans_id_str}"
+                                );
+                            }
+                        }
+                    }
+                }
+                b"writeVariable" if current_mode == Mode::Transition => {
+                    current_mode = Mode::TransitionWriteVariable;
+                }
+                // Data Petri nets (ProM DPN): <variables><variable type="java.lang.Long"><name>...</name></variable></variables>
+                b"variables" if current_mode == Mode::Net => {
+                    current_mode = Mode::Variables;
+                }
+                // Variables with a missing or unsupported type are rejected or skipped
+                b"variable" if current_mode == Mode::Variables => {
+                    let type_name = b
+                        .try_get_attribute("type")
+                        .unwrap_or_default()
+                        .map(|t| read_to_string(&mut t.value.as_ref()))
+                        .unwrap_or_default();
+                    current_variable = match DataVariableType::from_java_class_name(&type_name) {
+                        Some(variable_type) => {
+                            let mut variable = DataVariable::new(String::new(), variable_type);
+                            let value = |key: &str| {
+                                b.try_get_attribute(key).ok().flatten().and_then(|v| {
+                                    variable_type
+                                        .parse_value(&read_to_string(&mut v.value.as_ref()))
+                                })
+                            };
+                            variable.min_value = value("minValue");
+                            variable.max_value = value("maxValue");
+                            Some(variable)
+                        }
+                        None if options.reject_unsupported_data => {
+                            return Err(PNMLParseError::UnsupportedVariableType(type_name));
+                        }
+                        None => {
+                            eprintln!("Skipping variable with unsupported type {type_name:?}");
+                            None
+                        }
+                    };
+                    current_mode = Mode::Variable;
                 }
                 b"arc" => {
                     let source_id = read_to_string(
@@ -198,13 +337,36 @@ where
                     );
                     // Only add arcs to vec here, and add them to the PetriNet only at the end
                     // bc. we do not know if the source/targets are encountered yet and we need to know which one is the transitions and which one the place
-                    arcs.push((source_id, target_id, 1));
+                    let original_id = b
+                        .try_get_attribute("id")
+                        .unwrap_or_default()
+                        .map(|id| read_to_string(&mut id.value.as_ref()));
+                    arcs.push(ParsedArc {
+                        source_id,
+                        target_id,
+                        weight: 1,
+                        original_id,
+                        graphics: None,
+                        arc_type: None,
+                    });
                     current_mode = Mode::Arc;
                 }
                 // Handle weighted arcs
-                b"inscription" => {
-                    if current_mode == Mode::Arc {
-                        current_mode = Mode::ArcInscription;
+                b"inscription" if current_mode == Mode::Arc => {
+                    current_mode = Mode::ArcInscription;
+                }
+                // Inhibitor and reset arcs
+                // ProM: <arctype><text>inhibitor</text></arctype> (possibly inside <toolspecific>)
+                b"arctype" if current_mode == Mode::Arc => {
+                    current_mode = Mode::ArcTypeText;
+                }
+                // PIPE/TAPAAL: <type value="inhibitor"/>
+                b"type" if current_mode == Mode::Arc => {
+                    if let (Some(arc), Some(value)) = (
+                        arcs.last_mut(),
+                        b.try_get_attribute("value").unwrap_or_default(),
+                    ) {
+                        arc.arc_type = Some(read_to_string(&mut value.value.as_ref()));
                     }
                 }
                 // For handling silent transitions
@@ -230,18 +392,56 @@ where
                     current_mode = Mode::InitialMarking;
                 }
                 b"finalmarkings" => current_mode = Mode::FinalMarkings,
-                b"marking" => {
-                    if current_mode == Mode::FinalMarkings {
-                        current_mode = Mode::FinalMarkingsMarking;
-                        // Add new final marking
-                        final_markings.push(HashMap::new());
-                    }
+                b"marking" if current_mode == Mode::FinalMarkings => {
+                    current_mode = Mode::FinalMarkingsMarking;
+                    // Add new final marking
+                    final_markings.push(HashMap::new());
                 }
                 b"name" => match current_mode {
                     Mode::Place => current_mode = Mode::PlaceName,
                     Mode::Transition => current_mode = Mode::TransitionName,
+                    Mode::Variable => current_mode = Mode::VariableName,
+                    _ => {}
+                },
+                // Layout information (graphics of names, etc. are ignored)
+                b"graphics" => match current_mode {
+                    Mode::Place => current_mode = Mode::PlaceGraphics,
+                    Mode::Transition => current_mode = Mode::TransitionGraphics,
+                    Mode::Arc => {
+                        current_mode = Mode::ArcGraphics;
+                        if let Some(arc) = arcs.last_mut() {
+                            arc.graphics = Some(ArcGraphics::default());
+                        }
+                    }
                     _ => {}
                 },
+                b"position" | b"dimension" => {
+                    let graphics = match current_mode {
+                        Mode::PlaceGraphics => current_id
+                            .and_then(|id| pn.places.get_mut(&id))
+                            .map(|p| p.graphics.get_or_insert_with(NodeGraphics::default)),
+                        Mode::TransitionGraphics => current_id
+                            .and_then(|id| pn.transitions.get_mut(&id))
+                            .map(|t| t.graphics.get_or_insert_with(NodeGraphics::default)),
+                        _ => None,
+                    };
+                    let position = parse_position(&b);
+                    if let Some(graphics) = graphics {
+                        if b.name().as_ref() == b"position" {
+                            graphics.position = position;
+                        } else {
+                            graphics.dimension = position;
+                        }
+                    } else if current_mode == Mode::ArcGraphics && b.name().as_ref() == b"position"
+                    {
+                        if let (Some(arc_graphics), Some(position)) = (
+                            arcs.last_mut().and_then(|arc| arc.graphics.as_mut()),
+                            position,
+                        ) {
+                            arc_graphics.positions.push(position);
+                        }
+                    }
+                }
                 _ => {}
             },
             quick_xml::events::Event::End(b) => match b.name().as_ref() {
@@ -263,17 +463,35 @@ where
                 }
                 b"finalmarkings" => current_mode = Mode::Net,
                 b"marking" => current_mode = Mode::FinalMarkings,
-                b"inscription" => {
-                    if current_mode == Mode::ArcInscription {
-                        current_mode = Mode::Arc
-                    }
+                b"inscription" if current_mode == Mode::ArcInscription => {
+                    current_mode = Mode::Arc;
                 }
                 b"arc" => {
                     current_mode = Mode::Net;
                 }
+                b"arctype" if current_mode == Mode::ArcTypeText => {
+                    current_mode = Mode::Arc;
+                }
                 b"name" => match current_mode {
                     Mode::PlaceName => current_mode = Mode::Place,
                     Mode::TransitionName => current_mode = Mode::Transition,
+                    Mode::VariableName => current_mode = Mode::Variable,
+                    _ => {}
+                },
+                b"writeVariable" if current_mode == Mode::TransitionWriteVariable => {
+                    current_mode = Mode::Transition;
+                }
+                b"variable" if current_mode == Mode::Variable => {
+                    pn.variables.extend(current_variable.take());
+                    current_mode = Mode::Variables;
+                }
+                b"variables" if current_mode == Mode::Variables => {
+                    current_mode = Mode::Net;
+                }
+                b"graphics" => match current_mode {
+                    Mode::PlaceGraphics => current_mode = Mode::Place,
+                    Mode::TransitionGraphics => current_mode = Mode::Transition,
+                    Mode::ArcGraphics => current_mode = Mode::Arc,
                     _ => {}
                 },
                 _ => {}
@@ -281,6 +499,11 @@ where
             quick_xml::events::Event::Text(t) => {
                 let text = read_to_string(&mut t.as_ref());
                 match current_mode {
+                    Mode::PlaceName => {
+                        if let Some(place) = current_id.and_then(|id| pn.places.get_mut(&id)) {
+                            place.name = Some(text);
+                        }
+                    }
                     Mode::TransitionName => {
                         if let Some(trans) = current_id.and_then(|id| pn.transitions.get_mut(&id)) {
                             // Only overwrite label if it is set to Some(...)
@@ -309,9 +532,24 @@ where
                             }
                         }
                     }
+                    Mode::TransitionWriteVariable => {
+                        if let Some(trans) = current_id.and_then(|id| pn.transitions.get_mut(&id)) {
+                            trans.write_variables.push(text);
+                        }
+                    }
+                    Mode::VariableName => {
+                        if let Some(variable) = current_variable.as_mut() {
+                            variable.name = text;
+                        }
+                    }
+                    Mode::ArcTypeText => {
+                        if let Some(arc) = arcs.last_mut() {
+                            arc.arc_type = Some(text);
+                        }
+                    }
                     Mode::ArcInscription => {
                         if let Some(arc) = arcs.last_mut() {
-                            arc.2 = text.parse::<u32>().unwrap_or(1);
+                            arc.weight = text.parse::<u32>().unwrap_or(1);
                         }
                     }
                     _ => {}
@@ -326,16 +564,23 @@ where
         return Err(PNMLParseError::NoPNMLTag);
     }
 
-    for (from, to, weight) in arcs {
-        let from_uuid = id_map.get(&from);
-        let to_uuid = id_map.get(&to);
+    for arc in arcs {
+        let from_uuid = id_map.get(&arc.source_id);
+        let to_uuid = id_map.get(&arc.target_id);
         if let Some(from_uuid) = from_uuid {
             if let Some(to_uuid) = to_uuid {
                 let mut from_to = None;
-                // Option 1: Place -> Transition
+                // Option 1: Place -> Transition (possibly an inhibitor or reset arc)
                 if let Some(place) = pn.places.get(from_uuid) {
                     if let Some(trans) = pn.transitions.get(to_uuid) {
-                        from_to = Some(ArcType::place_to_transition(place.into(), trans.into()));
+                        let (place, trans) = (place.into(), trans.into());
+                        from_to = Some(
+                            match arc.arc_type.as_deref().map(|t| t.trim().to_lowercase()) {
+                                Some(t) if t == "inhibitor" => ArcType::inhibitor(place, trans),
+                                Some(t) if t == "reset" => ArcType::reset(place, trans),
+                                _ => ArcType::place_to_transition(place, trans),
+                            },
+                        );
                     }
                 // Option 2: Transition -> Place
                 } else if let Some(trans) = pn.transitions.get(from_uuid) {
@@ -344,7 +589,10 @@ where
                     }
                 }
                 if let Some(from_to) = from_to {
-                    pn.add_arc(from_to, Some(weight))
+                    pn.add_arc(from_to, Some(arc.weight));
+                    let added = pn.arcs.last_mut().unwrap();
+                    added.original_id = arc.original_id;
+                    added.graphics = arc.graphics;
                 }
             }
         }
@@ -388,7 +636,7 @@ mod test {
 
     use crate::utils::test_utils::get_test_data_path;
 
-    use super::import_pnml;
+    use super::{import_pnml, import_pnml_with_options, PNMLImportOptions, PNMLParseError};
 
     #[test]
     fn test_pnml_import() {
@@ -443,4 +691,58 @@ mod test {
         let pn_res = import_pnml(&mut Reader::from_file(path).unwrap());
         assert!(pn_res.is_err());
     }
+
+    #[test]
+    fn test_pnml_import_unsupported_data_elements() {
+        // Unsupported variable types and guards are skipped, and `<variable>`s outside of `<variables>` are ignored
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<pnml><net id="net" type="http://www.pnml.org/version-2009/grammar/pnmlcoremodel">
+  <page id="page">
+    <place id="p"><name><text>p</text></name></place>
+    <transition id="t" guard="amount &gt; 10 &amp;&amp; ok"><name><text>t</text></name></transition>
+    <transition id="u" guard="amount in (1, 2)"><name><text>u</text></name></transition>
+    <toolspecific tool="other"><variable type="java.lang.Long"><name>other</name></variable></toolspecific>
+    <arc id="a" source="p" target="t"/>
+  </page>
+  <variables>
+    <variable type="java.lang.Long" minValue="0"><name>amount</name></variable>
+    <variable type="java.util.List"><name>items</name></variable>
+    <variable><name>untyped</name></variable>
+    <variable type="java.lang.Boolean"><name>ok</name></variable>
+  </variables>
+</net></pnml>"#;
+        let pn = import_pnml(&mut Reader::from_reader(xml.as_bytes())).unwrap();
+        let names: Vec<&str> = pn.variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["amount", "ok"]);
+        assert_eq!(pn.arcs.len(), 1);
+        let guarded = |label: &str| {
+            pn.transitions
+                .values()
+                .find(|t| t.label.as_deref() == Some(label))
+                .unwrap()
+                .guard
+                .is_some()
+        };
+        assert!(guarded("t"));
+        assert!(!guarded("u"));
+
+        let strict = PNMLImportOptions {
+            reject_unsupported_data: true,
+        };
+        let res = import_pnml_with_options(&mut Reader::from_reader(xml.as_bytes()), &strict);
+        assert!(matches!(
+            res,
+            Err(PNMLParseError::UnsupportedGuard { transition, guard })
+                if transition == "u" && guard == "amount in (1, 2)"
+        ));
+        let without_u = xml.replace(
+            r#"<transition id="u" guard="amount in (1, 2)"><name><text>u</text></name></transition>"#,
+            "",
+        );
+        let res = import_pnml_with_options(&mut Reader::from_reader(without_u.as_bytes()), &strict);
+        assert!(matches!(
+            res,
+            Err(PNMLParseError::UnsupportedVariableType(type_name)) if type_name == "java.util.List"
+        ));
+    }
 }
@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use uuid::Uuid;
+
+use super::{
+    adjacency::PetriNetAdjacency,
+    petri_net_struct::{Marking, PetriNet, TransitionID},
+    token_game::marking_key,
+};
+
+/// Error when exploring the language of a [`PetriNet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguageError {
+    /// More than the given number of markings would have to be explored
+    StateLimitExceeded(usize),
+}
+
+impl std::fmt::Display for LanguageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LanguageError::StateLimitExceeded(max) => {
+                write!(f, "State limit of {max} markings exceeded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LanguageError {}
+
+///
+/// Deterministic automaton of the language of a [`PetriNet`], built on demand
+///
+/// Each state is a set of markings closed under firing silent transitions,
+/// i.e., all markings reachable by some firing sequence with the same label sequence.
+/// Thus, distinct label sequences correspond to distinct paths in the automaton.
+///
+struct LanguageAutomaton<'b> {
+    net: &'b PetriNet,
+    adjacency: PetriNetAdjacency,
+    silent: Vec<TransitionID>,
+    labelled: Vec<(TransitionID, &'b str)>,
+    max_states: usize,
+    /// Total number of markings in all states
+    explored: usize,
+    index: HashMap<Vec<Vec<(Uuid, u64)>>, usize>,
+    states: Vec<Vec<Marking>>,
+    accepting: Vec<bool>,
+    successors: Vec<Option<BTreeMap<&'b str, usize>>>,
+}
+
+impl<'b> LanguageAutomaton<'b> {
+    /// Create the automaton; its initial state has index `0`
+    fn new(net: &'b PetriNet, max_states: usize) -> Result<Self, LanguageError> {
+        let adjacency = net.adjacency();
+        let transitions = adjacency.transitions().to_vec();
+        let mut automaton = Self {
+            net,
+            adjacency,
+            silent: Vec::new(),
+            labelled: Vec::new(),
+            max_states,
+            explored: 0,
+            index: HashMap::new(),
+            states: Vec::new(),
+            accepting: Vec::new(),
+            successors: Vec::new(),
+        };
+        for t in transitions {
+            match net.transitions[&t.0].label.as_deref() {
+                Some(label) => automaton.labelled.push((t, label)),
+                None => automaton.silent.push(t),
+            }
+        }
+        automaton.intern(vec![net.initial_marking_or_empty()])?;
+        Ok(automaton)
+    }
+
+    /// Get the index of the state of the silent closure of `markings` (adding it if it is new)
+    fn intern(&mut self, markings: Vec<Marking>) -> Result<usize, LanguageError> {
+        let mut seen: HashSet<Vec<(Uuid, u64)>> = HashSet::new();
+        let mut closure: Vec<Marking> = Vec::new();
+        let mut stack = markings;
+        while let Some(m) = stack.pop() {
+            if !seen.insert(marking_key(&m)) {
+                continue;
+            }
+            // The closure alone is too large (checked here to also terminate for unbounded silent behavior)
+            if seen.len() > self.max_states {
+                return Err(LanguageError::StateLimitExceeded(self.max_states));
+            }
+            for t in &self.silent {
+                if let Ok(next) = self.adjacency.fire(&m, *t) {
+                    stack.push(next);
+                }
+            }
+            closure.push(m);
+        }
+        let mut key: Vec<Vec<(Uuid, u64)>> = seen.into_iter().collect();
+        key.sort();
+        if let Some(s) = self.index.get(&key) {
+            return Ok(*s);
+        }
+        if self.explored + closure.len() > self.max_states {
+            return Err(LanguageError::StateLimitExceeded(self.max_states));
+        }
+        self.explored += closure.len();
+        self.accepting.push(
+            closure
+                .iter()
+                .any(|m| self.net.is_accepting_marking_in(&self.adjacency, m)),
+        );
+        self.states.push(closure);
+        self.successors.push(None);
+        self.index.insert(key, self.states.len() - 1);
+        Ok(self.states.len() - 1)
+    }
+
+    /// Get the successor states of state `s` for each label
+    fn successors(&mut self, s: usize) -> Result<BTreeMap<&'b str, usize>, LanguageError> {
+        if let Some(succ) = &self.successors[s] {
+            return Ok(succ.clone());
+        }
+        let mut by_label: BTreeMap<&'b str, Vec<Marking>> = BTreeMap::new();
+        for m in &self.states[s] {
+            for (t, label) in &self.labelled {
+                if let Ok(next) = self.adjacency.fire(m, *t) {
+                    by_label.entry(*label).or_default().push(next);
+                }
+            }
+        }
+        let mut succ = BTreeMap::new();
+        for (label, markings) in by_label {
+            succ.insert(label, self.intern(markings)?);
+        }
+        self.successors[s] = Some(succ.clone());
+        Ok(succ)
+    }
+
+    fn enumerate(
+        &mut self,
+        s: usize,
+        max_length: usize,
+        prefix: &mut Vec<String>,
+        res: &mut BTreeSet<Vec<String>>,
+    ) -> Result<(), LanguageError> {
+        if self.accepting[s] {
+            res.insert(prefix.clone());
+        }
+        if prefix.len() >= max_length {
+            return Ok(());
+        }
+        for (label, next) in self.successors(s)? {
+            prefix.push(label.to_string());
+            self.enumerate(next, max_length, prefix, res)?;
+            prefix.pop();
+        }
+        Ok(())
+    }
+}
+
+impl PetriNet {
+    ///
+    /// Check if the net accepts the sequence of labels `labels`
+    ///
+    /// A sequence is accepted if there is a firing sequence from the initial marking to an accepting marking
+    /// (see [`PetriNet::is_accepting_marking`]) whose labelled transitions produce exactly `labels`.
+    /// Any number of silent transitions can fire in between.
+    ///
+    /// Fails if more than `max_states` markings have to be explored
+    /// (see [`DEFAULT_MAX_STATES`](super::state_space::DEFAULT_MAX_STATES) for a reasonable default).
+    ///
+    pub fn accepts_trace<S: AsRef<str>>(
+        &self,
+        labels: &[S],
+        max_states: usize,
+    ) -> Result<bool, LanguageError> {
+        let mut automaton = LanguageAutomaton::new(self, max_states)?;
+        let mut s = 0;
+        for label in labels {
+            match automaton.successors(s)?.get(label.as_ref()) {
+                Some(next) => s = *next,
+                None => return Ok(false),
+            }
+        }
+        Ok(automaton.accepting[s])
+    }
+
+    ///
+    /// Get all traces (i.e., label sequences) with at most `max_length` labels accepted by the net
+    ///
+    /// See [`PetriNet::accepts_trace`] for when a trace is accepted.
+    /// Fails if more than `max_states` markings have to be explored.
+    ///
+    pub fn bounded_language(
+        &self,
+        max_length: usize,
+        max_states: usize,
+    ) -> Result<BTreeSet<Vec<String>>, LanguageError> {
+        let mut automaton = LanguageAutomaton::new(self, max_states)?;
+        let mut res = BTreeSet::new();
+        automaton.enumerate(0, max_length, &mut Vec::new(), &mut res)?;
+        Ok(res)
+    }
+
+    ///
+    /// Get all pairs of labels which directly follow each other in a firing sequence producing at most `max_length` labels
+    ///
+    /// Firing sequences start in the initial marking, but do not have to end in an accepting marking.
+    /// Silent transitions in between two labels are ignored.
+    /// Fails if more than `max_states` markings have to be explored.
+    ///
+    pub(crate) fn directly_follows_relations(
+        &self,
+        max_length: usize,
+        max_states: usize,
+    ) -> Result<BTreeSet<(String, String)>, LanguageError> {
+        let mut automaton = LanguageAutomaton::new(self, max_states)?;
+        let mut res = BTreeSet::new();
+        // Breadth-first, so that each state is first visited with the minimal number of labels
+        let mut visited: HashSet<usize> = HashSet::from([0]);
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::from([(0, 0)]);
+        while let Some((s, length)) = queue.pop_front() {
+            // Pairs `(a, b)` after reaching `s` require two more labels
+            if length + 2 > max_length {
+                continue;
+            }
+            for (a, next) in automaton.successors(s)? {
+                for b in automaton.successors(next)?.into_keys() {
+                    res.insert((a.to_string(), b.to_string()));
+                }
+                if visited.insert(next) {
+                    queue.push_back((next, length + 1));
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    ///
+    /// Count the distinct traces accepted by the net for each length from `0` to `max_length`
+    ///
+    /// The entry at index `k` is the number of accepted traces with `k` labels (see [`PetriNet::bounded_language`]).
+    /// The traces are counted without enumerating them, so this is also feasible for large languages.
+    /// Counts exceeding [`u64::MAX`] are capped at [`u64::MAX`].
+    /// Fails if more than `max_states` markings have to be explored.
+    ///
+    pub fn trace_counts_per_length(
+        &self,
+        max_length: usize,
+        max_states: usize,
+    ) -> Result<Vec<u64>, LanguageError> {
+        let mut automaton = LanguageAutomaton::new(self, max_states)?;
+        let mut counts = Vec::with_capacity(max_length + 1);
+        // Number of distinct label sequences of the current length leading to each state
+        let mut current: BTreeMap<usize, u64> = BTreeMap::from([(0, 1)]);
+        for length in 0..=max_length {
+            counts.push(
+                current
+                    .iter()
+                    .filter(|(s, _)| automaton.accepting[**s])
+                    .fold(0u64, |acc, (_, n)| acc.saturating_add(*n)),
+            );
+            if length == max_length {
+                break;
+            }
+            let mut next: BTreeMap<usize, u64> = BTreeMap::new();
+            for (s, n) in current {
+                for succ in automaton.successors(s)?.into_values() {
+                    let entry = next.entry(succ).or_default();
+                    *entry = entry.saturating_add(n);
+                }
+            }
+            current = next;
+        }
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::{petri_net_struct::ArcType, token_game::DEFAULT_MAX_REPLAY_STATES};
+
+    /// `a`, then `b` and `c` in parallel, with an optional silent redo of `a` (looping back) before finishing with `d`
+    fn net() -> PetriNet {
+        let mut net = PetriNet::new();
+        let [p0, p1, p2, p3, p4, p5, p6] = [(); 7].map(|_| net.add_place(None));
+        let a = net.add_transition(Some("a".to_string()), None);
+        let split = net.add_transition(None, None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        let c = net.add_transition(Some("c".to_string()), None);
+        let join = net.add_transition(None, None);
+        let redo = net.add_transition(None, None);
+        let d = net.add_transition(Some("d".to_string()), None);
+        for (from, t, to) in [
+            (vec![p0], a, vec![p1]),
+            (vec![p1], split, vec![p2, p3]),
+            (vec![p2], b, vec![p4]),
+            (vec![p3], c, vec![p5]),
+            (vec![p4, p5], join, vec![p6]),
+            (vec![p6], redo, vec![p0]),
+            (vec![p6], d, vec![]),
+        ] {
+            for p in from {
+                net.add_arc(ArcType::place_to_transition(p, t), None);
+            }
+            for p in to {
+                net.add_arc(ArcType::transition_to_place(t, p), None);
+            }
+        }
+        net.initial_marking = Some(Marking::from([(p0, 1)]));
+        net.final_markings = Some(vec![Marking::new()]);
+        net
+    }
+
+    #[test]
+    fn test_accepts_trace() {
+        let net = net();
+        assert!(net.accepts_trace(&["a", "b", "c", "d"], 1_000).unwrap());
+        assert!(net.accepts_trace(&["a", "c", "b", "d"], 1_000).unwrap());
+        assert!(net
+            .accepts_trace(&["a", "c", "b", "a", "b", "c", "d"], 1_000)
+            .unwrap());
+        assert!(!net.accepts_trace(&["a", "b", "c"], 1_000).unwrap());
+        assert!(!net.accepts_trace(&["a", "b", "d"], 1_000).unwrap());
+        assert!(!net.accepts_trace::<&str>(&[], 1_000).unwrap());
+        assert_eq!(
+            net.accepts_trace(&["a"], 2),
+            Err(LanguageError::StateLimitExceeded(2))
+        );
+    }
+
+    #[test]
+    fn test_accepts_trace_agrees_with_replay() {
+        let mut without_final_markings = net();
+        without_final_markings.final_markings = None;
+        for net in [net(), without_final_markings] {
+            for trace in [
+                vec!["a", "b", "c", "d"],
+                vec!["a", "c", "b", "a", "b", "c", "d"],
+                vec!["a", "b", "c"],
+                vec!["a", "b", "d"],
+                vec![],
+            ] {
+                let replayed = net
+                    .replay_labels(&trace, DEFAULT_MAX_REPLAY_STATES)
+                    .is_ok_and(|r| r.reached_final_marking);
+                assert_eq!(net.accepts_trace(&trace, 1_000), Ok(replayed));
+            }
+        }
+    }
+
+    #[test]
+    fn test_state_limit_counts_distinct_markings() {
+        // `a` and `b` alternate between two markings, so revisiting the initial marking does not count again
+        let mut net = PetriNet::new();
+        let [p0, p1] = [(); 2].map(|_| net.add_place(None));
+        let a = net.add_transition(Some("a".to_string()), None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p0, a), None);
+        net.add_arc(ArcType::transition_to_place(a, p1), None);
+        net.add_arc(ArcType::place_to_transition(p1, b), None);
+        net.add_arc(ArcType::transition_to_place(b, p0), None);
+        net.initial_marking = Some(Marking::from([(p0, 1)]));
+        net.final_markings = Some(vec![Marking::from([(p0, 1)])]);
+        assert_eq!(net.accepts_trace(&["a", "b", "a", "b"], 2), Ok(true));
+        assert_eq!(net.accepts_trace(&["a", "b", "a"], 2), Ok(false));
+    }
+
+    #[test]
+    fn test_bounded_language_and_counts() {
+        let net = net();
+        let language = net.bounded_language(7, 1_000).unwrap();
+        let expected: BTreeSet<Vec<String>> =
+            ["abcd", "acbd", "abcabcd", "abcacbd", "acbabcd", "acbacbd"]
+                .iter()
+                .map(|t| t.chars().map(|c| c.to_string()).collect())
+                .collect();
+        assert_eq!(language, expected);
+        assert_eq!(
+            net.trace_counts_per_length(10, 1_000).unwrap(),
+            vec![0, 0, 0, 0, 2, 0, 0, 4, 0, 0, 8]
+        );
+        assert_eq!(net.trace_counts_per_length(70, 1_000).unwrap()[70], 1 << 23);
+    }
+}
@@ -2,11 +2,21 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use super::data_petri_net::{DataVariable, Expression};
 use super::import_pnml::PNMLParseError;
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// Place in a Petri net
 pub struct Place {
     id: Uuid,
+    /// Place name (e.g., the `<name>` of the place in PNML)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Original ID of the place (e.g., the `id` attribute in PNML)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_id: Option<String>,
+    /// Layout information
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphics: Option<NodeGraphics>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -15,6 +25,43 @@ pub struct Transition {
     /// Transition label (None if this transition is _invisible_)
     pub label: Option<String>,
     id: Uuid,
+    /// Original ID of the transition (e.g., the `id` attribute in PNML)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_id: Option<String>,
+    /// Layout information
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphics: Option<NodeGraphics>,
+    /// Guard over the variables of the net (data Petri nets)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guard: Option<Expression>,
+    /// Names of the variables written when firing this transition (data Petri nets)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub write_variables: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+/// Position or size in a (2D) layout
+pub struct Position {
+    /// X coordinate (or width)
+    pub x: f64,
+    /// Y coordinate (or height)
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+/// Layout information of a place or transition (e.g., from PNML `<graphics>`)
+pub struct NodeGraphics {
+    /// Position (of the center) of the node
+    pub position: Option<Position>,
+    /// Size of the node
+    pub dimension: Option<Position>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+/// Layout information of an arc (e.g., from PNML `<graphics>`)
+pub struct ArcGraphics {
+    /// Intermediate (bend) points of the arc
+    pub positions: Vec<Position>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -37,6 +84,15 @@ pub enum ArcType {
     PlaceTransition(Uuid, Uuid),
     /// From Transition to Place
     TransitionPlace(Uuid, Uuid),
+    /// Inhibitor arc from Place to Transition
+    ///
+    /// The transition is only enabled if the place contains fewer tokens than the arc weight (i.e., no tokens for weight 1).
+    /// No tokens are consumed.
+    Inhibitor(Uuid, Uuid),
+    /// Reset arc from Place to Transition
+    ///
+    /// Firing the transition removes all tokens from the place. It does not influence whether the transition is enabled.
+    Reset(Uuid, Uuid),
 }
 
 impl ArcType {
@@ -48,13 +104,32 @@ impl ArcType {
     pub fn transition_to_place(from: TransitionID, to: PlaceID) -> ArcType {
         ArcType::TransitionPlace(from.0, to.0)
     }
+    /// Create new inhibitor arc from place to transition
+    pub fn inhibitor(from: PlaceID, to: TransitionID) -> ArcType {
+        ArcType::Inhibitor(from.0, to.0)
+    }
+    /// Create new reset arc from place to transition
+    pub fn reset(from: PlaceID, to: TransitionID) -> ArcType {
+        ArcType::Reset(from.0, to.0)
+    }
     /// Checks if a given node ID is start or end of this arc
     pub fn contains(&self, id: &Uuid) -> bool {
-        match self {
-            ArcType::PlaceTransition(from, to) => from == id || to == id,
-            ArcType::TransitionPlace(from, to) => from == id || to == id,
+        let (from, to) = self.endpoints();
+        from == *id || to == *id
+    }
+    /// Get the source and target node IDs of this arc
+    pub fn endpoints(&self) -> (Uuid, Uuid) {
+        match *self {
+            ArcType::PlaceTransition(from, to)
+            | ArcType::TransitionPlace(from, to)
+            | ArcType::Inhibitor(from, to)
+            | ArcType::Reset(from, to) => (from, to),
         }
     }
+    /// Checks if this is an inhibitor or reset arc
+    pub fn is_inhibitor_or_reset(&self) -> bool {
+        matches!(self, ArcType::Inhibitor(_, _) | ArcType::Reset(_, _))
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -66,9 +141,15 @@ pub struct Arc {
     pub from_to: ArcType,
     /// Weight (i.e., how many tokens this arc moves)
     pub weight: u32,
+    /// Original ID of the arc (e.g., the `id` attribute in PNML)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_id: Option<String>,
+    /// Layout information
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphics: Option<ArcGraphics>,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Eq, Hash)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Eq, Hash, PartialOrd, Ord)]
 /// Place ID
 pub struct PlaceID(pub Uuid);
 impl PlaceID {
@@ -83,7 +164,7 @@ impl From<&Place> for PlaceID {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Eq, Hash, PartialOrd, Ord)]
 /// Transition ID
 pub struct TransitionID(pub Uuid);
 impl From<&Transition> for TransitionID {
@@ -117,6 +198,9 @@ pub struct PetriNet {
     pub initial_marking: Option<Marking>,
     /// Final markings (any of them are accepted as a final marking)
     pub final_markings: Option<Vec<Marking>>,
+    /// Case variables (data Petri nets)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<DataVariable>,
 }
 
 impl Default for PetriNet {
@@ -133,6 +217,7 @@ impl PetriNet {
             arcs: Vec::new(),
             initial_marking: None,
             final_markings: None,
+            variables: Vec::new(),
         }
     }
     /// Serialize to JSON string
@@ -144,7 +229,12 @@ impl PetriNet {
     /// If no ID is passed, a new UUID will be generated
     pub fn add_place(&mut self, place_id: Option<Uuid>) -> PlaceID {
         let place_id = place_id.unwrap_or(Uuid::new_v4());
-        let place = Place { id: place_id };
+        let place = Place {
+            id: place_id,
+            name: None,
+            original_id: None,
+            graphics: None,
+        };
         self.places.insert(place_id, place);
         PlaceID(place_id)
     }
@@ -161,6 +251,10 @@ impl PetriNet {
         let transition = Transition {
             id: transition_id,
             label,
+            original_id: None,
+            graphics: None,
+            guard: None,
+            write_variables: Vec::new(),
         };
         self.transitions.insert(transition_id, transition);
         TransitionID(transition_id)
@@ -170,6 +264,8 @@ impl PetriNet {
         self.arcs.push(Arc {
             from_to,
             weight: weight.unwrap_or(1),
+            original_id: None,
+            graphics: None,
         });
     }
 
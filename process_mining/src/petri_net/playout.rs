@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::event_log::{
+    constants::{TIMESTAMP_NAME, TRACE_ID_NAME},
+    event_log_struct::EventLogExtension,
+    AttributeValue, Event, EventLog, Trace, XESEditableAttribute,
+};
+
+use super::{
+    language::LanguageError,
+    petri_net_struct::{PetriNet, TransitionID},
+};
+
+/// How transitions are chosen during playout (see [`PetriNet::playout`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PlayoutMode {
+    /// Generate `num_traces` runs, choosing uniformly at random between the enabled transitions
+    Random {
+        /// Number of traces to generate
+        num_traces: usize,
+    },
+    /// Generate `num_traces` runs, choosing between the enabled transitions with a probability proportional to their weight
+    ///
+    /// Transitions without (positive) weight never fire.
+    /// The weights of a [`StochasticLabelledPetriNet`](super::stochastic::StochasticLabelledPetriNet) can be used directly.
+    Weighted {
+        /// Number of traces to generate
+        num_traces: usize,
+        /// Weights of transitions
+        weights: HashMap<TransitionID, f64>,
+    },
+    /// Generate all distinct traces (at most `max_traces`) of the bounded language of the net
+    ///
+    /// The traces are those of [`PetriNet::bounded_language`] with [`PlayoutOptions::max_trace_length`] as the maximal number of labels,
+    /// each generated once (sorted by activity sequence).
+    /// There is no separate loop bound: loops are unrolled as often as their labels fit into the maximal trace length.
+    Exhaustive {
+        /// Maximal number of traces to generate
+        max_traces: usize,
+        /// Maximal number of explored markings
+        /// (see [`DEFAULT_MAX_STATES`](super::state_space::DEFAULT_MAX_STATES) for a reasonable default)
+        max_states: usize,
+    },
+}
+
+/// Distribution of durations (all values in milliseconds)
+///
+/// Sampled durations are never negative.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TimeDistribution {
+    /// Always the same duration
+    Constant(f64),
+    /// Uniform distribution between `min` and `max`
+    Uniform {
+        /// Minimal duration
+        min: f64,
+        /// Maximal duration
+        max: f64,
+    },
+    /// Exponential distribution with the given mean
+    Exponential {
+        /// Mean duration
+        mean: f64,
+    },
+    /// Normal distribution (truncated at `0`)
+    Normal {
+        /// Mean duration
+        mean: f64,
+        /// Standard deviation
+        std_dev: f64,
+    },
+}
+
+impl TimeDistribution {
+    fn sample(&self, rng: &mut PlayoutRng) -> f64 {
+        let duration = match *self {
+            TimeDistribution::Constant(d) => d,
+            TimeDistribution::Uniform { min, max } => min + (max - min) * rng.next_f64(),
+            TimeDistribution::Exponential { mean } => -mean * (1.0 - rng.next_f64()).ln(),
+            TimeDistribution::Normal { mean, std_dev } => {
+                // Box-Muller transform
+                let (u1, u2) = (1.0 - rng.next_f64(), rng.next_f64());
+                mean + std_dev * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+            }
+        };
+        duration.max(0.0)
+    }
+}
+
+/// Options for generating an [`EventLog`] from a [`PetriNet`] (see [`PetriNet::playout`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayoutOptions {
+    /// How transitions are chosen (Default: 100 random traces)
+    pub mode: PlayoutMode,
+    /// Maximal length of a run (Default: `100`)
+    ///
+    /// For [`PlayoutMode::Random`] and [`PlayoutMode::Weighted`], this bounds the number of fired transitions (including silent transitions)
+    /// and runs exceeding it are discarded.
+    /// For [`PlayoutMode::Exhaustive`], this bounds the number of events (i.e., labels) per trace.
+    pub max_trace_length: usize,
+    /// Maximal number of attempted runs per requested trace (Default: `100`)
+    ///
+    /// Runs fail if they end in a marking which is not final or exceed [`PlayoutOptions::max_trace_length`].
+    /// Only used for [`PlayoutMode::Random`] and [`PlayoutMode::Weighted`].
+    pub max_attempts_per_trace: usize,
+    /// Start time of the first case (Default: `2024-01-01T00:00:00+00:00`)
+    pub start_time: DateTime<FixedOffset>,
+    /// Time between the starts of two consecutive cases (Default: exponential with a mean of one hour)
+    pub case_arrival: TimeDistribution,
+    /// Time between two consecutive events of a case (Default: exponential with a mean of 30 minutes)
+    pub inter_event_time: TimeDistribution,
+    /// Activity-specific time between an event with this activity and the previous event of the case
+    ///
+    /// Overrides [`PlayoutOptions::inter_event_time`] for the given activities.
+    pub activity_durations: HashMap<String, TimeDistribution>,
+    /// Prefix of generated case IDs, which are numbered starting at `1` (Default: `case_`)
+    pub case_id_prefix: String,
+    /// Seed of the random number generator (Default: `42`)
+    ///
+    /// The same seed (and options) generate the same [`EventLog`].
+    pub seed: u64,
+}
+
+impl Default for PlayoutOptions {
+    fn default() -> Self {
+        Self {
+            mode: PlayoutMode::Random { num_traces: 100 },
+            max_trace_length: 100,
+            max_attempts_per_trace: 100,
+            start_time: DateTime::parse_from_rfc3339("2024-01-01T00:00:00+00:00").unwrap(),
+            case_arrival: TimeDistribution::Exponential {
+                mean: 60.0 * 60.0 * 1000.0,
+            },
+            inter_event_time: TimeDistribution::Exponential {
+                mean: 30.0 * 60.0 * 1000.0,
+            },
+            activity_durations: HashMap::new(),
+            case_id_prefix: "case_".to_string(),
+            seed: 42,
+        }
+    }
+}
+
+/// Error during playout (see [`PetriNet::playout`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayoutError {
+    /// More than the given number of markings would have to be explored for [`PlayoutMode::Exhaustive`]
+    StateLimitExceeded(usize),
+    /// Too many runs failed to reach a final marking within [`PlayoutOptions::max_trace_length`]
+    TooManyFailedRuns {
+        /// Number of successfully generated traces
+        generated: usize,
+        /// Number of requested traces
+        requested: usize,
+    },
+}
+
+impl std::fmt::Display for PlayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlayoutError::StateLimitExceeded(max) => {
+                write!(f, "State limit of {max} markings exceeded")
+            }
+            PlayoutError::TooManyFailedRuns {
+                generated,
+                requested,
+            } => write!(
+                f,
+                "Too many runs did not reach a final marking ({generated} of {requested} traces generated)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlayoutError {}
+
+impl From<LanguageError> for PlayoutError {
+    fn from(error: LanguageError) -> Self {
+        match error {
+            LanguageError::StateLimitExceeded(max) => PlayoutError::StateLimitExceeded(max),
+        }
+    }
+}
+
+/// Small seedable pseudo-random number generator (`SplitMix64`)
+#[derive(Debug)]
+struct PlayoutRng {
+    state: u64,
+}
+
+impl PlayoutRng {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl PetriNet {
+    ///
+    /// Generate an [`EventLog`] by playing out the net from its initial marking to a final marking
+    ///
+    /// If the net has no final markings, runs end in a dead marking instead (see [`PetriNet::is_accepting_marking`]).
+    /// Runs stop as soon as a final marking is reached.
+    ///
+    /// Each fired labelled transition results in an event with the label as `concept:name` and a `time:timestamp`
+    /// (see [`PlayoutOptions`] for configuring the timing); silent transitions do not produce events.
+    /// Each case has a `concept:name` trace attribute as ID.
+    /// The resulting log can directly be exported, e.g., using [`export_xes_event_log_to_file_path`](crate::export_xes_event_log_to_file_path).
+    ///
+    pub fn playout(&self, options: &PlayoutOptions) -> Result<EventLog, PlayoutError> {
+        let mut rng = PlayoutRng {
+            state: options.seed,
+        };
+        let traces = match &options.mode {
+            PlayoutMode::Random { num_traces } => {
+                self.playout_runs(*num_traces, None, options, &mut rng)?
+            }
+            PlayoutMode::Weighted {
+                num_traces,
+                weights,
+            } => self.playout_runs(*num_traces, Some(weights), options, &mut rng)?,
+            PlayoutMode::Exhaustive {
+                max_traces,
+                max_states,
+            } => self
+                .bounded_language(options.max_trace_length, *max_states)?
+                .into_iter()
+                .take(*max_traces)
+                .collect(),
+        };
+
+        let mut case_start = options.start_time;
+        let log_traces = traces
+            .into_iter()
+            .enumerate()
+            .map(|(i, activities)| {
+                if i > 0 {
+                    case_start += duration_ms(options.case_arrival.sample(&mut rng));
+                }
+                let mut time = case_start;
+                let events = activities
+                    .into_iter()
+                    .enumerate()
+                    .map(|(j, activity)| {
+                        if j > 0 {
+                            let distribution = options
+                                .activity_durations
+                                .get(&activity)
+                                .unwrap_or(&options.inter_event_time);
+                            time += duration_ms(distribution.sample(&mut rng));
+                        }
+                        let mut event = Event::new(activity);
+                        event.attributes.add_to_attributes(
+                            TIMESTAMP_NAME.to_string(),
+                            AttributeValue::Date(time),
+                        );
+                        event
+                    })
+                    .collect();
+                let mut attributes = Vec::new();
+                attributes.add_to_attributes(
+                    TRACE_ID_NAME.to_string(),
+                    AttributeValue::String(format!("{}{}", options.case_id_prefix, i + 1)),
+                );
+                Trace { attributes, events }
+            })
+            .collect();
+
+        Ok(EventLog {
+            attributes: Vec::new(),
+            traces: log_traces,
+            extensions: Some(vec![
+                EventLogExtension {
+                    name: "Concept".to_string(),
+                    prefix: "concept".to_string(),
+                    uri: "http://www.xes-standard.org/concept.xesext".to_string(),
+                },
+                EventLogExtension {
+                    name: "Time".to_string(),
+                    prefix: "time".to_string(),
+                    uri: "http://www.xes-standard.org/time.xesext".to_string(),
+                },
+            ]),
+            classifiers: None,
+            global_trace_attrs: None,
+            global_event_attrs: None,
+        })
+    }
+
+    /// Generate `num_traces` random runs (uniformly random or weighted)
+    fn playout_runs(
+        &self,
+        num_traces: usize,
+        weights: Option<&HashMap<TransitionID, f64>>,
+        options: &PlayoutOptions,
+        rng: &mut PlayoutRng,
+    ) -> Result<Vec<Vec<String>>, PlayoutError> {
+        let adjacency = self.adjacency();
+        let initial = self.initial_marking_or_empty();
+        let mut traces = Vec::with_capacity(num_traces);
+        let mut attempts = 0;
+        while traces.len() < num_traces {
+            if attempts >= num_traces * options.max_attempts_per_trace {
+                return Err(PlayoutError::TooManyFailedRuns {
+                    generated: traces.len(),
+                    requested: num_traces,
+                });
+            }
+            attempts += 1;
+            let mut marking = initial.clone();
+            let mut activities = Vec::new();
+            for _ in 0..=options.max_trace_length {
+                if self.is_accepting_marking_in(&adjacency, &marking) {
+                    traces.push(activities);
+                    break;
+                }
+                let enabled: Vec<(TransitionID, f64)> = adjacency
+                    .enabled_transitions(&marking)
+                    .into_iter()
+                    .map(|t| {
+                        (
+                            t,
+                            weights.map_or(1.0, |w| w.get(&t).copied().unwrap_or(0.0)),
+                        )
+                    })
+                    .filter(|(_, w)| *w > 0.0)
+                    .collect();
+                let total: f64 = enabled.iter().map(|(_, w)| w).sum();
+                let mut r = rng.next_f64() * total;
+                let Some(t) = enabled
+                    .iter()
+                    .find(|(_, w)| {
+                        r -= w;
+                        r < 0.0
+                    })
+                    .or(enabled.last())
+                    .map(|(t, _)| *t)
+                else {
+                    // Dead marking which is not final
+                    break;
+                };
+                marking = adjacency
+                    .fire(&marking, t)
+                    .expect("Enabled transition can be fired");
+                if let Some(label) = &self.transitions[&t.0].label {
+                    activities.push(label.clone());
+                }
+            }
+        }
+        Ok(traces)
+    }
+}
+
+fn duration_ms(ms: f64) -> Duration {
+    Duration::milliseconds(ms.round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::{
+        petri_net_struct::{ArcType, Marking},
+        state_space::DEFAULT_MAX_STATES,
+    };
+
+    /// `a`, then a loop of `b` (with silent redo), then `c`
+    fn loop_net() -> (PetriNet, TransitionID) {
+        let mut net = PetriNet::new();
+        let p1 = net.add_place(None);
+        let p2 = net.add_place(None);
+        let p3 = net.add_place(None);
+        let p4 = net.add_place(None);
+        let a = net.add_transition(Some("a".to_string()), None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        let tau = net.add_transition(None, None);
+        let c = net.add_transition(Some("c".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p1, a), None);
+        net.add_arc(ArcType::transition_to_place(a, p2), None);
+        net.add_arc(ArcType::place_to_transition(p2, b), None);
+        net.add_arc(ArcType::transition_to_place(b, p3), None);
+        net.add_arc(ArcType::place_to_transition(p3, tau), None);
+        net.add_arc(ArcType::transition_to_place(tau, p2), None);
+        net.add_arc(ArcType::place_to_transition(p3, c), None);
+        net.add_arc(ArcType::transition_to_place(c, p4), None);
+        net.initial_marking = Some(Marking::from([(p1, 1)]));
+        net.final_markings = Some(vec![Marking::from([(p4, 1)])]);
+        (net, tau)
+    }
+
+    fn activities(log: &EventLog) -> Vec<Vec<String>> {
+        log.traces
+            .iter()
+            .map(|t| {
+                t.events
+                    .iter()
+                    .map(|e| {
+                        e.attributes
+                            .get_by_key("concept:name")
+                            .and_then(|a| a.value.try_as_string())
+                            .unwrap()
+                            .clone()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_playout_random_and_weighted() {
+        let (net, tau) = loop_net();
+        let options = PlayoutOptions {
+            mode: PlayoutMode::Random { num_traces: 50 },
+            inter_event_time: TimeDistribution::Constant(1000.0),
+            ..Default::default()
+        };
+        let log = net.playout(&options).unwrap();
+        assert_eq!(log.traces.len(), 50);
+        for (i, trace) in log.traces.iter().enumerate() {
+            assert_eq!(
+                trace.attributes.get_by_key("concept:name").unwrap().value,
+                AttributeValue::String(format!("case_{}", i + 1))
+            );
+            let times: Vec<_> = trace
+                .events
+                .iter()
+                .map(|e| {
+                    *e.attributes
+                        .get_by_key(TIMESTAMP_NAME)
+                        .unwrap()
+                        .value
+                        .try_as_date()
+                        .unwrap()
+                })
+                .collect();
+            assert!(times
+                .windows(2)
+                .all(|w| w[1] - w[0] == Duration::seconds(1)));
+        }
+        for trace in activities(&log) {
+            assert_eq!(trace.first().unwrap(), "a");
+            assert_eq!(trace.last().unwrap(), "c");
+        }
+        // Same seed, same log
+        assert_eq!(net.playout(&options).unwrap(), log);
+
+        // Never redo the loop
+        let weights = net
+            .transitions
+            .keys()
+            .map(|t| (TransitionID(*t), if *t == tau.0 { 0.0 } else { 1.0 }))
+            .collect();
+        let weighted = net
+            .playout(&PlayoutOptions {
+                mode: PlayoutMode::Weighted {
+                    num_traces: 10,
+                    weights,
+                },
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(activities(&weighted).iter().all(|t| t == &["a", "b", "c"]));
+    }
+
+    #[test]
+    fn test_playout_exhaustive() {
+        let (net, _) = loop_net();
+        let log = net
+            .playout(&PlayoutOptions {
+                mode: PlayoutMode::Exhaustive {
+                    max_traces: 100,
+                    max_states: DEFAULT_MAX_STATES,
+                },
+                max_trace_length: 5,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            activities(&log),
+            vec![
+                vec!["a", "b", "b", "b", "c"],
+                vec!["a", "b", "b", "c"],
+                vec!["a", "b", "c"],
+            ]
+        );
+
+        // Only the first traces (in order) are kept
+        let first = net
+            .playout(&PlayoutOptions {
+                mode: PlayoutMode::Exhaustive {
+                    max_traces: 2,
+                    max_states: DEFAULT_MAX_STATES,
+                },
+                max_trace_length: 5,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(activities(&first), activities(&log)[..2]);
+        assert_eq!(
+            net.playout(&PlayoutOptions {
+                mode: PlayoutMode::Exhaustive {
+                    max_traces: 100,
+                    max_states: 1,
+                },
+                ..Default::default()
+            }),
+            Err(PlayoutError::StateLimitExceeded(1))
+        );
+
+        // Exported log can be imported again
+        let mut xes = Vec::new();
+        crate::event_log::export_xes::export_xes_event_log(&mut xes, &log).unwrap();
+        let imported = crate::import_xes_slice(&xes, false, Default::default()).unwrap();
+        assert_eq!(activities(&imported), activities(&log));
+
+        // Net which cannot reach its final marking
+        let (mut net, _) = loop_net();
+        net.final_markings = Some(vec![Marking::new()]);
+        assert_eq!(
+            net.playout(&PlayoutOptions {
+                mode: PlayoutMode::Random { num_traces: 1 },
+                max_attempts_per_trace: 3,
+                ..Default::default()
+            }),
+            Err(PlayoutError::TooManyFailedRuns {
+                generated: 0,
+                requested: 1
+            })
+        );
+    }
+}
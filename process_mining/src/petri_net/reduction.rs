@@ -0,0 +1,473 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::adjacency::PetriNetAdjacency;
+use super::petri_net_struct::{ArcType, Marking, PetriNet, PlaceID, TransitionID};
+use super::token_game::marking_key;
+
+/// Language-preserving reduction rule on [`PetriNet`]s (see [`PetriNet::reduce`])
+///
+/// All rules preserve the labelled language (i.e., the sequences of transition labels leading from the initial to a final marking).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ReductionRule {
+    /// Remove silent transitions which consume and produce exactly the same tokens
+    SelfLoopTransition,
+    /// Remove duplicate transitions (with the same label and the same pre- and postset)
+    ParallelTransition,
+    /// Remove duplicate places (with the same pre- and postset and the same tokens in the initial and final markings)
+    ParallelPlace,
+    /// Remove places which are always marked and only used in self-loops
+    SelfLoopPlace,
+    /// Murata fusion of series places: Merge `p1` and `p2` connected by a silent transition `t`,
+    /// where `t` is the only output transition of `p1` and `p1` the only input place of `t`
+    FusionOfSeriesPlaces,
+    /// Murata fusion of series transitions: Merge `t1` and a silent transition `t2` connected by a place `p`,
+    /// where `t1` is the only input transition of `p`, `t2` the only output transition of `p` and `p` the only input place of `t2`
+    FusionOfSeriesTransitions,
+    /// Remove implicit places, which never are the only reason for a transition not being enabled
+    ///
+    /// Places without output transitions or with tokens in a final marking (e.g., the sink place of a workflow net) are kept.
+    /// Requires an initial marking and a (fully explored) reachability graph.
+    ImplicitPlace,
+}
+
+impl ReductionRule {
+    /// All reduction rules, in the order they are tried by [`PetriNet::reduce`]
+    pub const ALL: [ReductionRule; 7] = [
+        ReductionRule::SelfLoopTransition,
+        ReductionRule::ParallelTransition,
+        ReductionRule::ParallelPlace,
+        ReductionRule::SelfLoopPlace,
+        ReductionRule::FusionOfSeriesPlaces,
+        ReductionRule::FusionOfSeriesTransitions,
+        ReductionRule::ImplicitPlace,
+    ];
+}
+
+/// Single application of a [`ReductionRule`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReductionStep {
+    /// The applied rule
+    pub rule: ReductionRule,
+    /// Places removed (or merged into another place) by the rule
+    pub removed_places: Vec<PlaceID>,
+    /// Transitions removed (or merged into another transition) by the rule
+    pub removed_transitions: Vec<TransitionID>,
+}
+
+/// Report of all applied reductions (see [`PetriNet::reduce`])
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReductionReport {
+    /// Applied reductions, in order
+    pub steps: Vec<ReductionStep>,
+}
+
+impl ReductionReport {
+    /// Get how often each rule was applied
+    pub fn rule_counts(&self) -> HashMap<ReductionRule, usize> {
+        let mut counts = HashMap::new();
+        for step in &self.steps {
+            *counts.entry(step.rule).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Check if the given rule was applied at least once
+    pub fn fired(&self, rule: ReductionRule) -> bool {
+        self.steps.iter().any(|s| s.rule == rule)
+    }
+}
+
+/// Get a sorted copy of an adjacency row (rows are in arc order, see [`PetriNetAdjacency`])
+fn sorted<T: Ord + Copy>(row: &[T]) -> Vec<T> {
+    let mut row = row.to_vec();
+    row.sort();
+    row
+}
+
+impl PetriNet {
+    /// Apply all language-preserving [`ReductionRule`]s until none of them is applicable anymore
+    ///
+    /// `max_states` limits the size of the reachability graph used to detect implicit places (see [`ReductionRule::ImplicitPlace`]).
+    /// Returns a report of all applied rules.
+    pub fn reduce(&mut self, max_states: usize) -> ReductionReport {
+        self.reduce_with_rules(&ReductionRule::ALL, max_states)
+    }
+
+    /// Apply the given [`ReductionRule`]s until none of them is applicable anymore
+    ///
+    /// Rules are tried in the given order and, after each successful application, again from the first rule.
+    /// As it requires the reachability graph, [`ReductionRule::ImplicitPlace`] is only tried once none of the other rules is applicable.
+    /// Nets with inhibitor or reset arcs are not reduced (i.e., an empty report is returned).
+    /// See also [`PetriNet::reduce`].
+    pub fn reduce_with_rules(
+        &mut self,
+        rules: &[ReductionRule],
+        max_states: usize,
+    ) -> ReductionReport {
+        let mut report = ReductionReport::default();
+        if self
+            .arcs
+            .iter()
+            .any(|arc| arc.from_to.is_inhibitor_or_reset())
+        {
+            return report;
+        }
+        let implicit_places = rules.contains(&ReductionRule::ImplicitPlace);
+        // Reachable markings of the current net (`None` if not computed yet, `Some(None)` if the graph is incomplete)
+        let mut reachable: Option<Option<Vec<Marking>>> = None;
+        loop {
+            let adjacency = self.adjacency();
+            let mut step = rules
+                .iter()
+                .filter(|rule| **rule != ReductionRule::ImplicitPlace)
+                .find_map(|rule| self.find_reduction(*rule, &adjacency));
+            if step.is_none() && implicit_places && self.initial_marking.is_some() {
+                step = reachable
+                    .get_or_insert_with(|| {
+                        let graph = self.reachability_graph(max_states);
+                        graph.complete.then_some(graph.states)
+                    })
+                    .as_ref()
+                    .and_then(|states| self.find_implicit_place(&adjacency, states));
+            }
+            let Some(step) = step else {
+                break;
+            };
+            self.apply_reduction(&step, &adjacency);
+            if step.rule == ReductionRule::ImplicitPlace {
+                // Removing an implicit place does not change the behavior, so the reachable markings only lose that place
+                if let Some(Some(states)) = &mut reachable {
+                    for m in states.iter_mut() {
+                        m.remove(&step.removed_places[0]);
+                    }
+                }
+            } else {
+                reachable = None;
+            }
+            report.steps.push(step);
+        }
+        report
+    }
+
+    /// Add an arc from a transition to a place, or increase the weight of the existing one
+    fn add_transition_place_weight(&mut self, t: TransitionID, p: PlaceID, weight: u64) {
+        let weight = u32::try_from(weight).unwrap_or(u32::MAX);
+        let existing = self.arcs.iter_mut().find(
+            |arc| matches!(arc.from_to, ArcType::TransitionPlace(from, to) if from == t.0 && to == p.0),
+        );
+        match existing {
+            Some(arc) => arc.weight = arc.weight.saturating_add(weight),
+            None => self.add_arc(ArcType::transition_to_place(t, p), Some(weight)),
+        }
+    }
+
+    /// Number of tokens in the initial marking
+    fn initial_tokens(&self, p: &PlaceID) -> u64 {
+        self.initial_marking
+            .as_ref()
+            .and_then(|m| m.get(p).copied())
+            .unwrap_or_default()
+    }
+
+    /// Number of tokens in each final marking
+    fn final_tokens(&self, p: &PlaceID) -> Vec<u64> {
+        self.final_markings
+            .iter()
+            .flatten()
+            .map(|m| m.get(p).copied().unwrap_or_default())
+            .collect()
+    }
+
+    /// Check if the place has tokens in any final marking
+    fn marked_in_a_final_marking(&self, p: &PlaceID) -> bool {
+        self.final_tokens(p).iter().any(|tokens| *tokens > 0)
+    }
+
+    /// Find an application of the rule
+    ///
+    /// Implicit places are found separately (see [`PetriNet::find_implicit_place`]).
+    fn find_reduction(
+        &self,
+        rule: ReductionRule,
+        adjacency: &PetriNetAdjacency,
+    ) -> Option<ReductionStep> {
+        let mut places: Vec<PlaceID> = self.places.keys().map(|p| PlaceID(*p)).collect();
+        places.sort();
+        let mut transitions: Vec<TransitionID> =
+            self.transitions.keys().map(|t| TransitionID(*t)).collect();
+        transitions.sort();
+        let is_silent = |t: &TransitionID| self.transitions[&t.0].label.is_none();
+        let step = |removed_places: Vec<PlaceID>, removed_transitions: Vec<TransitionID>| {
+            Some(ReductionStep {
+                rule,
+                removed_places,
+                removed_transitions,
+            })
+        };
+        match rule {
+            ReductionRule::SelfLoopTransition => transitions
+                .into_iter()
+                .find(|t| {
+                    is_silent(t)
+                        && sorted(adjacency.weighted_preset_of_transition(*t))
+                            == sorted(adjacency.weighted_postset_of_transition(*t))
+                })
+                .and_then(|t| step(vec![], vec![t])),
+            ReductionRule::ParallelTransition => {
+                let mut seen = HashSet::new();
+                transitions
+                    .into_iter()
+                    .find(|t| {
+                        !seen.insert((
+                            self.transitions[&t.0].label.as_ref(),
+                            sorted(adjacency.weighted_preset_of_transition(*t)),
+                            sorted(adjacency.weighted_postset_of_transition(*t)),
+                        ))
+                    })
+                    .and_then(|t| step(vec![], vec![t]))
+            }
+            ReductionRule::ParallelPlace => {
+                let mut seen = HashSet::new();
+                places
+                    .into_iter()
+                    .find(|p| {
+                        !seen.insert((
+                            sorted(adjacency.weighted_preset_of_place(*p)),
+                            sorted(adjacency.weighted_postset_of_place(*p)),
+                            self.initial_tokens(p),
+                            self.final_tokens(p),
+                        ))
+                    })
+                    .and_then(|p| step(vec![p], vec![]))
+            }
+            ReductionRule::SelfLoopPlace => places
+                .into_iter()
+                .find(|p| {
+                    let tokens = self.initial_tokens(p);
+                    let post = sorted(adjacency.weighted_postset_of_place(*p));
+                    sorted(adjacency.weighted_preset_of_place(*p)) == post
+                        && post.iter().all(|(_, w)| *w <= tokens)
+                        && self.final_tokens(p).iter().all(|f| *f == tokens)
+                })
+                .and_then(|p| step(vec![p], vec![])),
+            ReductionRule::FusionOfSeriesPlaces => transitions
+                .into_iter()
+                .find_map(|t| {
+                    if !is_silent(&t) {
+                        return None;
+                    }
+                    let (p1, p2) = match (
+                        adjacency.weighted_preset_of_transition(t),
+                        adjacency.weighted_postset_of_transition(t),
+                    ) {
+                        ([(p1, 1)], [(p2, 1)]) if p1 != p2 => (*p1, *p2),
+                        _ => return None,
+                    };
+                    (adjacency.weighted_postset_of_place(p1) == [(t, 1)]
+                        && !self.marked_in_a_final_marking(&p1))
+                    .then_some((p1, p2, t))
+                })
+                .and_then(|(p1, _, t)| step(vec![p1], vec![t])),
+            ReductionRule::FusionOfSeriesTransitions => places
+                .into_iter()
+                .find_map(|p| {
+                    let (t1, t2) = match (
+                        adjacency.weighted_preset_of_place(p),
+                        adjacency.weighted_postset_of_place(p),
+                    ) {
+                        ([(t1, 1)], [(t2, 1)]) if t1 != t2 => (*t1, *t2),
+                        _ => return None,
+                    };
+                    (is_silent(&t2)
+                        && adjacency.weighted_preset_of_transition(t2) == [(p, 1)]
+                        && self.initial_tokens(&p) == 0
+                        && !self.marked_in_a_final_marking(&p))
+                    .then_some((p, t1, t2))
+                })
+                .and_then(|(p, _, t2)| step(vec![p], vec![t2])),
+            ReductionRule::ImplicitPlace => None,
+        }
+    }
+
+    /// Find an implicit place (see [`ReductionRule::ImplicitPlace`]) given all reachable markings
+    fn find_implicit_place(
+        &self,
+        adjacency: &PetriNetAdjacency,
+        reachable: &[Marking],
+    ) -> Option<ReductionStep> {
+        let mut places: Vec<PlaceID> = self.places.keys().map(|p| PlaceID(*p)).collect();
+        places.sort();
+        places
+            .into_iter()
+            .find(|p| self.is_implicit_place(p, reachable, adjacency))
+            .map(|p| ReductionStep {
+                rule: ReductionRule::ImplicitPlace,
+                removed_places: vec![p],
+                removed_transitions: vec![],
+            })
+    }
+
+    /// Check if a place is implicit in the given reachable markings
+    ///
+    /// A place is implicit if it never is the only reason for a transition not being enabled
+    /// and if it does not influence whether a reachable marking is final.
+    fn is_implicit_place(
+        &self,
+        p: &PlaceID,
+        reachable: &[Marking],
+        adjacency: &PetriNetAdjacency,
+    ) -> bool {
+        if adjacency.weighted_postset_of_place(*p).is_empty() || self.marked_in_a_final_marking(p) {
+            return false;
+        }
+        let tokens = |m: &Marking, q: &PlaceID| m.get(q).copied().unwrap_or_default();
+        let never_blocks = adjacency
+            .weighted_postset_of_place(*p)
+            .iter()
+            .all(|(t, w)| {
+                reachable.iter().all(|m| {
+                    tokens(m, p) >= *w
+                        || !adjacency
+                            .weighted_preset_of_transition(*t)
+                            .iter()
+                            .all(|(q, w_q)| q == p || tokens(m, q) >= *w_q)
+                })
+            });
+        if !never_blocks {
+            return false;
+        }
+        let project = |m: &Marking| {
+            let mut m = m.clone();
+            m.remove(p);
+            marking_key(&m)
+        };
+        let projected_finals: HashSet<Vec<(Uuid, u64)>> =
+            self.final_markings.iter().flatten().map(project).collect();
+        reachable
+            .iter()
+            .all(|m| self.is_final_marking(m) == projected_finals.contains(&project(m)))
+    }
+
+    /// Apply a reduction found by [`PetriNet::find_reduction`]
+    fn apply_reduction(&mut self, step: &ReductionStep, adjacency: &PetriNetAdjacency) {
+        match step.rule {
+            ReductionRule::FusionOfSeriesPlaces => {
+                let t = step.removed_transitions[0];
+                let p1 = step.removed_places[0];
+                let p2 = adjacency.weighted_postset_of_transition(t)[0].0;
+                // Redirect all input arcs of `p1` to `p2` (`t` is its only output) and move its initial tokens
+                for (t_in, w) in adjacency.weighted_preset_of_place(p1) {
+                    self.add_transition_place_weight(*t_in, p2, *w);
+                }
+                self.remove_transition(&t.0);
+                let tokens = self.initial_tokens(&p1);
+                if tokens > 0 {
+                    *self
+                        .initial_marking
+                        .as_mut()
+                        .unwrap()
+                        .entry(p2)
+                        .or_default() += tokens;
+                }
+                self.remove_place(&p1.0);
+            }
+            ReductionRule::FusionOfSeriesTransitions => {
+                let p = step.removed_places[0];
+                let t2 = step.removed_transitions[0];
+                let t1 = adjacency.weighted_preset_of_place(p)[0].0;
+                // `t1` directly produces the output tokens of `t2`
+                for (q, w) in adjacency.weighted_postset_of_transition(t2) {
+                    self.add_transition_place_weight(t1, *q, *w);
+                }
+                self.remove_place(&p.0);
+                self.remove_transition(&t2.0);
+            }
+            _ => {
+                for p in &step.removed_places {
+                    self.remove_place(&p.0);
+                }
+                for t in &step.removed_transitions {
+                    self.remove_transition(&t.0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::state_space::DEFAULT_MAX_STATES;
+    use crate::utils::test_utils::build_petri_net;
+
+    fn accepts(net: &PetriNet, labels: &[&str]) -> bool {
+        net.replay_labels(labels, DEFAULT_MAX_STATES)
+            .is_ok_and(|r| r.reached_final_marking)
+    }
+
+    #[test]
+    fn test_silent_transition_reduction() {
+        // a, then (silent) b or silent skip, then c, then silent end
+        let (mut net, p, _) = build_petri_net(
+            5,
+            &[
+                (vec![0], Some("a"), vec![1]),
+                (vec![1], Some("b"), vec![2]),
+                (vec![1], None, vec![2]),
+                (vec![2], Some("c"), vec![3]),
+                (vec![3], None, vec![4]),
+                (vec![4], None, vec![4]),
+            ],
+        );
+        net.initial_marking = Some(Marking::from([(p[0], 1)]));
+        net.final_markings = Some(vec![Marking::from([(p[4], 1)])]);
+
+        let report = net.reduce(DEFAULT_MAX_STATES);
+        assert!(report.fired(ReductionRule::SelfLoopTransition));
+        assert!(report.fired(ReductionRule::FusionOfSeriesPlaces));
+        // The silent end transition and one of its places are removed
+        assert_eq!(net.places.len(), 4);
+        assert_eq!(net.transitions.len(), 4);
+        for trace in [&["a", "b", "c"][..], &["a", "c"]] {
+            assert!(accepts(&net, trace));
+        }
+        assert!(!accepts(&net, &["a", "b"]));
+    }
+
+    #[test]
+    fn test_place_reduction() {
+        // Places 0 to 3 form a sequence, 4 is parallel to 1, 5 is a marked self-loop place on `b`,
+        // 6 is an implicit place from `a` to `c`, and 7 is an implicit place initially marked for `c`
+        let (mut net, p, _) = build_petri_net(
+            8,
+            &[
+                (vec![0], Some("a"), vec![1, 4, 6]),
+                (vec![1, 4, 5], Some("b"), vec![2, 5]),
+                (vec![2, 6, 7], Some("c"), vec![3]),
+            ],
+        );
+        let (self_loop, implicit, implicit_initial) = (p[5], p[6], p[7]);
+        net.initial_marking = Some(Marking::from([
+            (p[0], 1),
+            (self_loop, 1),
+            (implicit_initial, 1),
+        ]));
+        net.final_markings = Some(vec![Marking::from([(p[3], 1), (self_loop, 1)])]);
+
+        let report = net.reduce(DEFAULT_MAX_STATES);
+        let counts = report.rule_counts();
+        assert_eq!(counts.get(&ReductionRule::ParallelPlace), Some(&1));
+        assert_eq!(counts.get(&ReductionRule::SelfLoopPlace), Some(&1));
+        assert_eq!(counts.get(&ReductionRule::ImplicitPlace), Some(&2));
+        assert!(!net.places.contains_key(&self_loop.0));
+        assert!(!net.places.contains_key(&implicit.0));
+        assert!(!net.places.contains_key(&implicit_initial.0));
+        assert_eq!(net.places.len(), 4);
+        assert_eq!(net.final_markings.as_ref().unwrap()[0].len(), 1);
+        assert!(accepts(&net, &["a", "b", "c"]));
+        assert!(!accepts(&net, &["a", "c"]));
+    }
+}
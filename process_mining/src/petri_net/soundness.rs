@@ -0,0 +1,464 @@
+use std::collections::{HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::adjacency::PetriNetAdjacency;
+use super::petri_net_struct::{Marking, PetriNet, PlaceID, TransitionID};
+use super::state_space::{StateSpaceEdge, TokenCount};
+use super::token_game::marking_key;
+
+/// Source and sink place of a workflow net (see [`PetriNet::check_workflow_net`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowNetStructure {
+    /// Unique place without incoming arcs
+    pub source: PlaceID,
+    /// Unique place without outgoing arcs
+    pub sink: PlaceID,
+}
+
+/// Reason why a [`PetriNet`] is not a workflow net (see [`PetriNet::check_workflow_net`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkflowNetError {
+    /// No place without incoming arcs exists
+    NoSourcePlace,
+    /// Multiple places without incoming arcs exist
+    MultipleSourcePlaces(Vec<PlaceID>),
+    /// No place without outgoing arcs exists
+    NoSinkPlace,
+    /// Multiple places without outgoing arcs exist
+    MultipleSinkPlaces(Vec<PlaceID>),
+    /// Some nodes are not on a path from the source place to the sink place
+    NodesNotOnPath {
+        /// Places not on a path from source to sink
+        places: Vec<PlaceID>,
+        /// Transitions not on a path from source to sink
+        transitions: Vec<TransitionID>,
+    },
+}
+
+impl std::fmt::Display for WorkflowNetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkflowNetError::NoSourcePlace => write!(f, "No source place (without incoming arcs)"),
+            WorkflowNetError::MultipleSourcePlaces(places) => {
+                write!(f, "{} source places (without incoming arcs)", places.len())
+            }
+            WorkflowNetError::NoSinkPlace => write!(f, "No sink place (without outgoing arcs)"),
+            WorkflowNetError::MultipleSinkPlaces(places) => {
+                write!(f, "{} sink places (without outgoing arcs)", places.len())
+            }
+            WorkflowNetError::NodesNotOnPath {
+                places,
+                transitions,
+            } => write!(
+                f,
+                "{} places and {} transitions are not on a path from source to sink",
+                places.len(),
+                transitions.len()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorkflowNetError {}
+
+/// Violation of soundness (see [`PetriNet::verify_soundness`])
+///
+/// Counterexample paths start in the initial marking.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoundnessViolation {
+    /// The net is not a workflow net
+    NotAWorkflowNet(WorkflowNetError),
+    /// Some places can contain an unbounded number of tokens
+    Unbounded {
+        /// Unbounded places
+        places: Vec<PlaceID>,
+        /// Path in the coverability graph to a marking with unboundedly many tokens in `places`
+        path: Vec<StateSpaceEdge>,
+    },
+    /// A reachable marking exists, from which no final marking can be reached
+    NoOptionToComplete {
+        /// Path to a marking from which no final marking is reachable
+        path: Vec<StateSpaceEdge>,
+    },
+    /// A reachable marking exists, which strictly covers a final marking (i.e., tokens are left behind)
+    ImproperCompletion {
+        /// Path to a marking strictly covering a final marking
+        path: Vec<StateSpaceEdge>,
+    },
+    /// Some transitions can never fire
+    DeadTransitions(Vec<TransitionID>),
+}
+
+impl std::fmt::Display for SoundnessViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SoundnessViolation::NotAWorkflowNet(e) => write!(f, "Not a workflow net: {e}"),
+            SoundnessViolation::Unbounded { places, path } => write!(
+                f,
+                "{} unbounded places (after firing {} transitions)",
+                places.len(),
+                path.len()
+            ),
+            SoundnessViolation::NoOptionToComplete { path } => write!(
+                f,
+                "No final marking reachable after firing {} transitions",
+                path.len()
+            ),
+            SoundnessViolation::ImproperCompletion { path } => write!(
+                f,
+                "Improper completion after firing {} transitions",
+                path.len()
+            ),
+            SoundnessViolation::DeadTransitions(transitions) => {
+                write!(f, "{} dead transitions", transitions.len())
+            }
+        }
+    }
+}
+
+/// Result of soundness verification (see [`PetriNet::verify_soundness`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SoundnessResult {
+    /// Found violations of soundness
+    pub violations: Vec<SoundnessViolation>,
+    /// Whether the state space was fully explored
+    ///
+    /// If not, only violations which are certain for the explored part of the state space are reported
+    /// (i.e., [`SoundnessViolation::NotAWorkflowNet`], [`SoundnessViolation::Unbounded`] and [`SoundnessViolation::ImproperCompletion`]).
+    pub complete: bool,
+}
+
+impl SoundnessResult {
+    /// Check if the net is sound
+    ///
+    /// Returns `None` if no violation was found, but the state space was not fully explored.
+    pub fn is_sound(&self) -> Option<bool> {
+        if !self.violations.is_empty() {
+            Some(false)
+        } else if self.complete {
+            Some(true)
+        } else {
+            None
+        }
+    }
+}
+
+impl PetriNet {
+    /// Check if the net is a workflow net
+    ///
+    /// A workflow net has a unique source place (without incoming arcs), a unique sink place (without outgoing arcs),
+    /// and every node is on a path from the source place to the sink place.
+    /// Inhibitor and reset arcs are not considered for this structural check.
+    pub fn check_workflow_net(&self) -> Result<WorkflowNetStructure, WorkflowNetError> {
+        let adjacency = self.adjacency();
+        let unique_place = |without_arcs: fn(&PetriNetAdjacency, PlaceID) -> bool| {
+            let places: Vec<PlaceID> = adjacency
+                .places()
+                .iter()
+                .filter(|p| without_arcs(&adjacency, **p))
+                .copied()
+                .collect();
+            match places.len() {
+                0 => Err(None),
+                1 => Ok(places[0]),
+                _ => Err(Some(places)),
+            }
+        };
+        let source = unique_place(|adjacency, p| adjacency.weighted_preset_of_place(p).is_empty())
+            .map_err(|e| match e {
+                None => WorkflowNetError::NoSourcePlace,
+                Some(places) => WorkflowNetError::MultipleSourcePlaces(places),
+            })?;
+        let sink = unique_place(|adjacency, p| adjacency.weighted_postset_of_place(p).is_empty())
+            .map_err(|e| match e {
+            None => WorkflowNetError::NoSinkPlace,
+            Some(places) => WorkflowNetError::MultipleSinkPlaces(places),
+        })?;
+
+        let (places_from_source, transitions_from_source) =
+            reachable_nodes(&adjacency, source, true);
+        let (places_to_sink, transitions_to_sink) = reachable_nodes(&adjacency, sink, false);
+        let places: Vec<PlaceID> = adjacency
+            .places()
+            .iter()
+            .filter(|p| !places_from_source.contains(p) || !places_to_sink.contains(p))
+            .copied()
+            .collect();
+        let transitions: Vec<TransitionID> = adjacency
+            .transitions()
+            .iter()
+            .filter(|t| !transitions_from_source.contains(t) || !transitions_to_sink.contains(t))
+            .copied()
+            .collect();
+        if !places.is_empty() || !transitions.is_empty() {
+            return Err(WorkflowNetError::NodesNotOnPath {
+                places,
+                transitions,
+            });
+        }
+        Ok(WorkflowNetStructure { source, sink })
+    }
+
+    /// Verify the soundness of the net
+    ///
+    /// The net is first checked to be a workflow net (see [`PetriNet::check_workflow_net`]).
+    /// Starting in the initial marking, the net then is sound if
+    /// - it is bounded,
+    /// - a final marking can be reached from every reachable marking (option to complete),
+    /// - no reachable marking strictly covers a final marking (proper completion), and
+    /// - every transition can fire in some reachable marking (no dead transitions).
+    ///
+    /// The [`PetriNet::final_markings`] are used as final markings.
+    /// If no initial marking (or no final markings) are set, one token in the source place (or in the sink place) is used.
+    ///
+    /// For each violation, a counterexample path is provided where applicable.
+    /// At most `max_states` states of the coverability and reachability graph are explored (e.g., [`super::state_space::DEFAULT_MAX_STATES`]).
+    pub fn verify_soundness(&self, max_states: usize) -> SoundnessResult {
+        let structure = match self.check_workflow_net() {
+            Ok(structure) => structure,
+            Err(e) => {
+                return SoundnessResult {
+                    violations: vec![SoundnessViolation::NotAWorkflowNet(e)],
+                    complete: true,
+                }
+            }
+        };
+        let initial = self
+            .initial_marking
+            .clone()
+            .unwrap_or_else(|| Marking::from([(structure.source, 1)]));
+        let final_markings = self
+            .final_markings
+            .clone()
+            .unwrap_or_else(|| vec![Marking::from([(structure.sink, 1)])]);
+
+        // Boundedness (the reachability graph of an unbounded net is infinite)
+        let coverability_graph = self.coverability_graph_from(initial.clone(), max_states);
+        let unbounded_state = coverability_graph
+            .states
+            .iter()
+            .position(|m| m.values().any(|t| *t == TokenCount::Omega));
+        if let Some(state) = unbounded_state {
+            return SoundnessResult {
+                violations: vec![SoundnessViolation::Unbounded {
+                    places: coverability_graph.unbounded_places(),
+                    path: coverability_graph.shortest_path_to(state).unwrap(),
+                }],
+                complete: coverability_graph.complete,
+            };
+        }
+
+        let graph = self.reachability_graph_from(initial, max_states);
+        let mut violations = Vec::new();
+        let final_keys: HashSet<Vec<(Uuid, u64)>> =
+            final_markings.iter().map(marking_key).collect();
+        let is_final: Vec<bool> = graph
+            .states
+            .iter()
+            .map(|m| final_keys.contains(&marking_key(m)))
+            .collect();
+
+        // Option to complete: All states can reach a final state (backwards search from final states)
+        if graph.complete {
+            let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); graph.states.len()];
+            for e in &graph.edges {
+                predecessors[e.to].push(e.from);
+            }
+            let mut can_complete = is_final.clone();
+            let mut queue: VecDeque<usize> =
+                (0..graph.states.len()).filter(|s| is_final[*s]).collect();
+            while let Some(s) = queue.pop_front() {
+                for pred in &predecessors[s] {
+                    if !can_complete[*pred] {
+                        can_complete[*pred] = true;
+                        queue.push_back(*pred);
+                    }
+                }
+            }
+            // Prefer deadlocks as counterexamples (instead of, e.g., the initial state),
+            // otherwise report the first state (in breadth-first order) without option to complete
+            let dead_states = graph.dead_states();
+            let counterexample = dead_states
+                .into_iter()
+                .find(|s| !can_complete[*s])
+                .or_else(|| can_complete.iter().position(|c| !c));
+            if let Some(state) = counterexample {
+                violations.push(SoundnessViolation::NoOptionToComplete {
+                    path: graph.shortest_path_to(state).unwrap(),
+                });
+            }
+        }
+
+        // Proper completion: No state strictly covers a final marking
+        let improper_state = graph.states.iter().enumerate().position(|(s, m)| {
+            !is_final[s]
+                && final_markings.iter().any(|f| {
+                    f.iter()
+                        .all(|(p, tokens)| m.get(p).copied().unwrap_or_default() >= *tokens)
+                })
+        });
+        if let Some(state) = improper_state {
+            violations.push(SoundnessViolation::ImproperCompletion {
+                path: graph.shortest_path_to(state).unwrap(),
+            });
+        }
+
+        // No dead transitions
+        if graph.complete {
+            let fired: HashSet<TransitionID> = graph.edges.iter().map(|e| e.transition).collect();
+            let mut dead: Vec<TransitionID> = self
+                .transitions
+                .keys()
+                .map(|t| TransitionID(*t))
+                .filter(|t| !fired.contains(t))
+                .collect();
+            if !dead.is_empty() {
+                dead.sort();
+                violations.push(SoundnessViolation::DeadTransitions(dead));
+            }
+        }
+
+        SoundnessResult {
+            violations,
+            complete: graph.complete,
+        }
+    }
+}
+
+/// All places and transitions reachable from `start` (including `start`), following the arcs forward or backward
+///
+/// Inhibitor and reset arcs do not model a flow of tokens and thus are not followed.
+fn reachable_nodes(
+    adjacency: &PetriNetAdjacency,
+    start: PlaceID,
+    forward: bool,
+) -> (HashSet<PlaceID>, HashSet<TransitionID>) {
+    let mut places = HashSet::from([start]);
+    let mut transitions = HashSet::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(p) = queue.pop_front() {
+        let next_transitions = if forward {
+            adjacency.weighted_postset_of_place(p)
+        } else {
+            adjacency.weighted_preset_of_place(p)
+        };
+        for (t, _) in next_transitions {
+            if !transitions.insert(*t) {
+                continue;
+            }
+            let next_places = if forward {
+                adjacency.weighted_postset_of_transition(*t)
+            } else {
+                adjacency.weighted_preset_of_transition(*t)
+            };
+            for (q, _) in next_places {
+                if places.insert(*q) {
+                    queue.push_back(*q);
+                }
+            }
+        }
+    }
+    (places, transitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::petri_net_struct::ArcType;
+    use crate::petri_net::state_space::DEFAULT_MAX_STATES;
+
+    /// Sequence `a`, then a choice between `b` and `c`
+    fn sound_net() -> (PetriNet, Vec<PlaceID>) {
+        let mut net = PetriNet::new();
+        let places: Vec<PlaceID> = (0..3).map(|_| net.add_place(None)).collect();
+        let a = net.add_transition(Some("a".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(places[0], a), None);
+        net.add_arc(ArcType::transition_to_place(a, places[1]), None);
+        for label in ["b", "c"] {
+            let t = net.add_transition(Some(label.to_string()), None);
+            net.add_arc(ArcType::place_to_transition(places[1], t), None);
+            net.add_arc(ArcType::transition_to_place(t, places[2]), None);
+        }
+        net.initial_marking = Some(Marking::from([(places[0], 1)]));
+        net.final_markings = Some(vec![Marking::from([(places[2], 1)])]);
+        (net, places)
+    }
+
+    #[test]
+    fn test_workflow_net_check() {
+        let (mut net, places) = sound_net();
+        assert_eq!(
+            net.check_workflow_net(),
+            Ok(WorkflowNetStructure {
+                source: places[0],
+                sink: places[2]
+            })
+        );
+        let isolated = net.add_place(None);
+        assert!(matches!(
+            net.check_workflow_net(),
+            Err(WorkflowNetError::MultipleSourcePlaces(p)) if p.contains(&isolated)
+        ));
+    }
+
+    #[test]
+    fn test_soundness() {
+        let (mut net, places) = sound_net();
+        assert_eq!(
+            net.verify_soundness(DEFAULT_MAX_STATES).is_sound(),
+            Some(true)
+        );
+
+        // `d` produces an additional token in the sink place after `a` (improper completion),
+        // and afterwards `b`/`c` can still fire, but the final marking is never reached again
+        let d = net.add_transition(Some("d".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(places[1], d), None);
+        net.add_arc(ArcType::transition_to_place(d, places[1]), None);
+        net.add_arc(ArcType::transition_to_place(d, places[2]), None);
+        let result = net.verify_soundness(DEFAULT_MAX_STATES);
+        assert_eq!(result.is_sound(), Some(false));
+        match &result.violations[0] {
+            SoundnessViolation::Unbounded { places: p, path } => {
+                assert_eq!(p, &vec![places[2]]);
+                let labels: Vec<_> = path.iter().map(|e| e.label.as_deref()).collect();
+                assert_eq!(labels, vec![Some("a"), Some("d")]);
+            }
+            v => panic!("Unexpected violation {v}"),
+        }
+    }
+
+    #[test]
+    fn test_soundness_deadlock() {
+        // Parallel split (`a`) followed by a choice-based join (`b` or `c`), leaving a token behind
+        let mut net = PetriNet::new();
+        let places: Vec<PlaceID> = (0..4).map(|_| net.add_place(None)).collect();
+        let a = net.add_transition(Some("a".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(places[0], a), None);
+        net.add_arc(ArcType::transition_to_place(a, places[1]), None);
+        net.add_arc(ArcType::transition_to_place(a, places[2]), None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(places[1], b), None);
+        net.add_arc(ArcType::transition_to_place(b, places[3]), None);
+        let c = net.add_transition(Some("c".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(places[2], c), None);
+        net.add_arc(ArcType::transition_to_place(c, places[3]), None);
+        net.initial_marking = Some(Marking::from([(places[0], 1)]));
+        net.final_markings = Some(vec![Marking::from([(places[3], 1)])]);
+
+        let result = net.verify_soundness(DEFAULT_MAX_STATES);
+        assert!(result.complete);
+        assert_eq!(result.is_sound(), Some(false));
+        assert!(result.violations.iter().any(
+            |v| matches!(v, SoundnessViolation::NoOptionToComplete { path } if path.len() == 3)
+        ));
+        assert!(result.violations.iter().any(
+            |v| matches!(v, SoundnessViolation::ImproperCompletion { path } if path.len() == 2)
+        ));
+        assert!(!result
+            .violations
+            .iter()
+            .any(|v| matches!(v, SoundnessViolation::DeadTransitions(_))));
+    }
+}
@@ -0,0 +1,525 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::adjacency::Tokens;
+use super::petri_net_struct::{Marking, PetriNet, PlaceID, TransitionID};
+use super::token_game::marking_key;
+use crate::utils::graph_text_export::{dot_attributes, escape_dot};
+
+/// Default maximal number of states of a [`StateSpaceGraph`]
+pub const DEFAULT_MAX_STATES: usize = 100_000;
+
+/// Number of tokens in a place of an [`OmegaMarking`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TokenCount {
+    /// Finite number of tokens
+    Finite(u64),
+    /// Unbounded number of tokens (ω)
+    Omega,
+}
+
+impl Tokens for TokenCount {
+    fn finite(n: u64) -> Self {
+        TokenCount::Finite(n)
+    }
+
+    fn add(self, w: u64) -> Self {
+        match self {
+            TokenCount::Finite(n) => TokenCount::Finite(n + w),
+            TokenCount::Omega => TokenCount::Omega,
+        }
+    }
+
+    fn sub(self, w: u64) -> Self {
+        match self {
+            TokenCount::Finite(n) => TokenCount::Finite(n - w),
+            TokenCount::Omega => TokenCount::Omega,
+        }
+    }
+}
+
+impl std::fmt::Display for TokenCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenCount::Finite(n) => write!(f, "{n}"),
+            TokenCount::Omega => write!(f, "ω"),
+        }
+    }
+}
+
+/// Marking of a coverability graph, where places can contain an unbounded number of tokens (see [`TokenCount::Omega`])
+///
+/// Places without tokens are not contained.
+pub type OmegaMarking = HashMap<PlaceID, TokenCount>;
+
+/// Marking used as state of a [`StateSpaceGraph`]
+pub trait StateSpaceMarking {
+    /// Get a short human-readable description of the marking (e.g., for visualization)
+    fn marking_label(&self) -> String;
+}
+
+/// Short name of a place for visualization (first 8 characters of its ID)
+fn short_place_name(p: &PlaceID) -> String {
+    p.0.to_string().chars().take(8).collect()
+}
+
+/// Human-readable description of places and their tokens, sorted by place ID
+fn describe_tokens<T: std::fmt::Display>(mut tokens: Vec<(&PlaceID, T)>) -> String {
+    tokens.sort_by_key(|(p, _)| **p);
+    let parts: Vec<String> = tokens
+        .into_iter()
+        .map(|(p, t)| format!("{}: {t}", short_place_name(p)))
+        .collect();
+    format!("[{}]", parts.join(", "))
+}
+
+impl StateSpaceMarking for Marking {
+    fn marking_label(&self) -> String {
+        describe_tokens(self.iter().filter(|(_, t)| **t > 0).collect())
+    }
+}
+
+impl StateSpaceMarking for OmegaMarking {
+    fn marking_label(&self) -> String {
+        describe_tokens(
+            self.iter()
+                .filter(|(_, t)| **t != TokenCount::Finite(0))
+                .collect(),
+        )
+    }
+}
+
+/// Edge of a [`StateSpaceGraph`]: Firing a transition in one state, leading to another state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSpaceEdge {
+    /// Index of the source state
+    pub from: usize,
+    /// Index of the target state
+    pub to: usize,
+    /// Fired transition
+    pub transition: TransitionID,
+    /// Label of the fired transition (`None` for silent transitions)
+    pub label: Option<String>,
+}
+
+/// State space of a [`PetriNet`]: Markings as states and transitions as labelled edges
+///
+/// See [`ReachabilityGraph`] and [`CoverabilityGraph`].
+/// The state at index `0` is the initial state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSpaceGraph<M> {
+    /// All states (markings)
+    pub states: Vec<M>,
+    /// All edges between states
+    pub edges: Vec<StateSpaceEdge>,
+    /// Whether the state space was fully explored (i.e., the state limit was not reached)
+    pub complete: bool,
+}
+
+/// Reachability graph of a [`PetriNet`] (see [`PetriNet::reachability_graph`])
+pub type ReachabilityGraph = StateSpaceGraph<Marking>;
+
+/// Karp–Miller coverability graph of a [`PetriNet`] (see [`PetriNet::coverability_graph`])
+pub type CoverabilityGraph = StateSpaceGraph<OmegaMarking>;
+
+impl<M> StateSpaceGraph<M> {
+    /// Get all outgoing edges of a state
+    pub fn outgoing_edges(&self, state: usize) -> impl Iterator<Item = &StateSpaceEdge> {
+        self.edges.iter().filter(move |e| e.from == state)
+    }
+
+    /// Get all incoming edges of a state
+    pub fn incoming_edges(&self, state: usize) -> impl Iterator<Item = &StateSpaceEdge> {
+        self.edges.iter().filter(move |e| e.to == state)
+    }
+
+    /// Get the indices of all states without outgoing edges
+    pub fn dead_states(&self) -> Vec<usize> {
+        let mut has_outgoing = vec![false; self.states.len()];
+        for e in &self.edges {
+            has_outgoing[e.from] = true;
+        }
+        (0..self.states.len())
+            .filter(|s| !has_outgoing[*s])
+            .collect()
+    }
+
+    /// Get a shortest path (i.e., sequence of edges) from the initial state to the given state
+    ///
+    /// Returns `None` if the state is not reachable from the initial state.
+    pub fn shortest_path_to(&self, state: usize) -> Option<Vec<StateSpaceEdge>> {
+        if self.states.is_empty() {
+            return None;
+        }
+        let mut outgoing: Vec<Vec<&StateSpaceEdge>> = vec![Vec::new(); self.states.len()];
+        for e in &self.edges {
+            outgoing[e.from].push(e);
+        }
+        // Edge via which each state was first reached
+        let mut reached_by: Vec<Option<&StateSpaceEdge>> = vec![None; self.states.len()];
+        let mut visited = vec![false; self.states.len()];
+        visited[0] = true;
+        let mut queue = VecDeque::from([0]);
+        while let Some(s) = queue.pop_front() {
+            if s == state {
+                let mut path = Vec::new();
+                let mut current = s;
+                while let Some(e) = reached_by[current] {
+                    path.push(e.clone());
+                    current = e.from;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for e in &outgoing[s] {
+                if !visited[e.to] {
+                    visited[e.to] = true;
+                    reached_by[e.to] = Some(e);
+                    queue.push_back(e.to);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<M: Serialize> StateSpaceGraph<M> {
+    /// Serialize the graph to a JSON [`String`]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl<M: StateSpaceMarking> StateSpaceGraph<M> {
+    /// Export the graph to DOT source code (used in Graphviz)
+    ///
+    /// States are labelled with their markings (using the first 8 characters of the place IDs).
+    /// The initial state is drawn bold and edges are labelled with the transition labels (`τ` for silent transitions).
+    pub fn to_dot(&self) -> String {
+        let mut res = String::from("digraph {\n  rankdir=LR;\n");
+        for (i, m) in self.states.iter().enumerate() {
+            let mut attrs = vec![
+                ("label", format!("\"{}\"", escape_dot(&m.marking_label()))),
+                ("shape", "box".to_string()),
+                ("style", "rounded".to_string()),
+            ];
+            if i == 0 {
+                attrs[2].1 = "\"rounded,bold\"".to_string();
+            }
+            writeln!(res, "  s{i}{};", dot_attributes(&attrs)).unwrap();
+        }
+        for e in &self.edges {
+            let label = e.label.as_deref().unwrap_or("τ");
+            writeln!(
+                res,
+                "  s{} -> s{}{};",
+                e.from,
+                e.to,
+                dot_attributes(&[("label", format!("\"{}\"", escape_dot(label)))])
+            )
+            .unwrap();
+        }
+        res.push_str("}\n");
+        res
+    }
+}
+
+impl CoverabilityGraph {
+    /// Check if the net is bounded (i.e., no state contains [`TokenCount::Omega`])
+    ///
+    /// Only meaningful if the graph is [`StateSpaceGraph::complete`].
+    pub fn is_bounded(&self) -> bool {
+        self.states
+            .iter()
+            .all(|m| m.values().all(|t| *t != TokenCount::Omega))
+    }
+
+    /// Get all places which can contain an unbounded number of tokens
+    pub fn unbounded_places(&self) -> Vec<PlaceID> {
+        let mut res: Vec<PlaceID> = self
+            .states
+            .iter()
+            .flat_map(|m| m.iter().filter(|(_, t)| **t == TokenCount::Omega))
+            .map(|(p, _)| *p)
+            .collect();
+        res.sort();
+        res.dedup();
+        res
+    }
+}
+
+/// Canonical (hashable) representation of an [`OmegaMarking`]
+fn omega_marking_key(marking: &OmegaMarking) -> Vec<(Uuid, TokenCount)> {
+    let mut key: Vec<(Uuid, TokenCount)> = marking
+        .iter()
+        .filter(|(_, t)| **t != TokenCount::Finite(0))
+        .map(|(p, t)| (p.0, *t))
+        .collect();
+    key.sort();
+    key
+}
+
+/// Check if `a` is smaller or equal than `b` in every place
+fn omega_leq(a: &OmegaMarking, b: &OmegaMarking) -> bool {
+    a.iter().all(|(p, t)| {
+        let other = b.get(p).copied().unwrap_or(TokenCount::Finite(0));
+        *t <= other
+    })
+}
+
+impl PetriNet {
+    /// Construct the reachability graph of the net, starting in the initial marking
+    ///
+    /// The state space is explored breadth-first until at most `max_states` states are found (e.g., [`DEFAULT_MAX_STATES`]).
+    /// If the limit is reached, the result is not [`StateSpaceGraph::complete`] and only contains edges between the found states.
+    /// If no initial marking is set, the empty marking is used.
+    pub fn reachability_graph(&self, max_states: usize) -> ReachabilityGraph {
+        self.reachability_graph_from(self.initial_marking_or_empty(), max_states)
+    }
+
+    /// Construct the reachability graph of the net, starting in the given marking
+    ///
+    /// See [`PetriNet::reachability_graph`].
+    pub fn reachability_graph_from(
+        &self,
+        mut initial: Marking,
+        max_states: usize,
+    ) -> ReachabilityGraph {
+        let adjacency = self.adjacency();
+        initial.retain(|_, tokens| *tokens > 0);
+        let mut state_index: HashMap<Vec<(Uuid, u64)>, usize> = HashMap::new();
+        state_index.insert(marking_key(&initial), 0);
+        let mut graph = ReachabilityGraph {
+            states: vec![initial],
+            edges: Vec::new(),
+            complete: true,
+        };
+        let mut queue = VecDeque::from([0]);
+        while let Some(s) = queue.pop_front() {
+            for t in adjacency.transitions() {
+                let Ok(next) = adjacency.fire(&graph.states[s], *t) else {
+                    continue;
+                };
+                let key = marking_key(&next);
+                let to = match state_index.get(&key) {
+                    Some(to) => *to,
+                    None if graph.states.len() >= max_states => {
+                        graph.complete = false;
+                        continue;
+                    }
+                    None => {
+                        let to = graph.states.len();
+                        state_index.insert(key, to);
+                        graph.states.push(next);
+                        queue.push_back(to);
+                        to
+                    }
+                };
+                graph.edges.push(StateSpaceEdge {
+                    from: s,
+                    to,
+                    transition: *t,
+                    label: self.transitions[&t.0].label.clone(),
+                });
+            }
+        }
+        graph
+    }
+
+    /// Construct the Karp–Miller coverability graph of the net, starting in the initial marking
+    ///
+    /// Whenever a marking strictly covers a marking on the path from the initial marking to it,
+    /// all places with strictly more tokens are set to [`TokenCount::Omega`] (unbounded).
+    /// Equal markings are merged into one state, so the result is a graph (and not a tree).
+    ///
+    /// The coverability graph is always finite, but can be very large.
+    /// For nets with inhibitor or reset arcs, the Karp–Miller construction is only an approximation
+    /// (places with [`TokenCount::Omega`] tokens are considered to always inhibit transitions).
+    /// Thus, at most `max_states` states are constructed (e.g., [`DEFAULT_MAX_STATES`]).
+    /// If no initial marking is set, the empty marking is used.
+    pub fn coverability_graph(&self, max_states: usize) -> CoverabilityGraph {
+        self.coverability_graph_from(self.initial_marking_or_empty(), max_states)
+    }
+
+    /// Construct the Karp–Miller coverability graph of the net, starting in the given marking
+    ///
+    /// See [`PetriNet::coverability_graph`].
+    pub fn coverability_graph_from(
+        &self,
+        initial: Marking,
+        max_states: usize,
+    ) -> CoverabilityGraph {
+        let adjacency = self.adjacency();
+        let initial: OmegaMarking = initial
+            .into_iter()
+            .filter(|(_, t)| *t > 0)
+            .map(|(p, t)| (p, TokenCount::Finite(t)))
+            .collect();
+        let mut state_index: HashMap<Vec<(Uuid, TokenCount)>, usize> = HashMap::new();
+        state_index.insert(omega_marking_key(&initial), 0);
+        // Parent state of each state in the spanning tree (used to check for covered ancestors)
+        let mut parents: Vec<Option<usize>> = vec![None];
+        let mut graph = CoverabilityGraph {
+            states: vec![initial],
+            edges: Vec::new(),
+            complete: true,
+        };
+        let mut queue = VecDeque::from([0]);
+        while let Some(s) = queue.pop_front() {
+            for t in adjacency.transitions() {
+                // Places with ω tokens always inhibit transitions
+                let Ok(mut next) = adjacency.fire_in(&graph.states[s], *t) else {
+                    continue;
+                };
+                // Accelerate: Check all ancestors (including the current state)
+                let mut ancestor = Some(s);
+                while let Some(a) = ancestor {
+                    let a_marking = &graph.states[a];
+                    if omega_leq(a_marking, &next)
+                        && omega_marking_key(a_marking) != omega_marking_key(&next)
+                    {
+                        for (p, tokens) in next.iter_mut() {
+                            let a_tokens =
+                                a_marking.get(p).copied().unwrap_or(TokenCount::Finite(0));
+                            if a_tokens < *tokens {
+                                *tokens = TokenCount::Omega;
+                            }
+                        }
+                    }
+                    ancestor = parents[a];
+                }
+                let key = omega_marking_key(&next);
+                let to = match state_index.get(&key) {
+                    Some(to) => *to,
+                    None if graph.states.len() >= max_states => {
+                        graph.complete = false;
+                        continue;
+                    }
+                    None => {
+                        let to = graph.states.len();
+                        state_index.insert(key, to);
+                        graph.states.push(next);
+                        parents.push(Some(s));
+                        queue.push_back(to);
+                        to
+                    }
+                };
+                graph.edges.push(StateSpaceEdge {
+                    from: s,
+                    to,
+                    transition: *t,
+                    label: self.transitions[&t.0].label.clone(),
+                });
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::petri_net_struct::ArcType;
+
+    /// Net with a choice between `a` and `b` (bounded)
+    fn choice_net() -> PetriNet {
+        let mut net = PetriNet::new();
+        let p0 = net.add_place(None);
+        let p1 = net.add_place(None);
+        let a = net.add_transition(Some("a".to_string()), None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        for t in [a, b] {
+            net.add_arc(ArcType::place_to_transition(p0, t), None);
+            net.add_arc(ArcType::transition_to_place(t, p1), None);
+        }
+        net.initial_marking = Some(Marking::from([(p0, 1)]));
+        net
+    }
+
+    /// Net with a transition `gen` that produces tokens without bound, and `consume` consuming them
+    fn unbounded_net() -> (PetriNet, PlaceID) {
+        let mut net = PetriNet::new();
+        let p0 = net.add_place(None);
+        let p1 = net.add_place(None);
+        let gen = net.add_transition(Some("gen".to_string()), None);
+        let consume = net.add_transition(None, None);
+        net.add_arc(ArcType::place_to_transition(p0, gen), None);
+        net.add_arc(ArcType::transition_to_place(gen, p0), None);
+        net.add_arc(ArcType::transition_to_place(gen, p1), None);
+        net.add_arc(ArcType::place_to_transition(p1, consume), None);
+        net.initial_marking = Some(Marking::from([(p0, 1)]));
+        (net, p1)
+    }
+
+    #[test]
+    fn test_reachability_graph() {
+        let net = choice_net();
+        let rg = net.reachability_graph(DEFAULT_MAX_STATES);
+        assert!(rg.complete);
+        assert_eq!(rg.states.len(), 2);
+        assert_eq!(rg.edges.len(), 2);
+        assert_eq!(rg.outgoing_edges(0).count(), 2);
+        assert_eq!(rg.dead_states(), vec![1]);
+        let dot = rg.to_dot();
+        assert!(dot.contains("s0 -> s1 [label=\"a\"];"));
+        let json: ReachabilityGraph = serde_json::from_str(&rg.to_json()).unwrap();
+        assert_eq!(json, rg);
+    }
+
+    #[test]
+    fn test_reachability_graph_state_limit() {
+        let (net, _) = unbounded_net();
+        let rg = net.reachability_graph(10);
+        assert!(!rg.complete);
+        assert_eq!(rg.states.len(), 10);
+    }
+
+    #[test]
+    fn test_coverability_graph() {
+        let cg = choice_net().coverability_graph(DEFAULT_MAX_STATES);
+        assert!(cg.complete);
+        assert!(cg.is_bounded());
+        assert_eq!(cg.states.len(), 2);
+
+        let (net, p1) = unbounded_net();
+        let cg = net.coverability_graph(DEFAULT_MAX_STATES);
+        assert!(cg.complete);
+        assert!(!cg.is_bounded());
+        assert_eq!(cg.unbounded_places(), vec![p1]);
+        // Initial state and the state with ω tokens in p1
+        assert_eq!(cg.states.len(), 2);
+        assert_eq!(cg.states[1][&p1], TokenCount::Omega);
+        assert!(cg.to_dot().contains("ω"));
+        assert!(cg
+            .edges
+            .iter()
+            .any(|e| e.from == 1 && e.to == 1 && e.label.is_none()));
+    }
+
+    #[test]
+    fn test_zero_weight_arcs() {
+        // The arc from the (empty) place `p0` has weight 0, so it does not restrict `t`
+        let mut net = PetriNet::new();
+        let [p0, p1, p2] = [(); 3].map(|_| net.add_place(None));
+        let t = net.add_transition(Some("t".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p0, t), Some(0));
+        net.add_arc(ArcType::place_to_transition(p1, t), None);
+        net.add_arc(ArcType::transition_to_place(t, p2), None);
+        net.initial_marking = Some(Marking::from([(p1, 1)]));
+        assert!(net.fire(&net.initial_marking_or_empty(), t).is_ok());
+
+        let rg = net.reachability_graph(DEFAULT_MAX_STATES);
+        assert!(rg.complete);
+        assert_eq!(
+            rg.states,
+            vec![Marking::from([(p1, 1)]), Marking::from([(p2, 1)])]
+        );
+        assert_eq!(rg.edges.len(), 1);
+        let cg = net.coverability_graph(DEFAULT_MAX_STATES);
+        assert_eq!(cg.states.len(), 2);
+        assert_eq!(cg.edges.len(), 1);
+    }
+}
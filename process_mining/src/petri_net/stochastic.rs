@@ -0,0 +1,814 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::event_log::activity_projection::EventLogActivityProjection;
+
+use super::{
+    adjacency::PetriNetAdjacency,
+    petri_net_struct::{ArcType, Marking, PetriNet, PlaceID, TransitionID},
+    token_game::{marking_key, DEFAULT_MAX_REPLAY_STATES},
+};
+
+///
+/// Stochastic labelled Petri net: A [`PetriNet`] with a (non-negative) weight for each transition
+///
+/// In each marking, one of the enabled transitions with a positive weight is fired, with a probability proportional to its weight.
+/// A run terminates when no such transition is enabled (i.e., in a deadlock), which makes the net a stochastic language of traces.
+/// Transitions without a weight have weight `0` and never fire.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StochasticLabelledPetriNet {
+    /// Underlying Petri net
+    pub net: PetriNet,
+    /// Weights of transitions
+    pub weights: HashMap<TransitionID, f64>,
+}
+
+/// Error when parsing the `.slpn` format
+#[derive(Debug)]
+pub enum SLPNParseError {
+    /// IO error
+    IOError(std::io::Error),
+    /// The input ended before the net was fully specified
+    UnexpectedEndOfInput,
+    /// Invalid number (e.g., a token count or weight) in the given line
+    InvalidNumber {
+        /// Line number (starting at 1)
+        line: usize,
+        /// The invalid value
+        value: String,
+    },
+    /// Invalid transition specification (neither `label ...` nor `silent`) in the given line
+    InvalidTransition {
+        /// Line number (starting at 1)
+        line: usize,
+    },
+    /// Reference to a non-existing place in the given line
+    InvalidPlace {
+        /// Line number (starting at 1)
+        line: usize,
+        /// The referenced place index
+        place: usize,
+    },
+}
+
+impl std::fmt::Display for SLPNParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SLPNParseError::IOError(e) => write!(f, "IO error: {e}"),
+            SLPNParseError::UnexpectedEndOfInput => write!(f, "Unexpected end of input"),
+            SLPNParseError::InvalidNumber { line, value } => {
+                write!(f, "Invalid number {value:?} in line {line}")
+            }
+            SLPNParseError::InvalidTransition { line } => {
+                write!(f, "Invalid transition in line {line}")
+            }
+            SLPNParseError::InvalidPlace { line, place } => {
+                write!(f, "Invalid place {place} in line {line}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SLPNParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SLPNParseError::IOError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SLPNParseError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
+}
+
+/// Error when computing the probability of a trace (see [`StochasticLabelledPetriNet::trace_probability`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceProbabilityError {
+    /// More than the given number of states would have to be explored
+    StateLimitExceeded(usize),
+}
+
+impl std::fmt::Display for TraceProbabilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceProbabilityError::StateLimitExceeded(max) => {
+                write!(f, "State limit of {max} states exceeded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TraceProbabilityError {}
+
+/// Reader of the non-comment lines of a `.slpn` file (keeping track of line numbers)
+struct SLPNLines<R: BufRead> {
+    lines: std::io::Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> SLPNLines<R> {
+    fn next_line(&mut self) -> Result<String, SLPNParseError> {
+        loop {
+            let line = self
+                .lines
+                .next()
+                .ok_or(SLPNParseError::UnexpectedEndOfInput)??;
+            self.line += 1;
+            let line = line.trim();
+            if !line.starts_with('#') {
+                return Ok(line.to_string());
+            }
+        }
+    }
+
+    fn next_number<N: std::str::FromStr>(&mut self) -> Result<N, SLPNParseError> {
+        let value = self.next_line()?;
+        value.parse().map_err(|_| SLPNParseError::InvalidNumber {
+            line: self.line,
+            value,
+        })
+    }
+
+    fn next_weight(&mut self) -> Result<f64, SLPNParseError> {
+        let value = self.next_line()?;
+        match value.parse::<f64>() {
+            Ok(weight) if weight.is_finite() && weight >= 0.0 => Ok(weight),
+            _ => Err(SLPNParseError::InvalidNumber {
+                line: self.line,
+                value,
+            }),
+        }
+    }
+
+    fn next_place(&mut self, places: &[PlaceID]) -> Result<PlaceID, SLPNParseError> {
+        let index: usize = self.next_number()?;
+        places
+            .get(index)
+            .copied()
+            .ok_or(SLPNParseError::InvalidPlace {
+                line: self.line,
+                place: index,
+            })
+    }
+
+    /// Read a count followed by that many place references, merging repeated places into a single weighted arc
+    fn next_weighted_places(
+        &mut self,
+        places: &[PlaceID],
+    ) -> Result<Vec<(PlaceID, u32)>, SLPNParseError> {
+        let count: usize = self.next_number()?;
+        let mut index: HashMap<PlaceID, usize> = HashMap::new();
+        let mut weighted: Vec<(PlaceID, u32)> = Vec::new();
+        for _ in 0..count {
+            let p = self.next_place(places)?;
+            match index.get(&p) {
+                Some(i) => weighted[*i].1 += 1,
+                None => {
+                    index.insert(p, weighted.len());
+                    weighted.push((p, 1));
+                }
+            }
+        }
+        Ok(weighted)
+    }
+}
+
+/// A state during trace probability computation: A marking and the number of already replayed labels
+type TraceState = (Vec<(Uuid, u64)>, usize);
+
+/// A state during lower bound computation: A [`TraceState`] and the number of remaining silent steps
+type BoundedTraceState = (TraceState, usize);
+
+impl StochasticLabelledPetriNet {
+    /// Create a new stochastic net from a [`PetriNet`], assigning weight `1` to all transitions
+    pub fn new(net: PetriNet) -> Self {
+        let weights = net
+            .transitions
+            .keys()
+            .map(|t| (TransitionID(*t), 1.0))
+            .collect();
+        Self { net, weights }
+    }
+
+    /// Get the weight of a transition (`0` if not set)
+    pub fn weight(&self, t: TransitionID) -> f64 {
+        self.weights.get(&t).copied().unwrap_or_default()
+    }
+
+    /// Get the enabled transitions with a positive weight in `marking`, together with their firing probability
+    pub fn enabled_transition_probabilities(&self, marking: &Marking) -> Vec<(TransitionID, f64)> {
+        self.enabled_transition_probabilities_in(&self.net.adjacency(), marking)
+    }
+
+    /// Get the enabled transitions with their firing probability, using a prebuilt `adjacency` of the net
+    fn enabled_transition_probabilities_in(
+        &self,
+        adjacency: &PetriNetAdjacency,
+        marking: &Marking,
+    ) -> Vec<(TransitionID, f64)> {
+        let enabled: Vec<(TransitionID, f64)> = adjacency
+            .enabled_transitions(marking)
+            .into_iter()
+            .map(|t| (t, self.weight(t)))
+            .filter(|(_, w)| *w > 0.0)
+            .collect();
+        let total: f64 = enabled.iter().map(|(_, w)| w).sum();
+        enabled.into_iter().map(|(t, w)| (t, w / total)).collect()
+    }
+
+    ///
+    /// Estimate transition weights based on activity frequencies
+    ///
+    /// Each labelled transition gets the number of occurrences of its label in `log` as weight.
+    /// Silent transitions get the mean weight of all labelled transitions (or `1` if there are none).
+    ///
+    /// An [`EventLogActivityProjection`] can be obtained from an [`EventLog`](crate::EventLog) using `(&log).into()`.
+    ///
+    pub fn estimate_weights_frequency(net: PetriNet, log: &EventLogActivityProjection) -> Self {
+        let mut frequencies: HashMap<&str, f64> = HashMap::new();
+        for (trace, count) in &log.traces {
+            for act in trace {
+                *frequencies
+                    .entry(log.activities[*act].as_str())
+                    .or_default() += *count as f64;
+            }
+        }
+        let labelled: Vec<f64> = net
+            .transitions
+            .values()
+            .filter_map(|t| t.label.as_deref())
+            .map(|l| frequencies.get(l).copied().unwrap_or_default())
+            .collect();
+        let silent_weight = if labelled.is_empty() {
+            1.0
+        } else {
+            labelled.iter().sum::<f64>() / labelled.len() as f64
+        };
+        let weights = net
+            .transitions
+            .iter()
+            .map(|(id, t)| {
+                let weight = match t.label.as_deref() {
+                    Some(l) => frequencies.get(l).copied().unwrap_or_default(),
+                    None => silent_weight,
+                };
+                (TransitionID(*id), weight)
+            })
+            .collect();
+        Self { net, weights }
+    }
+
+    ///
+    /// Estimate transition weights by replaying the traces of `log`
+    ///
+    /// Each transition gets the number of times it fires when replaying all traces (see [`PetriNet::replay_labels`]) as weight.
+    /// Traces which cannot be replayed are ignored.
+    /// Unlike alignment-based estimation, only perfectly fitting traces contribute, and if a trace can be replayed by
+    /// several firing sequences, only one of them is counted.
+    ///
+    /// An [`EventLogActivityProjection`] can be obtained from an [`EventLog`](crate::EventLog) using `(&log).into()`.
+    ///
+    pub fn estimate_weights_replay(net: PetriNet, log: &EventLogActivityProjection) -> Self {
+        let mut weights: HashMap<TransitionID, f64> = net
+            .transitions
+            .keys()
+            .map(|t| (TransitionID(*t), 0.0))
+            .collect();
+        for (trace, count) in &log.traces {
+            let labels = log.acts_to_names(trace);
+            if let Ok(res) = net.replay_labels(&labels, DEFAULT_MAX_REPLAY_STATES) {
+                for t in res.firing_sequence {
+                    *weights.entry(t).or_default() += *count as f64;
+                }
+            }
+        }
+        Self { net, weights }
+    }
+
+    ///
+    /// Compute the probability of the stochastic net producing exactly the trace `labels`
+    ///
+    /// All (silent) runs producing `labels` and ending in a deadlock are considered.
+    /// Cycles of silent transitions are handled exactly by solving a linear equation system for each position in the trace.
+    /// Fails if more than `max_states` pairs of a marking and a position in the trace are reachable
+    /// (see also [`StochasticLabelledPetriNet::trace_probability_lower_bound`]).
+    ///
+    pub fn trace_probability<S: AsRef<str>>(
+        &self,
+        labels: &[S],
+        max_states: usize,
+    ) -> Result<f64, TraceProbabilityError> {
+        let adjacency = self.net.adjacency();
+        let initial = self.net.initial_marking_or_empty();
+        let mut index: HashMap<TraceState, usize> = HashMap::new();
+        // For each state: Marking, position and outgoing (probability, target state) pairs
+        let mut states: Vec<(Marking, usize)> = Vec::new();
+        let mut edges: Vec<Vec<(f64, usize)>> = Vec::new();
+        // States in which no transition can fire
+        let mut dead: Vec<bool> = Vec::new();
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        index.insert((marking_key(&initial), 0), 0);
+        states.push((initial, 0));
+        queue.push_back(0);
+        while let Some(s) = queue.pop_front() {
+            let (marking, pos) = states[s].clone();
+            let mut out = Vec::new();
+            let enabled = self.enabled_transition_probabilities_in(&adjacency, &marking);
+            dead.push(enabled.is_empty());
+            for (t, p) in enabled {
+                let next_pos = match &self.net.transitions[&t.0].label {
+                    None => pos,
+                    Some(l) if labels.get(pos).is_some_and(|x| x.as_ref() == l) => pos + 1,
+                    Some(_) => continue,
+                };
+                let next = adjacency
+                    .fire(&marking, t)
+                    .expect("Enabled transition can be fired");
+                let key = (marking_key(&next), next_pos);
+                let target = match index.get(&key) {
+                    Some(target) => *target,
+                    None => {
+                        if states.len() >= max_states {
+                            return Err(TraceProbabilityError::StateLimitExceeded(max_states));
+                        }
+                        index.insert(key, states.len());
+                        states.push((next, next_pos));
+                        queue.push_back(states.len() - 1);
+                        states.len() - 1
+                    }
+                };
+                out.push((p, target));
+            }
+            edges.push(out);
+        }
+
+        let mut value = vec![0.0; states.len()];
+        for pos in (0..=labels.len()).rev() {
+            let layer: Vec<usize> = (0..states.len()).filter(|s| states[*s].1 == pos).collect();
+            solve_layer(&layer, &states, &edges, &dead, labels.len(), &mut value);
+        }
+        Ok(value[0])
+    }
+
+    ///
+    /// Compute a lower bound for the probability of the stochastic net producing exactly the trace `labels`
+    ///
+    /// Only runs with at most `max_silent_steps` silent transitions are considered.
+    /// Without cycles of silent transitions, the result is exact if `max_silent_steps` is at least the length of the longest
+    /// silent path of the net; otherwise it converges to [`StochasticLabelledPetriNet::trace_probability`] with increasing bound.
+    ///
+    pub fn trace_probability_lower_bound<S: AsRef<str>>(
+        &self,
+        labels: &[S],
+        max_silent_steps: usize,
+    ) -> f64 {
+        let adjacency = self.net.adjacency();
+        let initial = self.net.initial_marking_or_empty();
+        let initial_key = ((marking_key(&initial), 0), max_silent_steps);
+        // Each state only leads to states with fewer remaining labels or silent steps, so a (post-order) depth-first
+        // traversal computes the probabilities of all successors before those of the state itself
+        let mut cache: HashMap<BoundedTraceState, f64> = HashMap::new();
+        // Entries: State, marking and (once expanded) the probabilities and successor states
+        let mut stack: Vec<(
+            BoundedTraceState,
+            Marking,
+            Option<Vec<(f64, BoundedTraceState)>>,
+        )> = vec![(initial_key.clone(), initial, None)];
+        while let Some((key, marking, successors)) = stack.pop() {
+            if let Some(successors) = successors {
+                let res = successors.iter().map(|(p, next)| p * cache[next]).sum();
+                cache.insert(key, res);
+                continue;
+            }
+            if cache.contains_key(&key) {
+                continue;
+            }
+            let ((_, pos), silent_left) = key;
+            let enabled = self.enabled_transition_probabilities_in(&adjacency, &marking);
+            if enabled.is_empty() {
+                cache.insert(key, if pos == labels.len() { 1.0 } else { 0.0 });
+                continue;
+            }
+            let mut successors = Vec::new();
+            let mut unexplored = Vec::new();
+            for (t, p) in enabled {
+                let (next_pos, next_silent_left) = match &self.net.transitions[&t.0].label {
+                    None if silent_left > 0 => (pos, silent_left - 1),
+                    Some(l) if labels.get(pos).is_some_and(|x| x.as_ref() == l) => {
+                        (pos + 1, silent_left)
+                    }
+                    _ => continue,
+                };
+                let next = adjacency
+                    .fire(&marking, t)
+                    .expect("Enabled transition can be fired");
+                let next_key = ((marking_key(&next), next_pos), next_silent_left);
+                if !cache.contains_key(&next_key) {
+                    unexplored.push((next_key.clone(), next, None));
+                }
+                successors.push((p, next_key));
+            }
+            stack.push((key, marking, Some(successors)));
+            stack.extend(unexplored);
+        }
+        cache[&initial_key]
+    }
+
+    ///
+    /// Export to the `.slpn` text format
+    ///
+    /// Lines starting with `#` are comments. The format consists of the number of places, the initial marking (tokens per place),
+    /// the number of transitions and, for each transition, its label (`label <label>` or `silent`), its weight
+    /// and its input and output places (count followed by one place index per line, repeated for arc weights above `1`).
+    /// Final markings and inhibitor or reset arcs cannot be represented and are omitted.
+    ///
+    pub fn export_slpn<W: Write>(&self, mut writer: W) -> Result<(), std::io::Error> {
+        let adjacency = self.net.adjacency();
+        let places = adjacency.places();
+        let transitions = adjacency.transitions();
+        let initial = self.net.initial_marking_or_empty();
+
+        writeln!(writer, "# number of places\n{}", places.len())?;
+        writeln!(writer, "# initial marking")?;
+        for p in places {
+            writeln!(writer, "{}", initial.get(p).copied().unwrap_or_default())?;
+        }
+        writeln!(writer, "# number of transitions\n{}", transitions.len())?;
+        for (i, t) in transitions.iter().enumerate() {
+            writeln!(writer, "# transition {i}")?;
+            match &self.net.transitions[&t.0].label {
+                Some(label) => writeln!(writer, "label {label}")?,
+                None => writeln!(writer, "silent")?,
+            }
+            writeln!(writer, "# weight\n{}", self.weight(*t))?;
+            for (direction, arcs) in [
+                ("input", adjacency.weighted_preset_of_transition(*t)),
+                ("output", adjacency.weighted_postset_of_transition(*t)),
+            ] {
+                let mut indices: Vec<usize> = arcs
+                    .iter()
+                    .flat_map(|(p, w)| {
+                        std::iter::repeat(adjacency.place_index(*p).unwrap()).take(*w as usize)
+                    })
+                    .collect();
+                indices.sort();
+                writeln!(writer, "# number of {direction} places\n{}", indices.len())?;
+                for index in indices {
+                    writeln!(writer, "{index}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Export to a `.slpn` file (see [`StochasticLabelledPetriNet::export_slpn`])
+    pub fn export_slpn_to_path<P: AsRef<Path>>(&self, path: P) -> Result<(), std::io::Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.export_slpn(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Import from the `.slpn` text format (see [`StochasticLabelledPetriNet::export_slpn`] for a description of the format)
+    pub fn import_slpn<R: BufRead>(reader: R) -> Result<Self, SLPNParseError> {
+        let mut lines = SLPNLines {
+            lines: reader.lines(),
+            line: 0,
+        };
+        let mut net = PetriNet::new();
+        let num_places: usize = lines.next_number()?;
+        let places: Vec<PlaceID> = (0..num_places).map(|_| net.add_place(None)).collect();
+        let mut initial_marking = Marking::new();
+        for p in &places {
+            let tokens: u64 = lines.next_number()?;
+            if tokens > 0 {
+                initial_marking.insert(*p, tokens);
+            }
+        }
+        net.initial_marking = Some(initial_marking);
+
+        let num_transitions: usize = lines.next_number()?;
+        let mut weights = HashMap::new();
+        for _ in 0..num_transitions {
+            let spec = lines.next_line()?;
+            let label = if spec == "silent" {
+                None
+            } else if let Some(label) = spec.strip_prefix("label ") {
+                Some(label.to_string())
+            } else {
+                return Err(SLPNParseError::InvalidTransition { line: lines.line });
+            };
+            let t = net.add_transition(label, None);
+            weights.insert(t, lines.next_weight()?);
+            for (p, weight) in lines.next_weighted_places(&places)? {
+                net.add_arc(ArcType::place_to_transition(p, t), Some(weight));
+            }
+            for (p, weight) in lines.next_weighted_places(&places)? {
+                net.add_arc(ArcType::transition_to_place(t, p), Some(weight));
+            }
+        }
+        Ok(Self { net, weights })
+    }
+
+    /// Import from a `.slpn` file (see [`StochasticLabelledPetriNet::import_slpn`])
+    pub fn import_slpn_from_path<P: AsRef<Path>>(path: P) -> Result<Self, SLPNParseError> {
+        Self::import_slpn(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Compute the success probabilities of all states at the same trace position
+///
+/// `value` must already contain the probabilities of all states at later positions.
+fn solve_layer(
+    layer: &[usize],
+    states: &[(Marking, usize)],
+    edges: &[Vec<(f64, usize)>],
+    dead: &[bool],
+    trace_len: usize,
+    value: &mut [f64],
+) {
+    let pos = |s: usize| states[s].1;
+    // Constant part: Successful termination or moving on to the next position
+    let constant: HashMap<usize, f64> = layer
+        .iter()
+        .map(|s| {
+            let c = if dead[*s] {
+                if pos(*s) == trace_len {
+                    1.0
+                } else {
+                    0.0
+                }
+            } else {
+                edges[*s]
+                    .iter()
+                    .filter(|(_, t)| pos(*t) != pos(*s))
+                    .map(|(p, t)| p * value[*t])
+                    .sum()
+            };
+            (*s, c)
+        })
+        .collect();
+    // Only states which can (silently) reach a state with a positive constant part have a positive probability
+    let mut predecessors: HashMap<usize, Vec<usize>> = HashMap::new();
+    for s in layer {
+        for (_, t) in &edges[*s] {
+            if pos(*t) == pos(*s) {
+                predecessors.entry(*t).or_default().push(*s);
+            }
+        }
+    }
+    let mut relevant: HashSet<usize> = layer
+        .iter()
+        .filter(|s| constant[s] > 0.0)
+        .copied()
+        .collect();
+    let mut queue: Vec<usize> = relevant.iter().copied().collect();
+    while let Some(s) = queue.pop() {
+        for pre in predecessors.get(&s).into_iter().flatten() {
+            if relevant.insert(*pre) {
+                queue.push(*pre);
+            }
+        }
+    }
+    let mut unknowns: Vec<usize> = relevant.into_iter().collect();
+    unknowns.sort();
+    let column: HashMap<usize, usize> = unknowns.iter().enumerate().map(|(i, s)| (*s, i)).collect();
+    // (I - A) x = b, solved using Gaussian elimination with partial pivoting
+    let n = unknowns.len();
+    let mut matrix = vec![vec![0.0; n + 1]; n];
+    for (i, s) in unknowns.iter().enumerate() {
+        matrix[i][i] += 1.0;
+        matrix[i][n] = constant[s];
+        for (p, t) in &edges[*s] {
+            if let Some(j) = column.get(t) {
+                matrix[i][*j] -= p;
+            }
+        }
+    }
+    for c in 0..n {
+        let pivot = (c..n)
+            .max_by(|a, b| matrix[*a][c].abs().total_cmp(&matrix[*b][c].abs()))
+            .unwrap();
+        matrix.swap(c, pivot);
+        let pivot_value = matrix[c][c];
+        if pivot_value.abs() < f64::EPSILON {
+            continue;
+        }
+        let pivot_row = matrix[c].clone();
+        for (r, row) in matrix.iter_mut().enumerate() {
+            if r != c && row[c] != 0.0 {
+                let factor = row[c] / pivot_value;
+                for (x, y) in row[c..].iter_mut().zip(&pivot_row[c..]) {
+                    *x -= factor * y;
+                }
+            }
+        }
+    }
+    for (i, s) in unknowns.iter().enumerate() {
+        value[*s] = if matrix[i][i].abs() < f64::EPSILON {
+            0.0
+        } else {
+            matrix[i][n] / matrix[i][i]
+        };
+    }
+}
+
+impl From<PetriNet> for StochasticLabelledPetriNet {
+    fn from(net: PetriNet) -> Self {
+        Self::new(net)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `a` followed by a choice between `b` (weight 3) and `c` (weight 1), with a silent loop back (weight 1) after `b`
+    const SLPN: &str = "# number of places
+3
+# initial marking
+1
+0
+0
+# number of transitions
+4
+# transition 0
+label a
+# weight
+1
+# number of input places
+1
+0
+# number of output places
+1
+1
+# transition 1
+label b
+# weight
+3
+1
+1
+1
+2
+# transition 2
+label c
+# weight
+1
+1
+1
+0
+# transition 3
+silent
+# weight
+1.0
+1
+2
+1
+1
+";
+
+    #[test]
+    fn test_slpn_import_export() {
+        let slpn = StochasticLabelledPetriNet::import_slpn(SLPN.as_bytes()).unwrap();
+        assert_eq!(slpn.net.places.len(), 3);
+        assert_eq!(slpn.net.transitions.len(), 4);
+        assert_eq!(slpn.net.arcs.len(), 7);
+        let total_weight: f64 = slpn.weights.values().sum();
+        assert_eq!(total_weight, 6.0);
+
+        let mut exported = Vec::new();
+        slpn.export_slpn(&mut exported).unwrap();
+        let reimported = StochasticLabelledPetriNet::import_slpn(exported.as_slice()).unwrap();
+        assert_eq!(reimported.net.arcs.len(), 7);
+        let mut weights: Vec<(Option<String>, f64)> = reimported
+            .weights
+            .iter()
+            .map(|(t, w)| (reimported.net.transitions[&t.0].label.clone(), *w))
+            .collect();
+        weights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            weights,
+            vec![
+                (None, 1.0),
+                (Some("a".to_string()), 1.0),
+                (Some("b".to_string()), 3.0),
+                (Some("c".to_string()), 1.0)
+            ]
+        );
+        assert!(matches!(
+            StochasticLabelledPetriNet::import_slpn("1\n1\n1\nfoo\n".as_bytes()),
+            Err(SLPNParseError::InvalidTransition { line: 4 })
+        ));
+        for weight in ["-1", "NaN", "inf"] {
+            let slpn = format!("1\n1\n1\nsilent\n{weight}\n0\n0\n");
+            assert!(matches!(
+                StochasticLabelledPetriNet::import_slpn(slpn.as_bytes()),
+                Err(SLPNParseError::InvalidNumber { line: 5, value }) if value == weight
+            ));
+        }
+    }
+
+    #[test]
+    fn test_trace_probability() {
+        let slpn = StochasticLabelledPetriNet::import_slpn(SLPN.as_bytes()).unwrap();
+        let prob = |trace: &[&str]| slpn.trace_probability(trace, 1_000).unwrap();
+        // After a: b (3/4) or c (1/4); after b: the silent loop back fires with probability 1 (only enabled transition)
+        assert!((prob(&["a", "c"]) - 0.25).abs() < 1e-9);
+        assert!((prob(&["a", "b", "c"]) - 0.75 * 0.25).abs() < 1e-9);
+        assert!((prob(&["a", "b", "b", "c"]) - 0.75 * 0.75 * 0.25).abs() < 1e-9);
+        assert_eq!(prob(&["a"]), 0.0);
+        assert_eq!(prob(&["b"]), 0.0);
+        assert!(
+            (slpn.trace_probability_lower_bound(&["a", "b", "c"], 1) - 0.75 * 0.25).abs() < 1e-9
+        );
+        assert_eq!(slpn.trace_probability_lower_bound(&["a", "b", "c"], 0), 0.0);
+    }
+
+    #[test]
+    fn test_silent_cycle_probability() {
+        // Silent self-loop (weight 1) competing with a (weight 1): a has probability 1 in the limit
+        let mut net = PetriNet::new();
+        let p1 = net.add_place(None);
+        let p2 = net.add_place(None);
+        let tau = net.add_transition(None, None);
+        let a = net.add_transition(Some("a".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p1, tau), None);
+        net.add_arc(ArcType::transition_to_place(tau, p1), None);
+        net.add_arc(ArcType::place_to_transition(p1, a), None);
+        net.add_arc(ArcType::transition_to_place(a, p2), None);
+        net.initial_marking = Some(Marking::from([(p1, 1)]));
+        let slpn = StochasticLabelledPetriNet::new(net);
+        assert!((slpn.trace_probability(&["a"], 100).unwrap() - 1.0).abs() < 1e-9);
+        assert!((slpn.trace_probability_lower_bound(&["a"], 3) - 0.9375).abs() < 1e-9);
+        assert_eq!(
+            slpn.trace_probability::<&str>(&[], 100).unwrap(),
+            0.0,
+            "The empty trace is never produced"
+        );
+    }
+
+    #[test]
+    fn test_lower_bound_long_trace() {
+        // Labelled self-loop a (weight 1000) competing with b (weight 1)
+        let mut net = PetriNet::new();
+        let p1 = net.add_place(None);
+        let p2 = net.add_place(None);
+        let a = net.add_transition(Some("a".to_string()), None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p1, a), None);
+        net.add_arc(ArcType::transition_to_place(a, p1), None);
+        net.add_arc(ArcType::place_to_transition(p1, b), None);
+        net.add_arc(ArcType::transition_to_place(b, p2), None);
+        net.initial_marking = Some(Marking::from([(p1, 1)]));
+        let mut slpn = StochasticLabelledPetriNet::new(net);
+        slpn.weights.insert(a, 1000.0);
+        let mut trace = vec!["a"; 20_000];
+        trace.push("b");
+        let expected = (1000.0f64 / 1001.0).powi(20_000) / 1001.0;
+        let bound = slpn.trace_probability_lower_bound(&trace, 0);
+        assert!((bound - expected).abs() < 1e-6 * expected);
+    }
+
+    #[test]
+    fn test_estimate_weights() {
+        let slpn = StochasticLabelledPetriNet::import_slpn(SLPN.as_bytes()).unwrap();
+        let log = EventLogActivityProjection {
+            activities: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            act_to_index: HashMap::from([
+                ("a".to_string(), 0),
+                ("b".to_string(), 1),
+                ("c".to_string(), 2),
+            ]),
+            traces: vec![(vec![0, 2], 2), (vec![0, 1, 2], 1), (vec![0, 0], 5)],
+            ..Default::default()
+        };
+        let weight_of = |slpn: &StochasticLabelledPetriNet, label: Option<&str>| {
+            slpn.weights
+                .iter()
+                .find(|(t, _)| slpn.net.transitions[&t.0].label.as_deref() == label)
+                .map(|(_, w)| *w)
+                .unwrap()
+        };
+        let frequency =
+            StochasticLabelledPetriNet::estimate_weights_frequency(slpn.net.clone(), &log);
+        assert_eq!(weight_of(&frequency, Some("a")), 13.0);
+        assert_eq!(weight_of(&frequency, Some("c")), 3.0);
+        assert_eq!(weight_of(&frequency, None), 17.0 / 3.0);
+
+        let replay = StochasticLabelledPetriNet::estimate_weights_replay(slpn.net, &log);
+        assert_eq!(weight_of(&replay, Some("a")), 3.0);
+        assert_eq!(weight_of(&replay, Some("b")), 1.0);
+        assert_eq!(weight_of(&replay, Some("c")), 3.0);
+        assert_eq!(weight_of(&replay, None), 1.0);
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::adjacency::PetriNetAdjacency;
+use super::petri_net_struct::{PetriNet, PlaceID, TransitionID};
+
+/// Incidence matrix of a [`PetriNet`] (see [`PetriNet::incidence_matrix`])
+///
+/// Rows correspond to places and columns to transitions.
+/// Each entry contains the number of tokens a transition produces in a place minus the number of tokens it consumes from that place.
+/// Inhibitor and reset arcs are not represented (thus, also the semiflows, siphons, traps and subclass checks ignore them).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncidenceMatrix {
+    /// Places (rows), sorted by ID
+    pub places: Vec<PlaceID>,
+    /// Transitions (columns), sorted by ID
+    pub transitions: Vec<TransitionID>,
+    /// Matrix entries (`matrix[place_index][transition_index]`)
+    pub matrix: Vec<Vec<i64>>,
+}
+
+impl IncidenceMatrix {
+    /// Get the entry for a place and a transition
+    ///
+    /// Returns `None` if the place or transition is not part of the matrix.
+    pub fn get(&self, p: &PlaceID, t: &TransitionID) -> Option<i64> {
+        let p_index = self.places.binary_search(p).ok()?;
+        let t_index = self.transitions.binary_search(t).ok()?;
+        Some(self.matrix[p_index][t_index])
+    }
+
+    /// Transpose the matrix (i.e., rows become columns)
+    fn transposed(&self) -> Vec<Vec<i64>> {
+        (0..self.transitions.len())
+            .map(|t| self.matrix.iter().map(|row| row[t]).collect())
+            .collect()
+    }
+}
+
+/// Semiflow (i.e., non-negative integer invariant) of a [`PetriNet`]
+///
+/// See [`PSemiflow`] and [`TSemiflow`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Semiflow<N> {
+    /// Nodes with a non-zero weight, sorted by ID
+    pub weights: Vec<(N, u64)>,
+}
+
+impl<N: Copy> Semiflow<N> {
+    /// Get the support of the semiflow (i.e., all nodes with a non-zero weight)
+    pub fn support(&self) -> Vec<N> {
+        self.weights.iter().map(|(n, _)| *n).collect()
+    }
+}
+
+/// P-semiflow (place invariant): The weighted sum of tokens in these places is constant in all reachable markings
+pub type PSemiflow = Semiflow<PlaceID>;
+
+/// T-semiflow (transition invariant): Firing the transitions this often reproduces the marking
+pub type TSemiflow = Semiflow<TransitionID>;
+
+/// Greatest common divisor
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Compute minimal-support semiflows `x >= 0` with `x^T * matrix = 0` (Farkas algorithm)
+///
+/// Returns the weights of the semiflows per row of the matrix.
+fn farkas(matrix: &[Vec<i64>]) -> Vec<Vec<u64>> {
+    let num_rows = matrix.len();
+    let num_cols = matrix.first().map(|r| r.len()).unwrap_or_default();
+    // Each row: (Remaining matrix row, weights of the original rows)
+    let mut rows: Vec<(Vec<i64>, Vec<u64>)> = matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut weights = vec![0; num_rows];
+            weights[i] = 1;
+            (row.clone(), weights)
+        })
+        .collect();
+    for col in 0..num_cols {
+        let mut next: Vec<(Vec<i64>, Vec<u64>)> = Vec::new();
+        for (i, (row_i, weights_i)) in rows.iter().enumerate() {
+            if row_i[col] == 0 {
+                next.push((row_i.clone(), weights_i.clone()));
+                continue;
+            }
+            // Combine rows with opposite signs in this column
+            for (row_k, weights_k) in rows.iter().skip(i + 1) {
+                if row_k[col] == 0 || row_k[col].signum() == row_i[col].signum() {
+                    continue;
+                }
+                let a = row_k[col].unsigned_abs();
+                let b = row_i[col].unsigned_abs();
+                let combined_row: Vec<i64> = row_i
+                    .iter()
+                    .zip(row_k)
+                    .map(|(x, y)| x * a as i64 + y * b as i64)
+                    .collect();
+                let combined_weights: Vec<u64> = weights_i
+                    .iter()
+                    .zip(weights_k)
+                    .map(|(x, y)| x * a + y * b)
+                    .collect();
+                next.push((combined_row, combined_weights));
+            }
+        }
+        // Normalize and remove duplicates and rows with non-minimal support
+        for (row, weights) in next.iter_mut() {
+            let divisor = weights
+                .iter()
+                .copied()
+                .chain(row.iter().map(|x| x.unsigned_abs()))
+                .fold(0, gcd);
+            if divisor > 1 {
+                weights.iter_mut().for_each(|w| *w /= divisor);
+                row.iter_mut().for_each(|x| *x /= divisor as i64);
+            }
+        }
+        let mut seen = HashSet::new();
+        next.retain(|(_, weights)| seen.insert(weights.clone()));
+        rows = retain_minimal_support(next, |(_, weights)| weights);
+    }
+    rows.into_iter()
+        .map(|(_, weights)| weights)
+        .filter(|weights| weights.iter().any(|w| *w > 0))
+        .collect()
+}
+
+/// Only keep the items whose support (non-zero weights) is minimal w.r.t. set inclusion
+fn retain_minimal_support<T, F: Fn(&T) -> &Vec<u64>>(items: Vec<T>, weights: F) -> Vec<T> {
+    let supports: Vec<Vec<bool>> = items
+        .iter()
+        .map(|item| weights(item).iter().map(|w| *w > 0).collect())
+        .collect();
+    let is_strict_subset =
+        |a: &Vec<bool>, b: &Vec<bool>| a != b && a.iter().zip(b).all(|(x, y)| !*x || *y);
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            !supports
+                .iter()
+                .any(|other| is_strict_subset(other, &supports[*i]))
+        })
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Convert weights (indexed like `nodes`) to a [`Semiflow`]
+fn to_semiflow<N: Copy>(nodes: &[N], weights: Vec<u64>) -> Semiflow<N> {
+    Semiflow {
+        weights: nodes
+            .iter()
+            .zip(weights)
+            .filter(|(_, w)| *w > 0)
+            .map(|(n, w)| (*n, w))
+            .collect(),
+    }
+}
+
+/// Weighted neighbours of a place (its preset or postset)
+type PlaceNeighbours = fn(&PetriNetAdjacency, PlaceID) -> &[(TransitionID, u64)];
+
+/// Weighted neighbours of a transition (its preset or postset)
+type TransitionNeighbours = fn(&PetriNetAdjacency, TransitionID) -> &[(PlaceID, u64)];
+
+/// Enumerate all minimal non-empty place sets `S`,
+/// such that every transition in `connected(S)` has a place of `S` in `required(t)`
+///
+/// For each violating transition `t`, the search branches over adding one of the places in `required(t)`.
+fn minimal_place_sets(
+    adjacency: &PetriNetAdjacency,
+    connected: PlaceNeighbours,
+    required: TransitionNeighbours,
+) -> Vec<Vec<PlaceID>> {
+    let mut found: Vec<Vec<PlaceID>> = Vec::new();
+    let mut visited: HashSet<Vec<PlaceID>> = HashSet::new();
+    let mut stack: Vec<Vec<PlaceID>> = adjacency.places().iter().map(|p| vec![*p]).collect();
+    while let Some(set) = stack.pop() {
+        if !visited.insert(set.clone()) {
+            continue;
+        }
+        // Supersets of found sets are not minimal
+        if found
+            .iter()
+            .any(|f| f.iter().all(|p| set.binary_search(p).is_ok()))
+        {
+            continue;
+        }
+        let violation = set
+            .iter()
+            .flat_map(|p| connected(adjacency, *p))
+            .map(|(t, _)| *t)
+            .find(|t| {
+                !required(adjacency, *t)
+                    .iter()
+                    .any(|(p, _)| set.binary_search(p).is_ok())
+            });
+        match violation {
+            None => found.push(set),
+            Some(t) => {
+                for (p, _) in required(adjacency, t) {
+                    let mut next = set.clone();
+                    if let Err(pos) = next.binary_search(p) {
+                        next.insert(pos, *p);
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+    }
+    let mut minimal: Vec<Vec<PlaceID>> = found
+        .iter()
+        .filter(|set| {
+            !found.iter().any(|other| {
+                other.len() < set.len() && other.iter().all(|p| set.binary_search(p).is_ok())
+            })
+        })
+        .cloned()
+        .collect();
+    minimal.sort();
+    minimal.dedup();
+    minimal
+}
+
+impl PetriNet {
+    /// Construct the incidence matrix of the net
+    ///
+    /// Arcs connected to nodes which are not part of the net are ignored.
+    pub fn incidence_matrix(&self) -> IncidenceMatrix {
+        let adjacency = self.adjacency();
+        let places = adjacency.places().to_vec();
+        let transitions = adjacency.transitions().to_vec();
+        let mut matrix = vec![vec![0; transitions.len()]; places.len()];
+        for (p_index, p) in places.iter().enumerate() {
+            let produced = adjacency.weighted_preset_of_place(*p).iter();
+            let consumed = adjacency.weighted_postset_of_place(*p).iter();
+            for (t, w, sign) in produced
+                .map(|(t, w)| (t, w, 1))
+                .chain(consumed.map(|(t, w)| (t, w, -1)))
+            {
+                if let Some(t_index) = adjacency.transition_index(*t) {
+                    matrix[p_index][t_index] += sign * *w as i64;
+                }
+            }
+        }
+        IncidenceMatrix {
+            places,
+            transitions,
+            matrix,
+        }
+    }
+
+    /// Compute all minimal P-semiflows (place invariants) using the Farkas algorithm
+    ///
+    /// Only semiflows with minimal support are returned, each scaled to the smallest integer weights.
+    /// Note that the number of intermediate results can grow exponentially in the size of the net.
+    pub fn minimal_p_semiflows(&self) -> Vec<PSemiflow> {
+        let incidence = self.incidence_matrix();
+        farkas(&incidence.matrix)
+            .into_iter()
+            .map(|weights| to_semiflow(&incidence.places, weights))
+            .collect()
+    }
+
+    /// Compute all minimal T-semiflows (transition invariants) using the Farkas algorithm
+    ///
+    /// Only semiflows with minimal support are returned, each scaled to the smallest integer weights.
+    /// Note that the number of intermediate results can grow exponentially in the size of the net.
+    pub fn minimal_t_semiflows(&self) -> Vec<TSemiflow> {
+        let incidence = self.incidence_matrix();
+        farkas(&incidence.transposed())
+            .into_iter()
+            .map(|weights| to_semiflow(&incidence.transitions, weights))
+            .collect()
+    }
+
+    /// Enumerate all minimal (non-empty) siphons
+    ///
+    /// A siphon is a set of places such that every transition producing tokens in it also consumes tokens from it.
+    /// Thus, once a siphon is empty, it stays empty.
+    /// Each siphon is sorted by place ID.
+    pub fn minimal_siphons(&self) -> Vec<Vec<PlaceID>> {
+        minimal_place_sets(
+            &self.adjacency(),
+            PetriNetAdjacency::weighted_preset_of_place,
+            PetriNetAdjacency::weighted_preset_of_transition,
+        )
+    }
+
+    /// Enumerate all minimal (non-empty) traps
+    ///
+    /// A trap is a set of places such that every transition consuming tokens from it also produces tokens in it.
+    /// Thus, once a trap is marked, it stays marked.
+    /// Each trap is sorted by place ID.
+    pub fn minimal_traps(&self) -> Vec<Vec<PlaceID>> {
+        minimal_place_sets(
+            &self.adjacency(),
+            PetriNetAdjacency::weighted_postset_of_place,
+            PetriNetAdjacency::weighted_postset_of_transition,
+        )
+    }
+
+    /// Check if the net is a state machine (every transition has exactly one input and one output place)
+    pub fn is_state_machine(&self) -> bool {
+        let adjacency = self.adjacency();
+        adjacency.transitions().iter().all(|t| {
+            adjacency.weighted_preset_of_transition(*t).len() == 1
+                && adjacency.weighted_postset_of_transition(*t).len() == 1
+        })
+    }
+
+    /// Check if the net is a marked graph (every place has exactly one input and one output transition)
+    pub fn is_marked_graph(&self) -> bool {
+        let adjacency = self.adjacency();
+        adjacency.places().iter().all(|p| {
+            adjacency.weighted_preset_of_place(*p).len() == 1
+                && adjacency.weighted_postset_of_place(*p).len() == 1
+        })
+    }
+
+    /// Check if the net is free-choice
+    ///
+    /// A net is free-choice if for every arc from a place `p` to a transition `t`,
+    /// `t` is the only output transition of `p` or `p` is the only input place of `t`.
+    pub fn is_free_choice(&self) -> bool {
+        let adjacency = self.adjacency();
+        adjacency.places().iter().all(|p| {
+            let postset = adjacency.weighted_postset_of_place(*p);
+            postset.len() == 1
+                || postset
+                    .iter()
+                    .all(|(t, _)| adjacency.weighted_preset_of_transition(*t).len() == 1)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::petri_net_struct::ArcType;
+
+    /// Cycle: `p0 -a-> p1 -b-> p0`, with `c` moving a token from `p1` into `p2` (and back via `d`), synchronizing with `p0`
+    fn cycle_net() -> (PetriNet, Vec<PlaceID>, Vec<TransitionID>) {
+        let mut net = PetriNet::new();
+        let places: Vec<PlaceID> = (0..3).map(|_| net.add_place(None)).collect();
+        let transitions: Vec<TransitionID> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|l| net.add_transition(Some(l.to_string()), None))
+            .collect();
+        let (p, t) = (&places, &transitions);
+        net.add_arc(ArcType::place_to_transition(p[0], t[0]), None);
+        net.add_arc(ArcType::transition_to_place(t[0], p[1]), None);
+        net.add_arc(ArcType::place_to_transition(p[1], t[1]), None);
+        net.add_arc(ArcType::transition_to_place(t[1], p[0]), None);
+        net.add_arc(ArcType::place_to_transition(p[1], t[2]), None);
+        net.add_arc(ArcType::transition_to_place(t[2], p[2]), None);
+        net.add_arc(ArcType::place_to_transition(p[2], t[3]), None);
+        net.add_arc(ArcType::transition_to_place(t[3], p[1]), None);
+        (net, places, transitions)
+    }
+
+    #[test]
+    fn test_incidence_matrix_and_semiflows() {
+        let (net, mut p, t) = cycle_net();
+        let incidence = net.incidence_matrix();
+        assert_eq!(incidence.get(&p[0], &t[0]), Some(-1));
+        assert_eq!(incidence.get(&p[1], &t[0]), Some(1));
+        assert_eq!(incidence.get(&p[2], &t[0]), Some(0));
+
+        let p_semiflows = net.minimal_p_semiflows();
+        assert_eq!(p_semiflows.len(), 1);
+        p.sort();
+        assert_eq!(p_semiflows[0].support(), p);
+        assert!(p_semiflows[0].weights.iter().all(|(_, w)| *w == 1));
+
+        let mut t_semiflows: Vec<Vec<TransitionID>> = net
+            .minimal_t_semiflows()
+            .iter()
+            .map(|s| {
+                let mut support = s.support();
+                support.sort();
+                support
+            })
+            .collect();
+        t_semiflows.sort();
+        let mut expected = vec![vec![t[0], t[1]], vec![t[2], t[3]]];
+        expected.iter_mut().for_each(|s| s.sort());
+        expected.sort();
+        assert_eq!(t_semiflows, expected);
+    }
+
+    #[test]
+    fn test_dangling_arcs_are_ignored() {
+        let (mut net, p, t) = cycle_net();
+        let unknown_place = PlaceID(uuid::Uuid::new_v4());
+        let unknown_transition = TransitionID(uuid::Uuid::new_v4());
+        net.add_arc(ArcType::transition_to_place(t[0], unknown_place), None);
+        net.add_arc(ArcType::place_to_transition(p[0], unknown_transition), None);
+        let incidence = net.incidence_matrix();
+        assert_eq!(incidence.places.len(), 3);
+        assert_eq!(incidence.transitions.len(), 4);
+        assert_eq!(incidence.get(&p[0], &t[0]), Some(-1));
+        assert_eq!(incidence.get(&p[1], &t[0]), Some(1));
+        assert_eq!(net.minimal_p_semiflows().len(), 1);
+        assert_eq!(net.minimal_siphons().len(), 1);
+        assert_eq!(net.minimal_traps().len(), 1);
+    }
+
+    #[test]
+    fn test_siphons_and_traps() {
+        let (net, mut p, _) = cycle_net();
+        p.sort();
+        // All places together form the only minimal siphon and trap
+        assert_eq!(net.minimal_siphons(), vec![p.clone()]);
+        assert_eq!(net.minimal_traps(), vec![p.clone()]);
+
+        let mut net = PetriNet::new();
+        let p0 = net.add_place(None);
+        let p1 = net.add_place(None);
+        let t = net.add_transition(None, None);
+        net.add_arc(ArcType::place_to_transition(p0, t), None);
+        net.add_arc(ArcType::transition_to_place(t, p1), None);
+        assert_eq!(net.minimal_siphons(), vec![vec![p0]]);
+        assert_eq!(net.minimal_traps(), vec![vec![p1]]);
+    }
+
+    #[test]
+    fn test_subclasses() {
+        let (mut net, p, _) = cycle_net();
+        assert!(net.is_state_machine());
+        assert!(!net.is_marked_graph());
+        assert!(net.is_free_choice());
+
+        // Synchronization of `p0` and `p2` in a transition whose input places have other output transitions
+        let sync = net.add_transition(None, None);
+        net.add_arc(ArcType::place_to_transition(p[0], sync), None);
+        net.add_arc(ArcType::place_to_transition(p[2], sync), None);
+        assert!(!net.is_state_machine());
+        assert!(!net.is_free_choice());
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use uuid::Uuid;
+
+use crate::utils::graph_text_export::{
+    dot_attributes, escape_dot, escape_mermaid, CytoscapeElement, CytoscapeGraph,
+};
+
+use super::petri_net_struct::{ArcType, PlaceID};
+use crate::PetriNet;
+
+/// Get the places and transitions of a [`PetriNet`] sorted by their ID (for a deterministic output)
+fn sorted_nodes(net: &PetriNet) -> (Vec<Uuid>, Vec<Uuid>) {
+    let mut places: Vec<Uuid> = net.places.keys().copied().collect();
+    places.sort();
+    let mut transitions: Vec<Uuid> = net.transitions.keys().copied().collect();
+    transitions.sort();
+    (places, transitions)
+}
+
+/// Get the label of a place: Its tokens in the initial marking (if any)
+fn place_label(net: &PetriNet, p: &Uuid) -> String {
+    let tokens = net
+        .initial_marking
+        .as_ref()
+        .and_then(|m| m.get(&PlaceID(*p)))
+        .copied()
+        .unwrap_or_default();
+    match tokens {
+        0 => String::new(),
+        1 => "●".to_string(),
+        n => n.to_string(),
+    }
+}
+
+///
+/// Export a [`PetriNet`] to DOT source code (used in Graphviz)
+///
+/// In contrast to the `image_export` module, this does not require the `graphviz-export` feature.
+/// The resulting graph uses the same visual notation: Places in the initial marking contain a token symbol,
+/// places in a final marking are drawn as double circles and silent transitions are filled black.
+/// Inhibitor arcs end in a circle and reset arcs in a double arrowhead.
+///
+pub fn export_petri_net_to_dot(net: &PetriNet) -> String {
+    let (places, transitions) = sorted_nodes(net);
+    let mut res = String::from("digraph {\n  rankdir=LR;\n");
+    for p in &places {
+        let is_final = net.is_in_a_final_marking(&PlaceID(*p));
+        let size = if is_final { 0.4 } else { 0.5 };
+        let attrs = dot_attributes(&[
+            ("label", format!("\"{}\"", place_label(net, p))),
+            (
+                "shape",
+                if is_final { "doublecircle" } else { "circle" }.to_string(),
+            ),
+            ("fixedsize", "true".to_string()),
+            ("width", size.to_string()),
+            ("height", size.to_string()),
+        ]);
+        writeln!(res, "  \"{p}\"{attrs};").unwrap();
+    }
+    for t in &transitions {
+        let label = net.transitions[t].label.as_ref();
+        let attrs = dot_attributes(&[
+            (
+                "label",
+                format!(
+                    "\"{}\"",
+                    escape_dot(label.map(|l| l.as_str()).unwrap_or(""))
+                ),
+            ),
+            ("shape", "box".to_string()),
+            ("fontsize", "12".to_string()),
+            ("style", "filled".to_string()),
+            (
+                "fillcolor",
+                if label.is_none() { "black" } else { "white" }.to_string(),
+            ),
+            ("width", "1".to_string()),
+            ("height", "0.5".to_string()),
+        ]);
+        writeln!(res, "  \"{t}\"{attrs};").unwrap();
+    }
+    for arc in &net.arcs {
+        let (from, to) = arc.from_to.endpoints();
+        let mut attrs = Vec::new();
+        if arc.weight != 1 {
+            attrs.push(("label", format!("\"{}\"", arc.weight)));
+        }
+        match arc.from_to {
+            ArcType::Inhibitor(_, _) => attrs.push(("arrowhead", "odot".to_string())),
+            ArcType::Reset(_, _) => attrs.push(("arrowhead", "normalnormal".to_string())),
+            ArcType::PlaceTransition(_, _) | ArcType::TransitionPlace(_, _) => {}
+        }
+        let attrs = dot_attributes(&attrs);
+        writeln!(res, "  \"{from}\" -> \"{to}\"{attrs};").unwrap();
+    }
+    res.push_str("}\n");
+    res
+}
+
+///
+/// Export a [`PetriNet`] to a Mermaid flowchart
+///
+/// Places are drawn as circles (double circles for places in a final marking) and transitions as boxes.
+/// Silent transitions are filled black.
+/// Inhibitor arcs end in a circle (`--o`) and reset arcs in a cross (`--x`).
+/// As Mermaid node IDs need to be simple identifiers, places and transitions are numbered (`p0`, `p1`, ..., `t0`, `t1`, ...)
+/// in the order of their IDs.
+///
+pub fn export_petri_net_to_mermaid(net: &PetriNet) -> String {
+    let (places, transitions) = sorted_nodes(net);
+    let mut node_ids: HashMap<Uuid, String> = HashMap::new();
+    let mut res = String::from("flowchart LR\n");
+    for (i, p) in places.iter().enumerate() {
+        let id = format!("p{i}");
+        let label = escape_mermaid(&place_label(net, p));
+        if net.is_in_a_final_marking(&PlaceID(*p)) {
+            writeln!(res, "  {id}(((\"{label}\")))").unwrap();
+        } else {
+            writeln!(res, "  {id}((\"{label}\"))").unwrap();
+        }
+        node_ids.insert(*p, id);
+    }
+    let mut silent_ids = Vec::new();
+    for (i, t) in transitions.iter().enumerate() {
+        let id = format!("t{i}");
+        match &net.transitions[t].label {
+            Some(label) => writeln!(res, "  {id}[\"{}\"]", escape_mermaid(label)).unwrap(),
+            None => {
+                writeln!(res, "  {id}[\" \"]").unwrap();
+                silent_ids.push(id.clone());
+            }
+        }
+        node_ids.insert(*t, id);
+    }
+    for arc in &net.arcs {
+        let (from, to) = arc.from_to.endpoints();
+        let (from, to) = (&node_ids[&from], &node_ids[&to]);
+        let link = match arc.from_to {
+            ArcType::Inhibitor(_, _) => "--o",
+            ArcType::Reset(_, _) => "--x",
+            ArcType::PlaceTransition(_, _) | ArcType::TransitionPlace(_, _) => "-->",
+        };
+        if arc.weight == 1 {
+            writeln!(res, "  {from} {link} {to}").unwrap();
+        } else {
+            writeln!(res, "  {from} {link}|{}| {to}", arc.weight).unwrap();
+        }
+    }
+    if !silent_ids.is_empty() {
+        res.push_str("  classDef silent fill:#000,stroke:#000\n");
+        writeln!(res, "  class {} silent", silent_ids.join(",")).unwrap();
+    }
+    res
+}
+
+///
+/// Export a [`PetriNet`] to a [`CytoscapeGraph`] (JSON graph format of Cytoscape.js)
+///
+/// Places have the class `place` and transitions the class `transition` (and additionally `silent` for silent transitions).
+/// Places additionally contain their `initial_tokens` and whether they are part of a final marking (`final`).
+/// Arcs contain their `weight` and have the class `arc` (and additionally `inhibitor` or `reset` for inhibitor and reset arcs).
+///
+pub fn export_petri_net_to_cytoscape(net: &PetriNet) -> CytoscapeGraph {
+    let (places, transitions) = sorted_nodes(net);
+    let mut elements = Vec::new();
+    for p in &places {
+        let tokens = net
+            .initial_marking
+            .as_ref()
+            .and_then(|m| m.get(&PlaceID(*p)))
+            .copied()
+            .unwrap_or_default();
+        elements.push(
+            CytoscapeElement::node(p.to_string(), "", &["place"])
+                .with_data("initial_tokens", tokens)
+                .with_data("final", net.is_in_a_final_marking(&PlaceID(*p))),
+        );
+    }
+    for t in &transitions {
+        let el = match &net.transitions[t].label {
+            Some(label) => CytoscapeElement::node(t.to_string(), label.clone(), &["transition"]),
+            None => CytoscapeElement::node(t.to_string(), "", &["transition", "silent"]),
+        };
+        elements.push(el);
+    }
+    for (i, arc) in net.arcs.iter().enumerate() {
+        let (from, to) = arc.from_to.endpoints();
+        elements.push(
+            CytoscapeElement::edge(
+                format!("arc{i}"),
+                from.to_string(),
+                to.to_string(),
+                match arc.from_to {
+                    ArcType::Inhibitor(_, _) => &["arc", "inhibitor"],
+                    ArcType::Reset(_, _) => &["arc", "reset"],
+                    ArcType::PlaceTransition(_, _) | ArcType::TransitionPlace(_, _) => &["arc"],
+                },
+            )
+            .with_data("weight", arc.weight),
+        );
+    }
+    CytoscapeGraph { elements }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::petri_net_struct::Marking;
+
+    fn sample_net() -> PetriNet {
+        let mut net = PetriNet::new();
+        let p1 = net.add_place(None);
+        let p2 = net.add_place(None);
+        let t1 = net.add_transition(Some("a \"quoted\"".to_string()), None);
+        let t2 = net.add_transition(None, None);
+        net.add_arc(ArcType::place_to_transition(p1, t1), None);
+        net.add_arc(ArcType::transition_to_place(t1, p2), Some(2));
+        net.add_arc(ArcType::place_to_transition(p2, t2), None);
+        net.initial_marking = Some(Marking::from([(p1, 1)]));
+        net.final_markings = Some(vec![Marking::from([(p2, 1)])]);
+        net
+    }
+
+    #[test]
+    fn test_petri_net_dot() {
+        let net = sample_net();
+        let dot = export_petri_net_to_dot(&net);
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("label=\"●\""));
+        assert!(dot.contains("shape=doublecircle"));
+        assert!(dot.contains("label=\"a \\\"quoted\\\"\""));
+        assert!(dot.contains("fillcolor=black"));
+        assert!(dot.contains("[label=\"2\"]"));
+        assert_eq!(dot.matches("->").count(), 3);
+    }
+
+    #[test]
+    fn test_petri_net_mermaid() {
+        let net = sample_net();
+        let mermaid = export_petri_net_to_mermaid(&net);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("#quot;quoted#quot;"));
+        assert!(mermaid.contains("-->|2|"));
+        assert!(mermaid.contains("(((\"\")))"));
+        assert!(mermaid.contains("class t"));
+        assert_eq!(mermaid.matches("-->").count(), 3);
+    }
+
+    #[test]
+    fn test_petri_net_cytoscape() {
+        let net = sample_net();
+        let graph = export_petri_net_to_cytoscape(&net);
+        assert_eq!(graph.elements.len(), 2 + 2 + 3);
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json()).unwrap();
+        let elements = json["elements"].as_array().unwrap();
+        assert_eq!(
+            elements
+                .iter()
+                .filter(|e| e["classes"][0] == "place" && e["data"]["initial_tokens"] == 1)
+                .count(),
+            1
+        );
+        assert!(elements
+            .iter()
+            .any(|e| e["group"] == "edges" && e["data"]["weight"] == 2));
+    }
+}
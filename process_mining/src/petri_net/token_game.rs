@@ -0,0 +1,439 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    adjacency::PetriNetAdjacency,
+    petri_net_struct::{Marking, PetriNet, PlaceID, TransitionID},
+};
+
+/// Default maximal number of states explored per replay step (see [`PetriNet::replay_labels`])
+pub const DEFAULT_MAX_REPLAY_STATES: usize = 10_000;
+
+/// Error when firing a transition (see [`PetriNet::fire`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FiringError {
+    /// The transition is not part of the [`PetriNet`]
+    UnknownTransition(TransitionID),
+    /// The transition is not enabled in the marking
+    NotEnabled(TransitionID),
+}
+
+impl std::fmt::Display for FiringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FiringError::UnknownTransition(t) => write!(f, "Unknown transition {}", t.0),
+            FiringError::NotEnabled(t) => write!(f, "Transition {} is not enabled", t.0),
+        }
+    }
+}
+
+impl std::error::Error for FiringError {}
+
+/// Result of replaying a sequence of labels (see [`PetriNet::replay_labels`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelReplayResult {
+    /// Fired transitions (including silent transitions)
+    pub firing_sequence: Vec<TransitionID>,
+    /// Marking reached after firing all transitions
+    pub marking: Marking,
+    /// Whether the reached marking is accepting (see [`PetriNet::is_accepting_marking`])
+    ///
+    /// I.e., a final marking or, if the net has no final markings, a dead marking.
+    /// This agrees with [`PetriNet::accepts_trace`].
+    pub reached_final_marking: bool,
+}
+
+/// Error when replaying a sequence of labels (see [`PetriNet::replay_labels`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LabelReplayError {
+    /// The label at the given position could not be replayed
+    ///
+    /// No transition with this label could be enabled (also considering silent transitions)
+    /// after replaying all previous labels.
+    LabelNotReplayable {
+        /// Position of the label in the sequence
+        position: usize,
+        /// The label
+        label: String,
+    },
+    /// More than the given number of markings would have to be explored in one step
+    StateLimitExceeded(usize),
+}
+
+impl std::fmt::Display for LabelReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelReplayError::LabelNotReplayable { position, label } => {
+                write!(
+                    f,
+                    "Label {label:?} at position {position} cannot be replayed"
+                )
+            }
+            LabelReplayError::StateLimitExceeded(max) => {
+                write!(f, "State limit of {max} markings per step exceeded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LabelReplayError {}
+
+/// Canonical (hashable) representation of a [`Marking`]: Sorted places with a non-zero number of tokens
+pub(crate) fn marking_key(marking: &Marking) -> Vec<(Uuid, u64)> {
+    let mut key: Vec<(Uuid, u64)> = marking
+        .iter()
+        .filter(|(_, tokens)| **tokens > 0)
+        .map(|(p, tokens)| (p.0, *tokens))
+        .collect();
+    key.sort();
+    key
+}
+
+impl PetriNet {
+    /// Get the preset of a transition together with the arc weights
+    ///
+    /// If there are multiple arcs between the same place and transition, their weights are summed up.
+    /// To query many transitions, use [`PetriNet::adjacency`] instead.
+    pub fn weighted_preset_of_transition(&self, t: TransitionID) -> Vec<(PlaceID, u64)> {
+        self.adjacency().weighted_preset_of_transition(t).to_vec()
+    }
+
+    /// Get the postset of a transition together with the arc weights
+    ///
+    /// If there are multiple arcs between the same transition and place, their weights are summed up.
+    /// To query many transitions, use [`PetriNet::adjacency`] instead.
+    pub fn weighted_postset_of_transition(&self, t: TransitionID) -> Vec<(PlaceID, u64)> {
+        self.adjacency().weighted_postset_of_transition(t).to_vec()
+    }
+
+    /// Get the inhibitor arcs of a transition as places with the arc weights
+    ///
+    /// The transition is only enabled if each of these places contains fewer tokens than the arc weight.
+    /// If there are multiple inhibitor arcs between the same place and transition, the most restrictive one applies.
+    /// To query many transitions, use [`PetriNet::adjacency`] instead.
+    pub fn inhibitor_arcs_of_transition(&self, t: TransitionID) -> Vec<(PlaceID, u64)> {
+        self.adjacency().inhibitor_arcs_of_transition(t).to_vec()
+    }
+
+    /// Get all places connected to a transition via reset arcs
+    ///
+    /// Firing the transition removes all tokens from these places.
+    /// To query many transitions, use [`PetriNet::adjacency`] instead.
+    pub fn reset_places_of_transition(&self, t: TransitionID) -> Vec<PlaceID> {
+        self.adjacency().reset_places_of_transition(t).to_vec()
+    }
+
+    /// Get the initial marking of the net (or an empty marking, if no initial marking is set)
+    pub fn initial_marking_or_empty(&self) -> Marking {
+        self.initial_marking.clone().unwrap_or_default()
+    }
+
+    /// Check if transition `t` is enabled in `marking`
+    ///
+    /// See [`PetriNetAdjacency::is_enabled`]; to check many transitions, use [`PetriNet::adjacency`] instead.
+    /// Returns `false` for transitions not part of the net.
+    pub fn is_enabled(&self, marking: &Marking, t: TransitionID) -> bool {
+        self.adjacency().is_enabled(marking, t)
+    }
+
+    /// Get all transitions enabled in `marking` (sorted by ID)
+    ///
+    /// See [`PetriNetAdjacency::enabled_transitions`]; to query many markings, use [`PetriNet::adjacency`] instead.
+    pub fn enabled_transitions(&self, marking: &Marking) -> Vec<TransitionID> {
+        self.adjacency().enabled_transitions(marking)
+    }
+
+    /// Fire transition `t` in `marking`, returning the resulting marking
+    ///
+    /// See [`PetriNetAdjacency::fire`]; to fire many transitions, use [`PetriNet::adjacency`] instead.
+    pub fn fire(&self, marking: &Marking, t: TransitionID) -> Result<Marking, FiringError> {
+        self.adjacency().fire(marking, t)
+    }
+
+    /// Fire a sequence of transitions, starting in `marking`
+    ///
+    /// Returns the marking reached after firing all transitions, or the error of the first transition which could not be fired.
+    pub fn fire_sequence<'b, I: IntoIterator<Item = &'b TransitionID>>(
+        &self,
+        marking: &Marking,
+        transitions: I,
+    ) -> Result<Marking, FiringError> {
+        let adjacency = self.adjacency();
+        transitions
+            .into_iter()
+            .try_fold(marking.clone(), |m, t| adjacency.fire(&m, *t))
+    }
+
+    /// Check if `marking` is equal to any of the final markings of the net
+    ///
+    /// Places with zero tokens are ignored in the comparison.
+    /// Returns `false` if no final markings are set.
+    pub fn is_final_marking(&self, marking: &Marking) -> bool {
+        let key = marking_key(marking);
+        self.final_markings
+            .iter()
+            .flatten()
+            .any(|m| marking_key(m) == key)
+    }
+
+    /// Check if `marking` covers any of the final markings of the net
+    ///
+    /// A marking covers a final marking if it contains at least as many tokens in every place.
+    /// Returns `false` if no final markings are set.
+    pub fn covers_final_marking(&self, marking: &Marking) -> bool {
+        self.final_markings.iter().flatten().any(|m| {
+            m.iter()
+                .all(|(p, tokens)| marking.get(p).copied().unwrap_or_default() >= *tokens)
+        })
+    }
+
+    /// Check if no transition is enabled in `marking` (i.e., the marking is a deadlock, unless it is a final marking)
+    ///
+    /// See [`PetriNetAdjacency::is_dead_marking`]; to check many markings, use [`PetriNet::adjacency`] instead.
+    pub fn is_dead_marking(&self, marking: &Marking) -> bool {
+        self.adjacency().is_dead_marking(marking)
+    }
+
+    /// Check if a run of the net may end in `marking`
+    ///
+    /// If final markings are set, `marking` must be one of them (see [`PetriNet::is_final_marking`]).
+    /// Otherwise, `marking` must be dead (see [`PetriNet::is_dead_marking`]).
+    pub fn is_accepting_marking(&self, marking: &Marking) -> bool {
+        self.is_accepting_marking_in(&self.adjacency(), marking)
+    }
+
+    /// Check if a run of the net may end in `marking`, using a prebuilt `adjacency` of the net
+    ///
+    /// See [`PetriNet::is_accepting_marking`].
+    pub(crate) fn is_accepting_marking_in(
+        &self,
+        adjacency: &PetriNetAdjacency,
+        marking: &Marking,
+    ) -> bool {
+        match &self.final_markings {
+            Some(fms) if !fms.is_empty() => self.is_final_marking(marking),
+            _ => adjacency.is_dead_marking(marking),
+        }
+    }
+
+    /// Replay a sequence of labels on the net, starting in the initial marking
+    ///
+    /// For each label, a transition with this label is fired.
+    /// Silent transitions (i.e., transitions without a label) are fired as needed to enable the next labelled transition
+    /// and, after the last label, to reach an accepting marking (see [`PetriNet::is_accepting_marking`]).
+    /// In case of multiple transitions with the same label, all options are considered.
+    ///
+    /// Fails if more than `max_states` distinct markings have to be explored in one step (see [`DEFAULT_MAX_REPLAY_STATES`]),
+    /// so that it also terminates for nets with unbounded silent behavior.
+    ///
+    /// If the full sequence can be replayed, the result prefers firing sequences reaching an accepting marking
+    /// (see [`LabelReplayResult::reached_final_marking`]).
+    /// Thus, an accepting marking is reached if and only if the net accepts `labels` (see [`PetriNet::accepts_trace`]).
+    pub fn replay_labels<S: AsRef<str>>(
+        &self,
+        labels: &[S],
+        max_states: usize,
+    ) -> Result<LabelReplayResult, LabelReplayError> {
+        let mut all: Vec<TransitionID> =
+            self.transitions.keys().map(|t| TransitionID(*t)).collect();
+        all.sort();
+        let (silent, labelled): (Vec<TransitionID>, Vec<TransitionID>) = all
+            .into_iter()
+            .partition(|t| self.transitions[&t.0].label.is_none());
+        let adjacency = self.adjacency();
+        let mut states: Vec<(Marking, Vec<TransitionID>)> =
+            vec![(self.initial_marking_or_empty(), Vec::new())];
+        for (position, label) in labels.iter().enumerate() {
+            let label = label.as_ref();
+            let candidates: Vec<TransitionID> = labelled
+                .iter()
+                .filter(|t| self.transitions[&t.0].label.as_deref() == Some(label))
+                .copied()
+                .collect();
+            let mut next_states = Vec::new();
+            let mut seen: HashSet<Vec<(Uuid, u64)>> = HashSet::new();
+            for (marking, path) in silent_closure(&adjacency, states, &silent, max_states)? {
+                for t in &candidates {
+                    if let Ok(m) = adjacency.fire(&marking, *t) {
+                        if seen.insert(marking_key(&m)) {
+                            if next_states.len() >= max_states {
+                                return Err(LabelReplayError::StateLimitExceeded(max_states));
+                            }
+                            let mut path = path.clone();
+                            path.push(*t);
+                            next_states.push((m, path));
+                        }
+                    }
+                }
+            }
+            if next_states.is_empty() {
+                return Err(LabelReplayError::LabelNotReplayable {
+                    position,
+                    label: label.to_string(),
+                });
+            }
+            states = next_states;
+        }
+        let closure = silent_closure(&adjacency, states, &silent, max_states)?;
+        let (marking, firing_sequence) = closure
+            .iter()
+            .find(|(m, _)| self.is_accepting_marking_in(&adjacency, m))
+            .unwrap_or(&closure[0])
+            .clone();
+        Ok(LabelReplayResult {
+            reached_final_marking: self.is_accepting_marking_in(&adjacency, &marking),
+            firing_sequence,
+            marking,
+        })
+    }
+}
+
+/// Get all states reachable from `states` by firing only the passed silent transitions (breadth-first)
+///
+/// The passed states are included in the result (first).
+/// Fails if the result would contain more than `max_states` states.
+fn silent_closure(
+    adjacency: &PetriNetAdjacency,
+    states: Vec<(Marking, Vec<TransitionID>)>,
+    silent: &[TransitionID],
+    max_states: usize,
+) -> Result<Vec<(Marking, Vec<TransitionID>)>, LabelReplayError> {
+    let mut seen: HashSet<Vec<(Uuid, u64)>> = states.iter().map(|(m, _)| marking_key(m)).collect();
+    let mut res = states;
+    let mut i = 0;
+    while i < res.len() {
+        for t in silent {
+            if let Ok(m) = adjacency.fire(&res[i].0, *t) {
+                if seen.insert(marking_key(&m)) {
+                    if res.len() >= max_states {
+                        return Err(LabelReplayError::StateLimitExceeded(max_states));
+                    }
+                    let mut path = res[i].1.clone();
+                    path.push(*t);
+                    res.push((m, path));
+                }
+            }
+        }
+        i += 1;
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::petri_net::petri_net_struct::ArcType;
+
+    /// Net: `a` then (`b` or silent skip) then `c`, where `a` produces two tokens for `c`
+    fn sample_net() -> (PetriNet, Vec<PlaceID>, Vec<TransitionID>) {
+        let mut net = PetriNet::new();
+        let p: Vec<PlaceID> = (0..4).map(|_| net.add_place(None)).collect();
+        let a = net.add_transition(Some("a".to_string()), None);
+        let b = net.add_transition(Some("b".to_string()), None);
+        let skip = net.add_transition(None, None);
+        let c = net.add_transition(Some("c".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p[0], a), None);
+        net.add_arc(ArcType::transition_to_place(a, p[1]), Some(2));
+        net.add_arc(ArcType::place_to_transition(p[1], b), Some(2));
+        net.add_arc(ArcType::place_to_transition(p[1], skip), Some(2));
+        net.add_arc(ArcType::transition_to_place(b, p[2]), None);
+        net.add_arc(ArcType::transition_to_place(skip, p[2]), None);
+        net.add_arc(ArcType::place_to_transition(p[2], c), None);
+        net.add_arc(ArcType::transition_to_place(c, p[3]), None);
+        net.initial_marking = Some(Marking::from([(p[0], 1)]));
+        net.final_markings = Some(vec![Marking::from([(p[3], 1)])]);
+        (net, p, vec![a, b, skip, c])
+    }
+
+    #[test]
+    fn test_firing() {
+        let (net, p, t) = sample_net();
+        let m0 = net.initial_marking_or_empty();
+        assert_eq!(net.enabled_transitions(&m0), vec![t[0]]);
+        assert!(net.is_enabled(&m0, t[0]));
+        assert!(!net.is_enabled(&m0, t[1]));
+        assert_eq!(net.fire(&m0, t[1]), Err(FiringError::NotEnabled(t[1])));
+        assert!(matches!(
+            net.fire(&m0, TransitionID(Uuid::new_v4())),
+            Err(FiringError::UnknownTransition(_))
+        ));
+
+        let m1 = net.fire(&m0, t[0]).unwrap();
+        assert_eq!(m1, Marking::from([(p[1], 2)]));
+        let mut enabled = vec![t[1], t[2]];
+        enabled.sort();
+        assert_eq!(net.enabled_transitions(&m1), enabled);
+
+        let m3 = net.fire_sequence(&m0, &[t[0], t[2], t[3]]).unwrap();
+        assert!(net.is_final_marking(&m3));
+        assert!(net.covers_final_marking(&m3));
+        assert!(net.is_dead_marking(&m3));
+        assert!(!net.is_final_marking(&m1));
+        assert_eq!(
+            net.fire_sequence(&m0, &[t[0], t[3]]),
+            Err(FiringError::NotEnabled(t[3]))
+        );
+    }
+
+    #[test]
+    fn test_replay_labels() {
+        let (net, _, t) = sample_net();
+        let res = net
+            .replay_labels(&["a", "c"], DEFAULT_MAX_REPLAY_STATES)
+            .unwrap();
+        assert_eq!(res.firing_sequence, vec![t[0], t[2], t[3]]);
+        assert!(res.reached_final_marking);
+
+        let res = net
+            .replay_labels(&["a", "b", "c"], DEFAULT_MAX_REPLAY_STATES)
+            .unwrap();
+        assert_eq!(res.firing_sequence, vec![t[0], t[1], t[3]]);
+
+        let res = net
+            .replay_labels(&["a"], DEFAULT_MAX_REPLAY_STATES)
+            .unwrap();
+        assert!(!res.reached_final_marking);
+
+        assert_eq!(
+            net.replay_labels(&["a", "c", "b"], DEFAULT_MAX_REPLAY_STATES),
+            Err(LabelReplayError::LabelNotReplayable {
+                position: 2,
+                label: "b".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_inhibitor_and_reset_arcs() {
+        let mut net = PetriNet::new();
+        let p: Vec<PlaceID> = (0..3).map(|_| net.add_place(None)).collect();
+        // `produce` can only fire while `p[1]` is empty, `clear` removes all tokens from `p[1]`
+        let produce = net.add_transition(Some("produce".to_string()), None);
+        let clear = net.add_transition(Some("clear".to_string()), None);
+        net.add_arc(ArcType::place_to_transition(p[0], produce), None);
+        net.add_arc(ArcType::transition_to_place(produce, p[0]), None);
+        net.add_arc(ArcType::transition_to_place(produce, p[1]), Some(3));
+        net.add_arc(ArcType::inhibitor(p[1], produce), None);
+        net.add_arc(ArcType::place_to_transition(p[2], clear), None);
+        net.add_arc(ArcType::reset(p[1], clear), None);
+        let m0 = Marking::from([(p[0], 1), (p[2], 1)]);
+
+        let mut enabled = vec![produce, clear];
+        enabled.sort();
+        assert_eq!(net.enabled_transitions(&m0), enabled);
+        let m1 = net.fire(&m0, produce).unwrap();
+        assert_eq!(m1, Marking::from([(p[0], 1), (p[1], 3), (p[2], 1)]));
+        assert!(!net.is_enabled(&m1, produce));
+        assert_eq!(
+            net.fire(&m1, produce),
+            Err(FiringError::NotEnabled(produce))
+        );
+        let m2 = net.fire(&m1, clear).unwrap();
+        assert_eq!(m2, Marking::from([(p[0], 1)]));
+        assert!(net.is_enabled(&m2, produce));
+        // The reachability graph respects both arc types
+        assert_eq!(net.reachability_graph_from(m0, 100).states.len(), 4);
+    }
+}
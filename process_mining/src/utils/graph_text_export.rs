@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Escape a string for use in a double-quoted DOT identifier or label
+pub fn escape_dot(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => {}
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+/// Escape a string for use in a double-quoted Mermaid label
+///
+/// Characters with special meaning in Mermaid are replaced by their entity codes.
+pub fn escape_mermaid(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("#quot;"),
+            '#' => res.push_str("#35;"),
+            '<' => res.push_str("#lt;"),
+            '>' => res.push_str("#gt;"),
+            '|' => res.push_str("#124;"),
+            '\n' => res.push_str("<br>"),
+            '\r' => {}
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+/// Format a DOT attribute list (e.g., `[label="a", shape=box]`)
+///
+/// Values are used as-is, so string values need to be quoted (and escaped using [`escape_dot`]) beforehand.
+pub(crate) fn dot_attributes(attrs: &[(&str, String)]) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+    let attrs: Vec<String> = attrs.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    format!(" [{}]", attrs.join(", "))
+}
+
+/// Element (node or edge) of a [`CytoscapeGraph`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CytoscapeElement {
+    /// Group of the element (`nodes` or `edges`)
+    pub group: String,
+    /// Data of the element
+    ///
+    /// Always contains an `id`. Edges additionally contain `source` and `target`.
+    pub data: Map<String, Value>,
+    /// Classes of the element (e.g., `place` or `transition`), usable for styling
+    pub classes: Vec<String>,
+}
+
+impl CytoscapeElement {
+    /// Create a new node element with the given `id`, `label` and `classes`
+    pub fn node(id: impl Into<String>, label: impl Into<String>, classes: &[&str]) -> Self {
+        let mut data = Map::new();
+        data.insert("id".to_string(), Value::String(id.into()));
+        data.insert("label".to_string(), Value::String(label.into()));
+        Self {
+            group: "nodes".to_string(),
+            data,
+            classes: classes.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    /// Create a new edge element with the given `id`, `source`, `target` and `classes`
+    pub fn edge(
+        id: impl Into<String>,
+        source: impl Into<String>,
+        target: impl Into<String>,
+        classes: &[&str],
+    ) -> Self {
+        let mut data = Map::new();
+        data.insert("id".to_string(), Value::String(id.into()));
+        data.insert("source".to_string(), Value::String(source.into()));
+        data.insert("target".to_string(), Value::String(target.into()));
+        Self {
+            group: "edges".to_string(),
+            data,
+            classes: classes.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    /// Add an additional data entry to the element
+    pub fn with_data(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.data.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// Graph in the JSON elements format of [Cytoscape.js](https://js.cytoscape.org/#notation/elements-json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CytoscapeGraph {
+    /// All nodes and edges of the graph
+    pub elements: Vec<CytoscapeElement>,
+}
+
+impl CytoscapeGraph {
+    /// Serialize the graph to a JSON [`String`]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escape_dot("a \"b\"\nc\\"), "a \\\"b\\\"\\nc\\\\");
+        assert_eq!(
+            escape_mermaid("a \"b\" | <c>"),
+            "a #quot;b#quot; #124; #lt;c#gt;"
+        );
+        assert_eq!(
+            dot_attributes(&[("label", "\"a\"".to_string()), ("shape", "box".to_string())]),
+            " [label=\"a\", shape=box]"
+        );
+        assert_eq!(dot_attributes(&[]), "");
+    }
+}
@@ -1,11 +1,8 @@
+/// Dependency-free helpers for exporting graphs as text (DOT, Mermaid, Cytoscape.js JSON)
+pub mod graph_text_export;
 /// Helper utils regarding XML import/export
 pub mod xml_utils;
 
+/// Helpers for tests (test data and small example nets)
 #[cfg(test)]
-pub mod test_utils {
-    use std::path::PathBuf;
-
-    pub fn get_test_data_path() -> PathBuf {
-        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data")
-    }
-}
+pub mod test_utils;
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use crate::petri_net::petri_net_struct::{ArcType, PetriNet, PlaceID, TransitionID};
+
+/// Path of the `test_data` directory
+pub fn get_test_data_path() -> PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data")
+}
+
+/// Input places, label (`None` for silent transitions) and output places of a transition (see [`build_petri_net`])
+pub type TestTransition<'a> = (Vec<usize>, Option<&'a str>, Vec<usize>);
+
+///
+/// Build a [`PetriNet`] with `num_places` places and the given transitions (without markings)
+///
+/// Places are referred to by their index; a place occurring several times in the input or output places results in a single weighted arc.
+/// Returns the net, its places and its transitions (both in the given order).
+///
+pub fn build_petri_net(
+    num_places: usize,
+    transitions: &[TestTransition<'_>],
+) -> (PetriNet, Vec<PlaceID>, Vec<TransitionID>) {
+    let mut net = PetriNet::new();
+    let places: Vec<PlaceID> = (0..num_places).map(|_| net.add_place(None)).collect();
+    // Places with the number of their occurrences (in order of first occurrence)
+    let weighted = |arcs: &[usize]| {
+        let mut res: Vec<(PlaceID, u32)> = Vec::new();
+        for p in arcs {
+            match res.iter_mut().find(|(q, _)| *q == places[*p]) {
+                Some((_, w)) => *w += 1,
+                None => res.push((places[*p], 1)),
+            }
+        }
+        res
+    };
+    let transitions = transitions
+        .iter()
+        .map(|(pre, label, post)| {
+            let t = net.add_transition(label.map(String::from), None);
+            for (p, w) in weighted(pre) {
+                net.add_arc(ArcType::place_to_transition(p, t), Some(w));
+            }
+            for (p, w) in weighted(post) {
+                net.add_arc(ArcType::transition_to_place(t, p), Some(w));
+            }
+            t
+        })
+        .collect();
+    (net, places, transitions)
+}